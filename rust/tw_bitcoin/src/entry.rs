@@ -1,7 +1,10 @@
+use crate::aliases::*;
 use crate::modules::plan_builder::BitcoinPlanBuilder;
 use crate::modules::signer::Signer;
+use crate::modules::transactions::{InputClaimBuilder, MAX_STACK_SIZE};
 use crate::{Error, Result};
 use bitcoin::address::NetworkChecked;
+use bitcoin::{Script, ScriptBuf, Witness};
 use std::borrow::Cow;
 use std::fmt::Display;
 use std::str::FromStr;
@@ -59,14 +62,12 @@ impl CoinEntry for BitcoinEntry {
     #[inline]
     fn parse_address_unchecked(
         &self,
-        _coin: &dyn CoinContext,
+        coin: &dyn CoinContext,
         address: &str,
     ) -> AddressResult<Self::Address> {
         let address = bitcoin::address::Address::from_str(address)
             .map_err(|_| AddressError::FromHexError)?
-            // At this moment, we support mainnet only.
-            // This check will be removed in coming PRs.
-            .require_network(bitcoin::Network::Bitcoin)
+            .require_network(coin_network(coin))
             .map_err(|_| AddressError::InvalidInput)?;
 
         Ok(Address(address))
@@ -75,7 +76,7 @@ impl CoinEntry for BitcoinEntry {
     #[inline]
     fn derive_address(
         &self,
-        _coin: &dyn CoinContext,
+        coin: &dyn CoinContext,
         public_key: PublicKey,
         _derivation: Derivation,
         _prefix: Option<Self::AddressPrefix>,
@@ -89,7 +90,7 @@ impl CoinEntry for BitcoinEntry {
             .map_err(|_| AddressError::InvalidInput)?;
 
         let address: bitcoin::address::Address<NetworkChecked> = bitcoin::address::Address::new(
-            bitcoin::Network::Bitcoin,
+            coin_network(coin),
             bitcoin::address::Payload::PubkeyHash(pubkey.pubkey_hash()),
         );
 
@@ -137,6 +138,14 @@ impl BitcoinEntry {
         proto: Proto::SigningInput<'_>,
     ) -> Result<Proto::PreSigningOutput<'static>> {
         let proto = pre_processor(proto);
+        let network = crate::modules::network::network_from_proto(proto.network);
+
+        // UTXOs of excluded addresses are never selected automatically.
+        let proto = exclude_inputs_by_address(proto, network)?;
+
+        // Time-locked inputs (CLTV vaults and HTLC refunds) can only be spent
+        // once the lock time of the transaction has reached their expiry.
+        check_timelocked_inputs(&proto)?;
 
         // Convert input builders into Utxo inputs.
         let utxo_inputs = proto
@@ -149,21 +158,40 @@ impl BitcoinEntry {
         let mut utxo_outputs = proto
             .outputs
             .iter()
-            .map(crate::modules::transactions::OutputBuilder::utxo_from_proto)
+            .map(|output| {
+                crate::modules::transactions::OutputBuilder::utxo_from_proto_for_network(
+                    output, network,
+                )
+            })
             .collect::<Result<Vec<_>>>()?;
 
+        check_dust_outputs(&proto, &utxo_outputs)?;
+        check_data_carrier_outputs(&proto, &utxo_outputs)?;
+        check_recipient_total(&proto)?;
+
+        // A malformed runestone (cenotaph) would burn all runes of the
+        // inputs. Edicts may refer to the change output, if enabled.
+        let output_count = utxo_outputs.len() + usize::from(!proto.disable_change_output);
+        crate::modules::runes::check_outputs(
+            utxo_outputs
+                .iter()
+                .map(|output| Script::from_bytes(output.script_pubkey.as_ref())),
+            output_count,
+        )?;
+
         // If automatic change output is enabled, a change script must be provided.
-        let change_script_pubkey = if proto.disable_change_output {
-            Cow::default()
+        let (change_script_pubkey, change_label) = if proto.disable_change_output {
+            (Cow::default(), Cow::default())
         } else {
             // Convert output builder to Utxo output.
-            let output = crate::modules::transactions::OutputBuilder::utxo_from_proto(
+            let output = crate::modules::transactions::OutputBuilder::utxo_from_proto_for_network(
                 &proto
                     .change_output
                     .ok_or_else(|| Error::from(Proto::Error::Error_invalid_change_output))?,
+                network,
             )?;
 
-            output.script_pubkey
+            (output.script_pubkey, output.label)
         };
 
         // Prepare SigningInput for Utxo sighash generation.
@@ -180,29 +208,81 @@ impl BitcoinEntry {
                 .collect(),
             input_selector: proto.input_selector,
             weight_base: proto.fee_per_vb,
-            change_script_pubkey,
+            change_script_pubkey: change_script_pubkey.clone(),
             disable_change_output: proto.disable_change_output,
+            min_change: proto.min_change,
+            min_change_amount: proto.min_change_amount,
+            target_change: proto.target_change,
+            include_preimages: proto.include_preimages,
+            bip69_sort_outputs: proto.bip69_sort_outputs,
         };
 
         // Generate the sighashes to be signed.
         let utxo_presigning = tw_utxo::compiler::Compiler::preimage_hashes(utxo_signing);
         handle_utxo_error(&utxo_presigning.error)?;
 
-        // If a change output was created by the Utxo compiler, we return it here too.
+        // Taproot sighashes commit to the amounts and scripts of all prevouts,
+        // so those must be provided for every selected input.
+        check_taproot_prevouts(&utxo_presigning.inputs)?;
+
+        // Report the public key(s) expected to sign each of the selected inputs.
+        let signing_keys = selected_input_indices(&proto.inputs, &utxo_presigning.inputs)?
+            .into_iter()
+            .map(|index| {
+                crate::modules::transactions::InputBuilder::signing_keys_from_proto(
+                    &proto.inputs[index],
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // If a change output was created by the Utxo compiler, we return it here
+        // too. Note that the change output is not necessarily the last output
+        // if the outputs are sorted.
         if utxo_presigning.outputs.len() == utxo_outputs.len() + 1 {
-            let change_output = utxo_presigning
+            let total_output: u64 = utxo_outputs.iter().map(|output| output.value).sum();
+            let change_amount = utxo_presigning
                 .outputs
-                .last()
-                .expect("expected change output");
+                .iter()
+                .map(|output| output.value)
+                .sum::<u64>()
+                - total_output;
 
             utxo_outputs.push(Proto::mod_PreSigningOutput::TxOut {
-                value: change_output.value,
-                script_pubkey: change_output.script_pubkey.to_vec().into(),
+                value: change_amount,
+                script_pubkey: change_script_pubkey.to_vec().into(),
                 control_block: Default::default(),
                 taproot_payload: Default::default(),
+                label: change_label.to_string().into(),
             })
         }
 
+        // Apply the same (stable) sort as the Utxo compiler, so that the
+        // outputs match the outputs the sighashes commit to.
+        if proto.bip69_sort_outputs {
+            sort_outputs_bip69(&mut utxo_outputs);
+        }
+
+        let total_input: u64 = utxo_presigning.inputs.iter().map(|input| input.value).sum();
+        let total_output: u64 = utxo_outputs.iter().map(|output| output.value).sum();
+
+        // The input selection guarantees that the inputs cover the outputs.
+        if total_output > total_input {
+            return Err(Error::from(Proto::Error::Error_utxo_insufficient_inputs));
+        }
+
+        // Any remainder that is not returned as change goes to the miner fee.
+        let (effective_fee, effective_fee_rate) =
+            effective_fee(total_input, total_output, utxo_presigning.weight_estimate);
+
+        // Report the expected confirmation time of the fee rate, if known.
+        let confirmation_bucket =
+            crate::modules::fee::confirmation_bucket(&proto.confirmation_buckets, proto.fee_per_vb)
+                .map(|bucket| Proto::ConfirmationBucket {
+                    min_fee_per_vb: bucket.min_fee_per_vb,
+                    blocks: bucket.blocks,
+                    label: bucket.label.to_string().into(),
+                });
+
         Ok(Proto::PreSigningOutput {
             error: Proto::Error::OK,
             error_message: Default::default(),
@@ -213,6 +293,14 @@ impl BitcoinEntry {
             utxo_outputs,
             weight_estimate: utxo_presigning.weight_estimate,
             fee_estimate: utxo_presigning.fee_estimate,
+            donated_change: utxo_presigning.donated_change,
+            signing_keys,
+            effective_fee,
+            effective_fee_rate,
+            total_input,
+            total_output,
+            confirmation_bucket,
+            change_amount: utxo_presigning.change_amount,
         })
     }
 
@@ -232,24 +320,49 @@ impl BitcoinEntry {
             ));
         }
 
+        let max_stack_items = if proto.max_witness_stack_items != 0 {
+            proto.max_witness_stack_items as usize
+        } else {
+            crate::modules::transactions::MAX_STANDARD_P2WSH_STACK_ITEMS
+        };
+
+        // A larger witness could never be valid, regardless of the policy.
+        if max_stack_items > MAX_STACK_SIZE {
+            return Err(
+                Error::from(Proto::Error::Error_too_many_witness_stack_items).with_context(format!(
+                    "{max_stack_items} witness stack items exceed the consensus limit of {MAX_STACK_SIZE}"
+                )),
+            );
+        }
+
         // Generate claims for all the inputs.
         let mut utxo_input_claims: Vec<UtxoProto::TxInClaim> = vec![];
         for (input, signature) in proto.inputs.iter().zip(signatures.into_iter()) {
-            let utxo_claim =
-                crate::modules::transactions::InputClaimBuilder::utxo_claim_from_proto(
-                    input, signature,
-                )?;
+            let utxo_claim = InputClaimBuilder::utxo_claim_from_proto_with_max_stack_items(
+                input,
+                signature,
+                max_stack_items,
+            )?;
             utxo_input_claims.push(utxo_claim);
         }
 
         // Process all the outputs.
         let mut utxo_outputs = vec![];
-        for output in proto.outputs {
-            let utxo = crate::modules::transactions::OutputBuilder::utxo_from_proto(&output)?;
+        for output in &proto.outputs {
+            let utxo = crate::modules::transactions::OutputBuilder::utxo_from_proto_for_network(
+                output,
+                crate::modules::network::network_from_proto(proto.network),
+            )?;
 
             utxo_outputs.push(utxo);
         }
 
+        check_data_carrier_outputs(&proto, &utxo_outputs)?;
+
+        if proto.bip69_sort_outputs {
+            sort_outputs_bip69(&mut utxo_outputs);
+        }
+
         // Prepare PreSerialization input for Utxo compiler.
         let utxo_preserializtion = UtxoProto::PreSerialization {
             version: proto.version,
@@ -270,9 +383,18 @@ impl BitcoinEntry {
         let utxo_serialized = tw_utxo::compiler::Compiler::compile(utxo_preserializtion);
         handle_utxo_error(&utxo_serialized.error)?;
 
+        let (effective_fee, effective_fee_rate) = effective_fee(
+            proto.inputs.iter().map(|input| input.value).sum(),
+            utxo_outputs.iter().map(|output| output.value).sum(),
+            utxo_serialized.weight,
+        );
+
         // Prepare `Proto::TransactionInput` protobufs for signing output.
         let mut proto_inputs = vec![];
+        let mut input_sizes = vec![];
         for input in utxo_input_claims {
+            input_sizes.push(input_size(&input));
+
             proto_inputs.push(Proto::TransactionInput {
                 txid: Cow::Owned(input.txid.to_vec()),
                 vout: input.vout,
@@ -294,6 +416,7 @@ impl BitcoinEntry {
                 value: output.value,
                 taproot_payload: output.taproot_payload,
                 control_block: output.control_block,
+                label: output.label,
             });
         }
 
@@ -314,10 +437,124 @@ impl BitcoinEntry {
             txid: utxo_serialized.txid,
             weight: utxo_serialized.weight,
             fee: utxo_serialized.fee,
+            wtxid: utxo_serialized.wtxid,
+            effective_fee,
+            effective_fee_rate,
+            input_sizes,
+            ..Default::default()
         })
     }
 }
 
+// Returns the actual scriptSig and witness sizes of the given (claimed) input.
+fn input_size(claim: &UtxoProto::TxInClaim<'_>) -> Proto::mod_SigningOutput::InputSize {
+    let witness = if claim.witness_items.is_empty() {
+        0
+    } else {
+        let witness = Witness::from_slice(&claim.witness_items);
+        bitcoin::consensus::serialize(&witness).len() as u64
+    };
+
+    Proto::mod_SigningOutput::InputSize {
+        script_sig: claim.script_sig.len() as u64,
+        witness,
+    }
+}
+
+// Rejects outputs below their dust threshold, which is either set per output
+// or globally, or derived from the script type at the dust relay fee rate (if
+// set). OP_RETURN outputs carry data and are usually of zero value.
+fn check_dust_outputs(
+    proto: &Proto::SigningInput<'_>,
+    utxo_outputs: &[Proto::mod_PreSigningOutput::TxOut<'_>],
+) -> Result<()> {
+    for (index, (output, utxo)) in proto.outputs.iter().zip(utxo_outputs).enumerate() {
+        let script_pubkey = Script::from_bytes(utxo.script_pubkey.as_ref());
+
+        let threshold = if output.dust_threshold != 0 {
+            output.dust_threshold
+        } else if proto.dust_threshold != 0 {
+            proto.dust_threshold
+        } else {
+            crate::modules::standardness::dust_threshold(script_pubkey, proto.dust_relay_feerate)
+        };
+
+        if threshold == 0 || script_pubkey.is_op_return() {
+            continue;
+        }
+
+        if utxo.value < threshold {
+            return Err(
+                Error::from(Proto::Error::Error_dust_output).with_context(format!(
+                    "output {index} of {} sats is below the dust threshold of {threshold} sats",
+                    utxo.value
+                )),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Data carrier outputs must be standard in order to be relayed, with their
+// payload size limited by the configured (or default) data carrier size.
+fn check_data_carrier_outputs(
+    proto: &Proto::SigningInput<'_>,
+    utxo_outputs: &[Proto::mod_PreSigningOutput::TxOut<'_>],
+) -> Result<()> {
+    let data_carrier_size = proto.data_carrier_size.as_ref().map_or(
+        crate::modules::standardness::DEFAULT_DATA_CARRIER_SIZE,
+        |size| size.value as usize,
+    );
+
+    for (output, utxo) in proto.outputs.iter().zip(utxo_outputs) {
+        let is_data_carrier = matches!(
+            &output.to_recipient,
+            ProtoOutputRecipient::builder(builder)
+                if matches!(builder.variant, ProtoOutputBuilder::op_return(_))
+        );
+
+        if is_data_carrier {
+            crate::modules::standardness::check_output_script_with_data_carrier_size(
+                Script::from_bytes(utxo.script_pubkey.as_ref()),
+                data_carrier_size,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// Returns the network of the coin, based on its HRP. Mainnet by default.
+fn coin_network(coin: &dyn CoinContext) -> bitcoin::Network {
+    coin.hrp()
+        .and_then(|hrp| crate::modules::network::network_from_hrp(&hrp))
+        .unwrap_or(bitcoin::Network::Bitcoin)
+}
+
+// Sorts the outputs according to BIP-69, by ascending value and then by
+// scriptPubkey in lexicographic order.
+fn sort_outputs_bip69(outputs: &mut [Proto::mod_PreSigningOutput::TxOut<'_>]) {
+    outputs.sort_by(|a, b| {
+        (a.value, a.script_pubkey.as_ref()).cmp(&(b.value, b.script_pubkey.as_ref()))
+    });
+}
+
+// Returns the actual fee (total input amount minus total output amount) and
+// the resulting fee rate in sat/vbyte for the given transaction weight.
+fn effective_fee(total_input: u64, total_output: u64, weight: u64) -> (u64, f64) {
+    let fee = total_input.saturating_sub(total_output);
+
+    let vsize = (weight + 3) / 4;
+    let fee_rate = if vsize == 0 {
+        0.0
+    } else {
+        fee as f64 / vsize as f64
+    };
+
+    (fee, fee_rate)
+}
+
 // Convenience function for pre-processing of certain fields that must be
 // executed on each `CoinEntry` call.
 pub(crate) fn pre_processor(mut proto: Proto::SigningInput<'_>) -> Proto::SigningInput<'_> {
@@ -335,9 +572,212 @@ pub(crate) fn pre_processor(mut proto: Proto::SigningInput<'_>) -> Proto::Signin
         }
     });
 
+    // Detect the spend path of `auto` inputs. Inputs that can not be resolved
+    // are rejected by the input builder.
+    let private_key = proto.private_key.clone();
+    proto.inputs.iter_mut().for_each(|txin| {
+        if let ProtoInputRecipient::builder(builder) = &mut txin.to_recipient {
+            if let ProtoInputBuilder::auto(auto) = &builder.variant {
+                if let Some(resolved) =
+                    crate::modules::transactions::InputBuilder::resolve_auto(auto, &private_key)
+                {
+                    builder.variant = resolved;
+                }
+            }
+        }
+    });
+
     proto
 }
 
+// Convenience function: checks that the lock time and sequences of the
+// signing input satisfy all CLTV vault and HTLC refund inputs.
+fn check_timelocked_inputs(proto: &Proto::SigningInput<'_>) -> Result<()> {
+    let lock_time = crate::modules::transactions::lock_time_from_proto(&proto.lock_time)?;
+
+    for input in &proto.inputs {
+        let ProtoInputRecipient::builder(builder) = &input.to_recipient else {
+            continue;
+        };
+
+        match &builder.variant {
+            ProtoInputBuilder::cltv_vault(vault) => {
+                let pubkey = bitcoin::PublicKey::from_slice(vault.public_key.as_ref())?;
+                crate::modules::transactions::CltvVault::new(vault.expiry, pubkey)
+                    .check_spend(lock_time, input.sequence)?;
+            },
+            ProtoInputBuilder::htlc_refund(refund) => {
+                crate::modules::transactions::Htlc::from_proto(refund)?
+                    .check_refund(lock_time, input.sequence)?;
+            },
+            _ => continue,
+        }
+    }
+
+    Ok(())
+}
+
+// Convenience function: checks that the amount and script of every prevout is
+// available if any input uses a Taproot sighash that commits to all of them.
+fn check_taproot_prevouts(inputs: &[UtxoProto::TxIn<'_>]) -> Result<()> {
+    let commits_to_all_prevouts = inputs.iter().any(|input| {
+        input.signing_method == UtxoProto::SigningMethod::TaprootAll
+            && !matches!(
+                input.sighash_type,
+                UtxoProto::SighashType::AllPlusAnyoneCanPay
+                    | UtxoProto::SighashType::NonePlusAnyoneCanPay
+                    | UtxoProto::SighashType::SinglePlusAnyoneCanPay
+            )
+    });
+
+    if !commits_to_all_prevouts {
+        return Ok(());
+    }
+
+    for input in inputs {
+        if input.value == 0 || input.script_pubkey.is_empty() {
+            // The txid is stored in reversed order, the conventional
+            // representation is non-reversed.
+            let txid: Vec<u8> = input.txid.iter().copied().rev().collect();
+
+            return Err(
+                Error::from(Proto::Error::Error_missing_taproot_prevout).with_context(format!(
+                    "all prevouts must be provided for Taproot inputs, missing {}:{}",
+                    tw_encoding::hex::encode(txid, false),
+                    input.vout
+                )),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Compares the total of the (non-change) outputs with the total declared by
+// the caller, if any.
+fn check_recipient_total(proto: &Proto::SigningInput<'_>) -> Result<()> {
+    if proto.expected_recipient_total == 0 {
+        return Ok(());
+    }
+
+    let total = proto
+        .outputs
+        .iter()
+        .try_fold(0u64, |total, output| total.checked_add(output.value))
+        .ok_or_else(|| Error::from(Proto::Error::Error_utxo_amount_overflow))?;
+
+    if total != proto.expected_recipient_total {
+        return Err(
+            Error::from(Proto::Error::Error_recipient_total_mismatch).with_context(format!(
+                "outputs total {total} sats, but {} sats are expected",
+                proto.expected_recipient_total
+            )),
+        );
+    }
+
+    Ok(())
+}
+
+// Removes the inputs that spend from one of the `excluded_addresses`, unless
+// all inputs are used anyway.
+fn exclude_inputs_by_address(
+    mut proto: Proto::SigningInput<'_>,
+    network: bitcoin::Network,
+) -> Result<Proto::SigningInput<'_>> {
+    if proto.excluded_addresses.is_empty()
+        || proto.input_selector == UtxoProto::InputSelector::UseAll
+    {
+        return Ok(proto);
+    }
+
+    let excluded = proto
+        .excluded_addresses
+        .iter()
+        .map(|addr| {
+            bitcoin::Address::from_str(addr)
+                .ok()
+                .and_then(|addr| addr.require_network(network).ok())
+                .map(|addr| addr.script_pubkey())
+                .ok_or_else(|| {
+                    Error::from(Proto::Error::Error_bad_address_recipient)
+                        .with_context(format!("invalid excluded address {addr}"))
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut inputs = Vec::with_capacity(proto.inputs.len());
+    for input in proto.inputs {
+        let utxo = crate::modules::transactions::InputBuilder::utxo_from_proto(&input)?;
+
+        match prevout_script_pubkey(&input, &utxo) {
+            Some(script) if excluded.contains(&script) => continue,
+            _ => inputs.push(input),
+        }
+    }
+
+    proto.inputs = inputs;
+    Ok(proto)
+}
+
+// Convenience function: returns the scriptPubkey of the output spent by the
+// given input, if it can be derived. Note that for some spend types, the Utxo
+// input carries the script code or the redeem script instead.
+fn prevout_script_pubkey(
+    input: &Proto::Input<'_>,
+    utxo: &UtxoProto::TxIn<'_>,
+) -> Option<ScriptBuf> {
+    let script = Script::from_bytes(utxo.script_pubkey.as_ref());
+
+    let ProtoInputRecipient::builder(builder) = &input.to_recipient else {
+        return Some(script.to_owned());
+    };
+
+    match &builder.variant {
+        ProtoInputBuilder::p2pkh(_)
+        | ProtoInputBuilder::p2pk(_)
+        | ProtoInputBuilder::p2tr_key_path(_)
+        | ProtoInputBuilder::p2tr_pay_to_contract(_)
+        | ProtoInputBuilder::p2tr_untweaked_key_path(_)
+        | ProtoInputBuilder::p2tr_script_path(_) => Some(script.to_owned()),
+        ProtoInputBuilder::p2wpkh(pubkey) => {
+            let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref()).ok()?;
+            Some(ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash()?))
+        },
+        ProtoInputBuilder::p2sh(_) => Some(ScriptBuf::new_p2sh(&script.script_hash())),
+        ProtoInputBuilder::p2wsh(_)
+        | ProtoInputBuilder::cltv_vault(_)
+        | ProtoInputBuilder::htlc_refund(_) => {
+            Some(ScriptBuf::new_v0_p2wsh(&script.wscript_hash()))
+        },
+        _ => None,
+    }
+}
+
+// Convenience function: returns the indices of the given inputs that were
+// selected by the Utxo compiler, in the selected order.
+pub(crate) fn selected_input_indices(
+    inputs: &[Proto::Input<'_>],
+    utxo_inputs: &[UtxoProto::TxIn<'_>],
+) -> Result<Vec<usize>> {
+    let mut taken = vec![false; inputs.len()];
+    let mut indices = Vec::with_capacity(utxo_inputs.len());
+
+    for utxo in utxo_inputs {
+        let index = inputs
+            .iter()
+            .enumerate()
+            .position(|(index, input)| {
+                !taken[index] && input.txid == utxo.txid && input.vout == utxo.vout
+            })
+            .ok_or_else(|| Error::from(Proto::Error::Error_unmatched_input_signature_count))?;
+
+        taken[index] = true;
+        indices.push(index);
+    }
+
+    Ok(indices)
+}
+
 #[rustfmt::skip]
 /// Convert `Utxo.proto` error type to `BitcoinV2.proto` error type.
 fn handle_utxo_error(utxo_err: &UtxoProto::Error) -> Result<()> {
@@ -352,6 +792,11 @@ fn handle_utxo_error(utxo_err: &UtxoProto::Error) -> Result<()> {
         UtxoProto::Error::Error_failed_encoding => Proto::Error::Error_utxo_failed_encoding,
         UtxoProto::Error::Error_insufficient_inputs => Proto::Error::Error_utxo_insufficient_inputs,
         UtxoProto::Error::Error_missing_change_script_pubkey => Proto::Error::Error_utxo_missing_change_script_pubkey,
+        UtxoProto::Error::Error_zero_value_output => Proto::Error::Error_utxo_zero_value_output,
+        UtxoProto::Error::Error_amount_overflow => Proto::Error::Error_utxo_amount_overflow,
+        UtxoProto::Error::Error_sighash_single_missing_output => Proto::Error::Error_utxo_sighash_single_missing_output,
+        UtxoProto::Error::Error_min_change_not_reached => Proto::Error::Error_utxo_min_change_not_reached,
+        UtxoProto::Error::Error_missing_target_change => Proto::Error::Error_utxo_missing_target_change,
     };
 
     Err(Error::from(bitcoin_err))
@@ -14,18 +14,29 @@ use tw_proto::BitcoinV2::Proto;
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
-pub struct Error(Proto::Error);
+pub struct Error(Proto::Error, Option<String>);
+
+impl Error {
+    /// Attaches a human-readable context to the error (such as the affected
+    /// outpoint), which is returned as part of the error message.
+    pub fn with_context(self, context: impl Into<String>) -> Self {
+        Error(self.0, Some(context.into()))
+    }
+}
 
 // TODO: We can improve this.
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.0)
+        match &self.1 {
+            Some(context) => write!(f, "{:?}: {}", self.0, context),
+            None => write!(f, "{:?}", self.0),
+        }
     }
 }
 
 impl From<Proto::Error> for Error {
     fn from(value: Proto::Error) -> Self {
-        Error(value)
+        Error(value, None)
     }
 }
 
@@ -0,0 +1,135 @@
+use crate::{Error, Result};
+use bitcoin::address::{Address, NetworkUnchecked, Payload, WitnessVersion};
+use bitcoin::{Network, PublicKey, Script};
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+/// The script type an address pays to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptType {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+    /// A witness program of an unknown (future) version or length.
+    UnknownWitness,
+}
+
+/// How a multisig redeem script is paid to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultisigWrap {
+    /// Legacy P2SH, `3...` on mainnet.
+    P2sh,
+    /// Native Segwit P2WSH, `bc1q...` on mainnet.
+    P2wsh,
+    /// P2WSH nested in P2SH, `3...` on mainnet.
+    P2shP2wsh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressInfo {
+    pub script_type: ScriptType,
+    /// The network the address belongs to. Note that Testnet and Signet
+    /// addresses are indistinguishable and reported as Testnet.
+    pub network: Network,
+    /// The witness version, if the address is a Segwit or Taproot address.
+    pub witness_version: Option<u8>,
+    /// The (public key or script) hash, the witness program or the x-only
+    /// public key (Taproot), respectively.
+    pub program: Vec<u8>,
+}
+
+/// Decodes the given address and reports its script type, network, witness
+/// version and program.
+pub fn inspect(addr: &str) -> Result<AddressInfo> {
+    let addr = Address::<NetworkUnchecked>::from_str(addr)
+        .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
+
+    let network = addr.network;
+
+    let info = match addr.payload {
+        Payload::PubkeyHash(pubkey_hash) => AddressInfo {
+            script_type: ScriptType::P2pkh,
+            network,
+            witness_version: None,
+            program: pubkey_hash.to_vec(),
+        },
+        Payload::ScriptHash(script_hash) => AddressInfo {
+            script_type: ScriptType::P2sh,
+            network,
+            witness_version: None,
+            program: script_hash.to_vec(),
+        },
+        Payload::WitnessProgram(program) => {
+            let version = program.version();
+            let program = program.program().as_bytes().to_vec();
+
+            let script_type = match (version, program.len()) {
+                (WitnessVersion::V0, 20) => ScriptType::P2wpkh,
+                (WitnessVersion::V0, 32) => ScriptType::P2wsh,
+                (WitnessVersion::V1, 32) => ScriptType::P2tr,
+                _ => ScriptType::UnknownWitness,
+            };
+
+            AddressInfo {
+                script_type,
+                network,
+                witness_version: Some(version.to_num()),
+                program,
+            }
+        },
+        _ => {
+            return Err(Error::from(
+                Proto::Error::Error_unsupported_address_recipient,
+            ))
+        },
+    };
+
+    Ok(info)
+}
+
+/// Encodes the P2WPKH address of the given (compressed) public key for the
+/// given network, e.g. `bc1q...` for mainnet or `tb1q...` for Testnet/Signet.
+pub fn p2wpkh_address(pubkey: &[u8], network: Network) -> Result<String> {
+    let pubkey = PublicKey::from_slice(pubkey)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+    let addr = Address::p2wpkh(&pubkey, network)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+    Ok(addr.to_string())
+}
+
+/// Encodes the address of a multisig wallet with the given redeem (or witness)
+/// script for the given network, such as `OP_2 <pubkey_1> <pubkey_2>
+/// <pubkey_3> OP_3 OP_CHECKMULTISIG`. The script is hashed as required by
+/// `wrap`, i.e. HASH160 for P2SH and SHA256 for P2WSH.
+pub fn multisig_address(
+    redeem_script: &Script,
+    wrap: MultisigWrap,
+    network: Network,
+) -> Result<String> {
+    let addr = match wrap {
+        // Fails if the script exceeds the maximum push size of 520 bytes.
+        MultisigWrap::P2sh => Address::p2sh(redeem_script, network)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_redeem_script))?,
+        MultisigWrap::P2wsh => Address::p2wsh(redeem_script, network),
+        MultisigWrap::P2shP2wsh => Address::p2shwsh(redeem_script, network),
+    };
+
+    Ok(addr.to_string())
+}
+
+/// Encodes the address that the given scriptPubkey pays to, along with the
+/// network the address was encoded for. Since a scriptPubkey does not carry
+/// any network information, this is a best guess which always defaults to
+/// mainnet; use [`Address::from_script`] if the network is known.
+pub fn script_to_any_address(script: &Script) -> Result<(String, Network)> {
+    let network = Network::Bitcoin;
+
+    let addr = Address::from_script(script, network)
+        .map_err(|_| Error::from(Proto::Error::Error_unsupported_address_recipient))?;
+
+    Ok((addr.to_string(), network))
+}
@@ -0,0 +1,50 @@
+use crate::modules::standardness::TRUC_VERSION;
+use crate::{Error, Result};
+use bitcoin::opcodes::all::OP_PUSHNUM_1;
+use bitcoin::{Script, ScriptBuf, Transaction, TxOut};
+use tw_proto::BitcoinV2::Proto;
+
+/// The witness program of the "Pay to Anchor" (P2A) output.
+const P2A_PROGRAM: [u8; 2] = [0x4e, 0x73];
+
+/// The dust threshold of a P2A output at the default dust relay fee rate.
+pub const P2A_DUST_THRESHOLD: u64 = 240;
+
+/// Returns the keyless "Pay to Anchor" (P2A) script, `OP_1 <0x4e73>`, which
+/// can be spent by anyone without a witness.
+pub fn p2a_script() -> ScriptBuf {
+    ScriptBuf::builder()
+        .push_opcode(OP_PUSHNUM_1)
+        .push_slice(P2A_PROGRAM)
+        .into_script()
+}
+
+pub fn is_anchor(script: &Script) -> bool {
+    script == p2a_script().as_script()
+}
+
+/// Appends a P2A anchor output with the dust threshold as value to the given
+/// TRUC (version 3) transaction, so it can be fee-bumped by its single
+/// allowed child via CPFP.
+pub fn append_anchor(tx: &mut Transaction) -> Result<()> {
+    if tx.version != TRUC_VERSION {
+        return Err(Error::from(Proto::Error::Error_non_standard_truc)
+            .with_context("anchors require a version 3 transaction"));
+    }
+
+    if tx
+        .output
+        .iter()
+        .any(|output| is_anchor(&output.script_pubkey))
+    {
+        return Err(Error::from(Proto::Error::Error_non_standard_truc)
+            .with_context("the transaction already has an anchor output"));
+    }
+
+    tx.output.push(TxOut {
+        value: P2A_DUST_THRESHOLD,
+        script_pubkey: p2a_script(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use crate::{Error, Result};
+use bitcoin::absolute::LOCK_TIME_THRESHOLD;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// The sequence that enables the lock time, but does not signal RBF.
+pub const SEQUENCE_NO_RBF: u32 = 0xFFFF_FFFE;
+/// The sequence that enables the lock time and signals RBF (BIP-125).
+pub const SEQUENCE_RBF: u32 = 0xFFFF_FFFD;
+
+/// Enables anti-fee-sniping for the given signing input by setting the lock
+/// time to the current block height, so that the transaction can not be mined
+/// in a reorganized, earlier block.
+///
+/// The lock time is only enforced if at least one input has a non-final
+/// sequence, so the sequence of the input at `input_index` is set to the given
+/// `sequence`, such as [`SEQUENCE_NO_RBF`] or [`SEQUENCE_RBF`]. Note that this
+/// input must be spent by the transaction, for example by selecting the
+/// inputs with `InputSelector::UseAll`.
+pub fn enable(
+    signing: &mut Proto::SigningInput<'_>,
+    current_height: u32,
+    input_index: usize,
+    sequence: u32,
+) -> Result<()> {
+    if current_height >= LOCK_TIME_THRESHOLD {
+        return Err(Error::from(Proto::Error::Error_utxo_invalid_lock_time));
+    }
+
+    if sequence == u32::MAX {
+        return Err(
+            Error::from(Proto::Error::Error_invalid_anti_fee_sniping_input)
+                .with_context("a final sequence does not enable the lock time"),
+        );
+    }
+
+    let inputs = signing.inputs.len();
+    let input = signing.inputs.get_mut(input_index).ok_or_else(|| {
+        Error::from(Proto::Error::Error_invalid_anti_fee_sniping_input).with_context(format!(
+            "input index {input_index} is out of range of {inputs} inputs"
+        ))
+    })?;
+
+    input.sequence = sequence;
+    // Otherwise, a sequence of zero is interpreted as the final sequence.
+    input.sequence_enable_zero = sequence == 0;
+
+    signing.lock_time = Some(UtxoProto::LockTime {
+        variant: UtxoProto::mod_LockTime::OneOfvariant::blocks(current_height),
+    });
+
+    Ok(())
+}
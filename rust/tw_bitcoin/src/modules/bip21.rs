@@ -0,0 +1,172 @@
+use crate::{Error, Result};
+use bitcoin::address::{Address, NetworkUnchecked};
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+/// The number of decimal places of a BTC amount.
+const BTC_DECIMALS: usize = 8;
+
+/// A payment request of a BIP-21 URI, such as
+/// `bitcoin:bc1q...?amount=0.001&label=Alice`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequest {
+    /// The (validated) address, as provided.
+    pub address: String,
+    /// The requested amount in satoshis.
+    pub amount: Option<u64>,
+    pub label: Option<String>,
+    pub message: Option<String>,
+}
+
+/// Parses a BIP-21 payment URI. The scheme is case-insensitive, the `label` and
+/// `message` parameters are percent-decoded. Unknown parameters are ignored,
+/// unless they start with `req-` (required) as specified by BIP-21.
+pub fn parse(uri: &str) -> Result<PaymentRequest> {
+    let (scheme, rest) = uri
+        .split_once(':')
+        .ok_or_else(|| invalid_uri("missing bitcoin: scheme"))?;
+    if !scheme.eq_ignore_ascii_case("bitcoin") {
+        return Err(invalid_uri(format!("unsupported scheme {scheme}")));
+    }
+
+    let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    Address::<NetworkUnchecked>::from_str(address).map_err(|_| {
+        Error::from(Proto::Error::Error_bad_address_recipient)
+            .with_context(format!("invalid address {address}"))
+    })?;
+
+    let mut request = PaymentRequest {
+        address: address.to_string(),
+        amount: None,
+        label: None,
+        message: None,
+    };
+
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        let (key, value) = param.split_once('=').unwrap_or((param, ""));
+
+        let field = match key {
+            "amount" => {
+                if request.amount.is_some() {
+                    return Err(invalid_uri("duplicate amount"));
+                }
+                request.amount = Some(parse_btc_amount(value)?);
+                continue;
+            },
+            "label" => &mut request.label,
+            "message" => &mut request.message,
+            _ if key.starts_with("req-") => {
+                return Err(invalid_uri(format!("unsupported required parameter {key}")));
+            },
+            _ => continue,
+        };
+
+        if field.is_some() {
+            return Err(invalid_uri(format!("duplicate {key}")));
+        }
+        *field = Some(percent_decode(value)?);
+    }
+
+    Ok(request)
+}
+
+/// Builds a BIP-21 payment URI, with the amount as decimal BTC string (e.g.
+/// `0.001` for 100000 satoshis) and the percent-encoded label and message.
+/// The inverse of [`parse`].
+pub fn build(
+    address: &str,
+    amount_sats: u64,
+    label: Option<&str>,
+    message: Option<&str>,
+) -> String {
+    let mut uri = format!(
+        "bitcoin:{address}?amount={}",
+        format_btc_amount(amount_sats)
+    );
+
+    if let Some(label) = label {
+        uri.push_str("&label=");
+        uri.push_str(&percent_encode(label));
+    }
+    if let Some(message) = message {
+        uri.push_str("&message=");
+        uri.push_str(&percent_encode(message));
+    }
+
+    uri
+}
+
+// Converts a decimal BTC amount, such as `0.001`, to satoshis without any
+// floating point arithmetic.
+fn parse_btc_amount(amount: &str) -> Result<u64> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+    let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty())
+        || !is_digits(whole)
+        || !is_digits(fraction)
+        || fraction.len() > BTC_DECIMALS
+    {
+        return Err(invalid_uri(format!("invalid amount {amount}")));
+    }
+
+    // Pad the fraction to eight digits, e.g. `001` to `00100000`.
+    let satoshis = format!("{whole}{fraction:0<width$}", width = BTC_DECIMALS);
+
+    satoshis
+        .parse::<u64>()
+        .map_err(|_| invalid_uri(format!("amount {amount} is out of range")))
+}
+
+// Formats the satoshis as decimal BTC amount, without trailing zeros.
+fn format_btc_amount(satoshis: u64) -> String {
+    let unit = 10u64.pow(BTC_DECIMALS as u32);
+    let (whole, fraction) = (satoshis / unit, satoshis % unit);
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{fraction:0>width$}", width = BTC_DECIMALS);
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+// Percent-encodes all but the unreserved characters (RFC 3986).
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            },
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> Result<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.bytes();
+
+    while let Some(byte) = rest.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let hex: Vec<u8> = rest.by_ref().take(2).collect();
+        let decoded = std::str::from_utf8(&hex)
+            .ok()
+            .filter(|hex| hex.len() == 2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| invalid_uri("invalid percent-encoding"))?;
+        bytes.push(decoded);
+    }
+
+    String::from_utf8(bytes).map_err(|_| invalid_uri("invalid UTF-8"))
+}
+
+fn invalid_uri(context: impl Into<String>) -> Error {
+    Error::from(Proto::Error::Error_invalid_payment_uri).with_context(context)
+}
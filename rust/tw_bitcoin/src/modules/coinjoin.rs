@@ -0,0 +1,158 @@
+use crate::aliases::*;
+use crate::modules::consolidate::{INPUT_BASE_WEIGHT, TX_OVERHEAD_WEIGHT};
+use crate::modules::standardness::{dust_threshold, DEFAULT_DUST_RELAY_FEERATE};
+use crate::modules::transactions::InputBuilder;
+use crate::{Error, Result};
+use bitcoin::ScriptBuf;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// A participant of a CoinJoin round.
+#[derive(Debug, Clone)]
+pub struct Participant<'a> {
+    /// The UTXOs contributed by the participant.
+    pub inputs: Vec<Proto::Input<'a>>,
+    /// The scriptPubkey of the participant's denomination output.
+    pub output_script: ScriptBuf,
+    /// The scriptPubkey of the participant's change output.
+    pub change_script: ScriptBuf,
+}
+
+/// Creates the signing input of a CoinJoin round, with one output of exactly
+/// `denomination` satoshis per participant plus the change outputs of the
+/// participants. Each participant pays the fee (at `fee_rate`, in sat/vbyte) of
+/// its own inputs and outputs plus an equal share of the transaction overhead.
+/// Change below the dust threshold is donated to the miner fee.
+///
+/// The inputs and outputs are sorted according to BIP-69, so that their
+/// position does not reveal which participant they belong to.
+///
+/// Fails if a change amount equals the denomination, since it could not be
+/// told apart from the denomination outputs. Each participant signs (and
+/// verifies) its own inputs only, so the private keys are not set.
+pub fn build<'a>(
+    participants: Vec<Participant<'a>>,
+    denomination: u64,
+    fee_rate: u64,
+) -> Result<Proto::SigningInput<'a>> {
+    if participants.is_empty() {
+        return Err(Error::from(Proto::Error::Error_missing_recipient));
+    }
+
+    let overhead_fee = (TX_OVERHEAD_WEIGHT + 3) / 4 * fee_rate;
+    let overhead_share = (overhead_fee + participants.len() as u64 - 1) / participants.len() as u64;
+
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    let mut change_outputs = vec![];
+
+    for (index, participant) in participants.into_iter().enumerate() {
+        let dust = dust_threshold(&participant.output_script, DEFAULT_DUST_RELAY_FEERATE);
+        if denomination < dust {
+            return Err(
+                Error::from(Proto::Error::Error_dust_output).with_context(format!(
+                    "denomination of {denomination} sats is below the dust threshold of {dust} sats"
+                )),
+            );
+        }
+
+        let mut weight =
+            output_weight(&participant.output_script) + output_weight(&participant.change_script);
+        let mut total_input: u64 = 0;
+
+        for input in &participant.inputs {
+            weight += INPUT_BASE_WEIGHT + InputBuilder::utxo_from_proto(input)?.weight_estimate;
+            total_input = total_input
+                .checked_add(input.value)
+                .ok_or_else(|| Error::from(Proto::Error::Error_utxo_amount_overflow))?;
+        }
+
+        let fee = (weight + 3) / 4 * fee_rate + overhead_share;
+        let change = total_input
+            .checked_sub(denomination)
+            .and_then(|change| change.checked_sub(fee))
+            .ok_or_else(|| {
+                Error::from(Proto::Error::Error_utxo_insufficient_inputs).with_context(format!(
+                    "participant {index} can not cover the denomination and fee of {fee} sats"
+                ))
+            })?;
+
+        inputs.extend(participant.inputs);
+        outputs.push((denomination, participant.output_script));
+
+        if change >= dust_threshold(&participant.change_script, DEFAULT_DUST_RELAY_FEERATE) {
+            change_outputs.push((change, participant.change_script));
+        }
+    }
+
+    let participants = outputs.len();
+    outputs.extend(change_outputs);
+
+    // Sort the inputs by txid (in the displayed byte order) and vout, and the
+    // outputs by value and scriptPubkey, according to BIP-69.
+    inputs.sort_by(|a, b| {
+        a.txid
+            .iter()
+            .rev()
+            .cmp(b.txid.iter().rev())
+            .then(a.vout.cmp(&b.vout))
+    });
+    outputs.sort();
+
+    let outputs: Vec<_> = outputs
+        .iter()
+        .map(|(value, script)| custom_output(*value, script))
+        .collect();
+
+    // Rejects change outputs that happen to match the denomination.
+    check_denomination_outputs(&outputs, denomination, participants)?;
+
+    Ok(Proto::SigningInput {
+        inputs,
+        outputs,
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: fee_rate,
+        disable_change_output: true,
+        ..Default::default()
+    })
+}
+
+/// Checks that the transaction carries `participants` outputs of exactly
+/// `denomination` satoshis, as built by [`build`], and returns their indices.
+/// Participants should check this (and their own outputs) before signing.
+pub fn check_denomination_outputs(
+    outputs: &[Proto::Output<'_>],
+    denomination: u64,
+    participants: usize,
+) -> Result<Vec<usize>> {
+    let indices: Vec<usize> = outputs
+        .iter()
+        .enumerate()
+        .filter(|(_, output)| output.value == denomination)
+        .map(|(index, _)| index)
+        .collect();
+
+    if indices.len() != participants {
+        return Err(
+            Error::from(Proto::Error::Error_invalid_coinjoin).with_context(format!(
+                "expected {participants} outputs of {denomination} sats, found {}",
+                indices.len()
+            )),
+        );
+    }
+
+    Ok(indices)
+}
+
+// Value, length and scriptPubkey (scale factor applied).
+fn output_weight(script: &ScriptBuf) -> u64 {
+    4 * (8 + 1 + script.len() as u64)
+}
+
+fn custom_output(value: u64, script: &ScriptBuf) -> Proto::Output<'static> {
+    Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(script.to_bytes().into()),
+        ..Default::default()
+    }
+}
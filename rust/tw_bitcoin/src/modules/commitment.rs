@@ -0,0 +1,16 @@
+use crate::aliases::*;
+use tw_proto::BitcoinV2::Proto;
+
+/// Creates a zero-value OP_RETURN output that embeds the given commitment
+/// (such as the SHA256 hash of some document) for timestamping purposes. The
+/// resulting scriptPubkey is `OP_RETURN OP_PUSHBYTES_32 <commitment>`, which is
+/// 34 bytes long.
+pub fn build_output(commitment: [u8; 32]) -> Proto::Output<'static> {
+    Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::op_return(commitment.to_vec().into()),
+        }),
+        ..Default::default()
+    }
+}
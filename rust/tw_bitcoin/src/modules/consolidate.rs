@@ -0,0 +1,102 @@
+use crate::modules::transactions::InputBuilder;
+use crate::Result;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+// Weight of the non-witness part of each input (txid, vout, sequence and the
+// length of the scriptSig), the scale factor is applied.
+pub(crate) const INPUT_BASE_WEIGHT: u64 = 4 * (32 + 4 + 4 + 1);
+// Weight of the transaction overhead (version, lock time, input/output
+// counters and the Segwit marker/flag).
+pub(crate) const TX_OVERHEAD_WEIGHT: u64 = 4 * (4 + 4 + 1 + 1) + 2;
+// Weight of the consolidated P2WPKH output (value + length + scriptPubkey).
+const CONSOLIDATED_OUTPUT_WEIGHT: u64 = 4 * (8 + 1 + 22);
+// Weight of spending the consolidated P2WPKH output later on.
+const CONSOLIDATED_INPUT_WEIGHT: u64 = INPUT_BASE_WEIGHT + 1 + 1 + 72 + 1 + 33;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsolidationReport {
+    /// The fee (in satoshis) of consolidating all UTXOs into a single P2WPKH
+    /// output at the consolidation fee rate.
+    pub consolidation_fee: u64,
+    /// The fee (in satoshis) saved when spending the single consolidated
+    /// output instead of all UTXOs at the future fee rate.
+    pub future_savings: u64,
+    /// The future savings minus the consolidation fee.
+    pub net_savings: i64,
+    /// Whether consolidating now is recommended (positive net savings).
+    pub recommended: bool,
+}
+
+/// Estimates the fee of consolidating the given UTXOs now (at `fee_rate`, in
+/// sat/vbyte) versus the savings of spending a single consolidated output
+/// instead of all UTXOs in the future (at `future_rate`, in sat/vbyte).
+pub fn analyze(
+    utxos: &[Proto::Input<'_>],
+    fee_rate: u64,
+    future_rate: u64,
+) -> Result<ConsolidationReport> {
+    let mut inputs_weight = 0;
+    for utxo in utxos {
+        inputs_weight += INPUT_BASE_WEIGHT + InputBuilder::utxo_from_proto(utxo)?.weight_estimate;
+    }
+
+    let consolidation_weight = TX_OVERHEAD_WEIGHT + inputs_weight + CONSOLIDATED_OUTPUT_WEIGHT;
+    let consolidation_fee = vsize(consolidation_weight) * fee_rate;
+
+    let future_savings =
+        vsize(inputs_weight).saturating_sub(vsize(CONSOLIDATED_INPUT_WEIGHT)) * future_rate;
+
+    let net_savings = future_savings as i64 - consolidation_fee as i64;
+
+    Ok(ConsolidationReport {
+        consolidation_fee,
+        future_savings,
+        net_savings,
+        recommended: net_savings > 0,
+    })
+}
+
+/// Creates the signing input that consolidates all given UTXOs into the given
+/// destination output, which receives the full amount minus the fee. The
+/// consolidation is not time-sensitive, so the given `fee_rate` (in
+/// sat/vbyte) is usually lower than the fee rate of normal sends. The private
+/// key(s) must be set by the caller.
+pub fn build_signing_input<'a>(
+    utxos: Vec<Proto::Input<'a>>,
+    destination: Proto::Output<'a>,
+    fee_rate: u64,
+) -> Proto::SigningInput<'a> {
+    Proto::SigningInput {
+        inputs: utxos,
+        outputs: vec![],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: fee_rate,
+        // The destination receives the consolidated amount.
+        change_output: Some(destination),
+        disable_change_output: false,
+        ..Default::default()
+    }
+}
+
+/// Like [`build_signing_input`], but the UTXOs at the `excluded` indices are
+/// neither spent nor affected, for example UTXOs that carry an inscription.
+pub fn build_signing_input_excluding<'a>(
+    utxos: Vec<Proto::Input<'a>>,
+    excluded: &[usize],
+    destination: Proto::Output<'a>,
+    fee_rate: u64,
+) -> Proto::SigningInput<'a> {
+    let utxos = utxos
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| !excluded.contains(index))
+        .map(|(_, utxo)| utxo)
+        .collect();
+
+    build_signing_input(utxos, destination, fee_rate)
+}
+
+fn vsize(weight: u64) -> u64 {
+    (weight + 3) / 4
+}
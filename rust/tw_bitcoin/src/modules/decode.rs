@@ -0,0 +1,344 @@
+use crate::{Error, Result};
+use bitcoin::consensus::{encode, Decodable};
+use bitcoin::script::Instruction;
+use bitcoin::taproot::{
+    ControlBlock, TAPROOT_ANNEX_PREFIX, TAPROOT_CONTROL_BASE_SIZE, TAPROOT_CONTROL_NODE_SIZE,
+};
+use bitcoin::{Transaction, TxIn, TxOut, Witness};
+use std::io::{self, Read};
+use tw_proto::BitcoinV2::Proto;
+
+/// The reason why an ECDSA signature is non-standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureIssue {
+    /// The signature is not strictly DER encoded (BIP-66).
+    NonStrictDer,
+    /// The S value of the signature is in the upper half of the curve order
+    /// (BIP-62, rule 5).
+    HighS,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlaggedInput {
+    /// The index of the input.
+    pub index: usize,
+    pub issue: SignatureIssue,
+}
+
+/// The spend path of a Taproot input, as committed to by the `spend_type`
+/// byte of the BIP-341 sighash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaprootSpendPath {
+    KeyPath,
+    ScriptPath {
+        /// The leaf version of the control block, e.g. `0xc0` for Tapscript.
+        leaf_version: u8,
+        /// The number of Merkle proof nodes of the control block, i.e. the
+        /// depth of the leaf within the script tree.
+        depth: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaprootSpend {
+    pub path: TaprootSpendPath,
+    /// Whether the witness carries an annex.
+    pub has_annex: bool,
+}
+
+impl TaprootSpend {
+    /// The sighash epoch of BIP-341, which is always zero.
+    pub const SIGHASH_EPOCH: u8 = 0x00;
+
+    /// The `spend_type` byte of the BIP-341 sighash: the extension flag (one
+    /// for script-path spends) times two, plus one if an annex is present.
+    pub fn spend_type(&self) -> u8 {
+        let ext_flag = match self.path {
+            TaprootSpendPath::KeyPath => 0,
+            TaprootSpendPath::ScriptPath { .. } => 1,
+        };
+
+        ext_flag * 2 + u8::from(self.has_annex)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaprootInput {
+    /// The index of the input.
+    pub index: usize,
+    pub spend: TaprootSpend,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedTransaction {
+    pub transaction: Transaction,
+    /// The inputs carrying non-standard ECDSA signatures, only reported in
+    /// strict mode.
+    pub flagged_inputs: Vec<FlaggedInput>,
+    /// The inputs whose witness is structured like a Taproot spend, see
+    /// [`taproot_spend`].
+    pub taproot_inputs: Vec<TaprootInput>,
+}
+
+/// Decodes the given serialized transaction. In strict mode, the ECDSA
+/// signatures of each input (in both the scriptSig and the witness) are
+/// additionally checked for strict DER encoding and low S values, since nodes
+/// do not relay transactions that violate these rules.
+///
+/// Note that the type of the spent outputs is not known, so any push that is
+/// structured like a DER signature (followed by the sighash type) is treated
+/// as an ECDSA signature.
+///
+/// If the transaction is malformed, the error context reports the byte offset
+/// at which decoding failed. A SegWit transaction must provide exactly one
+/// witness stack per input.
+///
+/// Output scripts are kept opaque, including witness programs of unknown
+/// (future) versions, so re-encoding the decoded transaction results in the
+/// original bytes.
+pub fn decode_transaction(bytes: &[u8], strict: bool) -> Result<DecodedTransaction> {
+    check_witness_stack_count(bytes)?;
+
+    let mut reader = OffsetReader {
+        inner: bytes,
+        offset: 0,
+    };
+
+    let transaction = Transaction::consensus_decode(&mut reader).map_err(|err| {
+        let context = match err {
+            encode::Error::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof => format!(
+                "unexpected end of data at byte offset {}, expected more bytes",
+                reader.offset
+            ),
+            err => format!("{err} at byte offset {}", reader.offset),
+        };
+
+        Error::from(Proto::Error::Error_malformed_transaction).with_context(context)
+    })?;
+
+    if !reader.inner.is_empty() {
+        return Err(
+            Error::from(Proto::Error::Error_malformed_transaction).with_context(format!(
+                "{} trailing bytes at byte offset {}",
+                reader.inner.len(),
+                reader.offset
+            )),
+        );
+    }
+
+    let mut flagged_inputs = vec![];
+
+    if strict {
+        for (index, input) in transaction.input.iter().enumerate() {
+            let script_sig_pushes =
+                input
+                    .script_sig
+                    .instructions()
+                    .filter_map(|instruction| match instruction {
+                        Ok(Instruction::PushBytes(push)) => Some(push.as_bytes()),
+                        _ => None,
+                    });
+
+            let issue = script_sig_pushes
+                .chain(input.witness.iter())
+                .filter(|item| looks_like_ecdsa_signature(item))
+                .find_map(check_ecdsa_signature);
+
+            if let Some(issue) = issue {
+                flagged_inputs.push(FlaggedInput { index, issue });
+            }
+        }
+    }
+
+    let taproot_inputs = transaction
+        .input
+        .iter()
+        .enumerate()
+        .filter_map(|(index, input)| {
+            taproot_spend(input).map(|spend| TaprootInput { index, spend })
+        })
+        .collect();
+
+    Ok(DecodedTransaction {
+        transaction,
+        flagged_inputs,
+        taproot_inputs,
+    })
+}
+
+/// Inspects the witness of the given input for a Taproot spend (BIP-341).
+///
+/// Since the spent output is not known, this is a heuristic: after stripping
+/// the annex, a single Schnorr signature (with an optional sighash type) is
+/// reported as a key-path spend, and a last item that decodes as a control
+/// block as a script-path spend. Inputs with a non-empty scriptSig are never
+/// reported.
+pub fn taproot_spend(input: &TxIn) -> Option<TaprootSpend> {
+    if !input.script_sig.is_empty() {
+        return None;
+    }
+
+    let mut witness: Vec<&[u8]> = input.witness.iter().collect();
+
+    // The annex is only present if there are at least two witness items.
+    let has_annex = witness.len() >= 2
+        && witness.last().and_then(|item| item.first()) == Some(&TAPROOT_ANNEX_PREFIX);
+    if has_annex {
+        witness.pop();
+    }
+
+    match witness.as_slice() {
+        [sig] if sig.len() == 64 || sig.len() == 65 => Some(TaprootSpend {
+            path: TaprootSpendPath::KeyPath,
+            has_annex,
+        }),
+        [.., _leaf_script, control_block] => {
+            let leaf_version = control_block.first()? & 0xfe;
+
+            // A compressed public key (e.g. of P2WPKH) starts with `0x02` or
+            // `0x03` and has the size of a control block with an empty path.
+            if leaf_version == 0x02 {
+                return None;
+            }
+
+            ControlBlock::decode(control_block).ok()?;
+
+            Some(TaprootSpend {
+                path: TaprootSpendPath::ScriptPath {
+                    leaf_version,
+                    depth: (control_block.len() - TAPROOT_CONTROL_BASE_SIZE)
+                        / TAPROOT_CONTROL_NODE_SIZE,
+                },
+                has_annex,
+            })
+        },
+        _ => None,
+    }
+}
+
+// For SegWit transactions (marker and flag present), the witness stacks are
+// not prefixed by a count, but implied by the number of inputs. A mismatch
+// shifts the lock time, which is usually reported as some unrelated decoding
+// error. Hence, we count the witness stacks in front of the lock time
+// explicitly. If the inputs or outputs are malformed, this is left to the
+// actual decoding.
+fn check_witness_stack_count(bytes: &[u8]) -> Result<()> {
+    const LOCK_TIME_SIZE: usize = 4;
+
+    // Version, followed by the SegWit marker and flag.
+    if bytes.len() < 6 || bytes[4] != 0x00 || bytes[5] != 0x01 {
+        return Ok(());
+    }
+
+    let mut reader = &bytes[6..];
+    let (Ok(inputs), Ok(_)) = (
+        Vec::<TxIn>::consensus_decode(&mut reader),
+        Vec::<TxOut>::consensus_decode(&mut reader),
+    ) else {
+        return Ok(());
+    };
+
+    let mut stacks = 0;
+    while reader.len() > LOCK_TIME_SIZE {
+        if Witness::consensus_decode(&mut reader).is_err() {
+            return Ok(());
+        }
+        stacks += 1;
+    }
+
+    if reader.len() == LOCK_TIME_SIZE && stacks != inputs.len() {
+        return Err(
+            Error::from(Proto::Error::Error_malformed_transaction).with_context(format!(
+                "SegWit transaction provides {stacks} witness stacks for {} inputs",
+                inputs.len()
+            )),
+        );
+    }
+
+    Ok(())
+}
+
+// Keeps track of the number of consumed bytes.
+struct OffsetReader<'a> {
+    inner: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Read for OffsetReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.offset += read;
+        Ok(read)
+    }
+}
+
+/// Checks the given signature (including the trailing sighash type) for strict
+/// DER encoding and a low S value.
+pub fn check_ecdsa_signature(sig: &[u8]) -> Option<SignatureIssue> {
+    if !is_strict_der(sig) {
+        return Some(SignatureIssue::NonStrictDer);
+    }
+
+    // Strip the sighash type.
+    let der = &sig[..sig.len() - 1];
+    let Ok(parsed) = secp256k1::ecdsa::Signature::from_der(der) else {
+        return Some(SignatureIssue::NonStrictDer);
+    };
+
+    let mut normalized = parsed;
+    normalized.normalize_s();
+
+    if normalized != parsed {
+        return Some(SignatureIssue::HighS);
+    }
+
+    None
+}
+
+// A DER signature starts with the SEQUENCE tag and its length, which does not
+// cover the trailing sighash type.
+fn looks_like_ecdsa_signature(item: &[u8]) -> bool {
+    item.len() >= 9 && item[0] == 0x30 && item[1] as usize == item.len() - 3
+}
+
+// Port of `IsValidSignatureEncoding` of Bitcoin Core (BIP-66), the signature
+// includes the trailing sighash type:
+//
+// 0x30 [total-length] 0x02 [R-length] [R] 0x02 [S-length] [S] [sighash]
+fn is_strict_der(sig: &[u8]) -> bool {
+    if sig.len() < 9 || sig.len() > 73 {
+        return false;
+    }
+
+    if sig[0] != 0x30 || sig[1] as usize != sig.len() - 3 {
+        return false;
+    }
+
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= sig.len() {
+        return false;
+    }
+
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 7 != sig.len() {
+        return false;
+    }
+
+    // R must be a positive integer without excessive padding.
+    if sig[2] != 0x02 || len_r == 0 || sig[4] & 0x80 != 0 {
+        return false;
+    }
+    if len_r > 1 && sig[4] == 0x00 && sig[5] & 0x80 == 0 {
+        return false;
+    }
+
+    // S must be a positive integer without excessive padding.
+    if sig[len_r + 4] != 0x02 || len_s == 0 || sig[len_r + 6] & 0x80 != 0 {
+        return false;
+    }
+    if len_s > 1 && sig[len_r + 6] == 0x00 && sig[len_r + 7] & 0x80 == 0 {
+        return false;
+    }
+
+    true
+}
@@ -0,0 +1,293 @@
+use crate::aliases::*;
+use crate::modules::transactions::taproot_output_key;
+use crate::{Error, Result};
+use bitcoin::bip32::{ChildNumber, ExtendedPubKey};
+use bitcoin::key::XOnlyPublicKey;
+use bitcoin::{PublicKey, ScriptBuf};
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+/// The character set of the descriptor checksum input (BIP-380).
+const INPUT_CHARSET: &str =
+    "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+/// The character set of the descriptor checksum itself.
+const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// A single-key output descriptor (BIP-380), such as `wpkh(xpub.../0/*)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Descriptor {
+    /// `pkh(KEY)`
+    Pkh(DescriptorKey),
+    /// `wpkh(KEY)`
+    Wpkh(DescriptorKey),
+    /// `sh(wpkh(KEY))`
+    ShWpkh(DescriptorKey),
+    /// `tr(KEY)`, key-path only (BIP-86).
+    Tr(DescriptorKey),
+}
+
+/// The key expression of a descriptor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescriptorKey {
+    /// A fixed public key.
+    Single(PublicKey),
+    /// An extended public key with a (non-hardened) derivation path. Ranged
+    /// keys end with the `/*` wildcard, which is replaced by the index.
+    Extended {
+        xpub: ExtendedPubKey,
+        path: Vec<ChildNumber>,
+        ranged: bool,
+    },
+}
+
+impl Descriptor {
+    /// Whether the descriptor contains a `/*` wildcard.
+    pub fn is_ranged(&self) -> bool {
+        matches!(self.key(), DescriptorKey::Extended { ranged: true, .. })
+    }
+
+    /// Returns the scriptPubkey of the descriptor at the given index. The
+    /// index is ignored for descriptors that are not ranged.
+    pub fn script_pubkey(&self, index: u32) -> Result<ScriptBuf> {
+        let pubkey = self.key().derive(index)?;
+
+        let script_pubkey = match self {
+            Descriptor::Pkh(_) => ScriptBuf::new_p2pkh(&pubkey.pubkey_hash()),
+            Descriptor::Wpkh(_) => ScriptBuf::new_v0_p2wpkh(&wpubkey_hash(&pubkey)?),
+            Descriptor::ShWpkh(_) => {
+                let redeem_script = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash(&pubkey)?);
+                ScriptBuf::new_p2sh(&redeem_script.script_hash())
+            },
+            Descriptor::Tr(_) => {
+                let internal_key = XOnlyPublicKey::from(pubkey.inner);
+                ScriptBuf::new_v1_p2tr_tweaked(taproot_output_key(internal_key, None)?)
+            },
+        };
+
+        Ok(script_pubkey)
+    }
+
+    fn key(&self) -> &DescriptorKey {
+        match self {
+            Descriptor::Pkh(key)
+            | Descriptor::Wpkh(key)
+            | Descriptor::ShWpkh(key)
+            | Descriptor::Tr(key) => key,
+        }
+    }
+}
+
+impl FromStr for Descriptor {
+    type Err = Error;
+
+    /// Parses the descriptor, verifying the `#checksum` suffix if present.
+    fn from_str(descriptor: &str) -> Result<Self> {
+        let descriptor = match descriptor.split_once('#') {
+            Some((descriptor, expected)) => {
+                if checksum(descriptor)? != expected {
+                    return Err(invalid_descriptor("invalid checksum"));
+                }
+                descriptor
+            },
+            None => descriptor,
+        };
+
+        if let Some(inner) = strip_function(descriptor, "sh") {
+            let key = strip_function(inner, "wpkh")
+                .ok_or_else(|| invalid_descriptor("only sh(wpkh(KEY)) is supported"))?;
+            return Ok(Descriptor::ShWpkh(DescriptorKey::from_str(key)?));
+        }
+
+        if let Some(key) = strip_function(descriptor, "pkh") {
+            Ok(Descriptor::Pkh(DescriptorKey::from_str(key)?))
+        } else if let Some(key) = strip_function(descriptor, "wpkh") {
+            Ok(Descriptor::Wpkh(DescriptorKey::from_str(key)?))
+        } else if let Some(key) = strip_function(descriptor, "tr") {
+            Ok(Descriptor::Tr(DescriptorKey::from_tr_str(key)?))
+        } else {
+            Err(invalid_descriptor("unsupported script type"))
+        }
+    }
+}
+
+impl DescriptorKey {
+    /// Returns the public key, replacing the wildcard of ranged keys by the
+    /// given index.
+    pub fn derive(&self, index: u32) -> Result<PublicKey> {
+        match self {
+            DescriptorKey::Single(pubkey) => Ok(*pubkey),
+            DescriptorKey::Extended { xpub, path, ranged } => {
+                let mut path = path.clone();
+                if *ranged {
+                    path.push(ChildNumber::from_normal_idx(index).map_err(|_| {
+                        invalid_descriptor(format!("index {index} is out of range"))
+                    })?);
+                }
+
+                let derived = xpub
+                    .derive_pub(&secp256k1::Secp256k1::new(), &path)
+                    .map_err(|_| Error::from(Proto::Error::Error_hd_derivation_failed))?;
+
+                Ok(PublicKey::new(derived.public_key))
+            },
+        }
+    }
+
+    // Like `from_str`, but also accepts x-only public keys.
+    fn from_tr_str(key: &str) -> Result<Self> {
+        if key.len() == 64 {
+            let xonly = XOnlyPublicKey::from_str(key)
+                .map_err(|_| invalid_descriptor("invalid x-only public key"))?;
+            let pubkey =
+                secp256k1::PublicKey::from_x_only_public_key(xonly, secp256k1::Parity::Even);
+            return Ok(DescriptorKey::Single(PublicKey::new(pubkey)));
+        }
+
+        DescriptorKey::from_str(key)
+    }
+}
+
+impl FromStr for DescriptorKey {
+    type Err = Error;
+
+    fn from_str(key: &str) -> Result<Self> {
+        // The key origin (`[fingerprint/path]`) does not affect the scriptPubkey.
+        let key = match key.strip_prefix('[') {
+            Some(origin) => {
+                origin
+                    .split_once(']')
+                    .ok_or_else(|| invalid_descriptor("unterminated key origin"))?
+                    .1
+            },
+            None => key,
+        };
+
+        let mut parts = key.split('/');
+        let key = parts.next().unwrap_or_default();
+        let steps: Vec<&str> = parts.collect();
+
+        if let Ok(pubkey) = PublicKey::from_str(key) {
+            if !steps.is_empty() {
+                return Err(invalid_descriptor("a single key can not be derived"));
+            }
+            return Ok(DescriptorKey::Single(pubkey));
+        }
+
+        let xpub =
+            ExtendedPubKey::from_str(key).map_err(|_| invalid_descriptor("invalid public key"))?;
+
+        let mut path = Vec::with_capacity(steps.len());
+        let mut ranged = false;
+
+        for (position, step) in steps.iter().enumerate() {
+            if *step == "*" && position == steps.len() - 1 {
+                ranged = true;
+            } else if step.ends_with('\'') || step.ends_with('h') {
+                return Err(invalid_descriptor(
+                    "hardened derivation requires a private key",
+                ));
+            } else {
+                let child = step
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|index| ChildNumber::from_normal_idx(index).ok())
+                    .ok_or_else(|| invalid_descriptor(format!("invalid path step {step}")))?;
+                path.push(child);
+            }
+        }
+
+        Ok(DescriptorKey::Extended { xpub, path, ranged })
+    }
+}
+
+/// Returns an output of the given amount that pays to the scriptPubkey of
+/// the descriptor at the given index.
+pub fn build_output_to_descriptor(
+    descriptor: &str,
+    index: u32,
+    amount: u64,
+) -> Result<Proto::Output<'static>> {
+    let script_pubkey = Descriptor::from_str(descriptor)?.script_pubkey(index)?;
+
+    Ok(Proto::Output {
+        value: amount,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(script_pubkey.to_bytes().into()),
+        ..Default::default()
+    })
+}
+
+/// Returns the eight character checksum of the descriptor (BIP-380).
+pub fn checksum(descriptor: &str) -> Result<String> {
+    fn polymod(c: u64, value: u64) -> u64 {
+        const GENERATORS: [u64; 5] = [
+            0xf5dee51989,
+            0xa9fdca3312,
+            0x1bab10e32d,
+            0x3706b1677a,
+            0x644d626ffd,
+        ];
+
+        let top = c >> 35;
+        let mut c = ((c & 0x7ffffffff) << 5) ^ value;
+        for (bit, generator) in GENERATORS.iter().enumerate() {
+            if (top >> bit) & 1 == 1 {
+                c ^= generator;
+            }
+        }
+        c
+    }
+
+    let mut c = 1;
+    let mut class = 0;
+    let mut class_count = 0;
+
+    for ch in descriptor.chars() {
+        let position = INPUT_CHARSET
+            .find(ch)
+            .ok_or_else(|| invalid_descriptor(format!("invalid character {ch:?}")))?
+            as u64;
+
+        // Symbols are fed in groups of five bits, the character classes in
+        // groups of three.
+        c = polymod(c, position & 31);
+        class = class * 3 + (position >> 5);
+        class_count += 1;
+
+        if class_count == 3 {
+            c = polymod(c, class);
+            class = 0;
+            class_count = 0;
+        }
+    }
+
+    if class_count > 0 {
+        c = polymod(c, class);
+    }
+    for _ in 0..8 {
+        c = polymod(c, 0);
+    }
+    c ^= 1;
+
+    Ok((0..8)
+        .map(|j| CHECKSUM_CHARSET[((c >> (5 * (7 - j))) & 31) as usize] as char)
+        .collect())
+}
+
+// Returns the argument of `name(...)`, if the descriptor is of that form.
+fn strip_function<'a>(descriptor: &'a str, name: &str) -> Option<&'a str> {
+    descriptor
+        .strip_prefix(name)?
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+fn wpubkey_hash(pubkey: &PublicKey) -> Result<bitcoin::WPubkeyHash> {
+    pubkey.wpubkey_hash().ok_or_else(|| {
+        Error::from(Proto::Error::Error_invalid_witness_pubkey_hash)
+            .with_context("Segwit requires a compressed public key")
+    })
+}
+
+fn invalid_descriptor(context: impl Into<String>) -> Error {
+    Error::from(Proto::Error::Error_invalid_descriptor).with_context(context)
+}
@@ -0,0 +1,144 @@
+use bitcoin::Transaction;
+use tw_proto::BitcoinV2::Proto;
+
+/// The type of an input, assuming the standard spend with a single signature.
+/// The ECDSA signatures are assumed to be of the maximum size of 72 bytes
+/// (including the sighash type), the Schnorr signature uses the default
+/// sighash type (64 bytes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputDescriptor {
+    P2pkh,
+    P2wpkh,
+    P2trKeyPath,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputDescriptor {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// The estimated virtual size of the signed transaction.
+    pub vsize: u64,
+    /// The fee (in satoshis) for the given fee rate.
+    pub fee: u64,
+}
+
+// Non-witness bytes of an input without the scriptSig: txid, vout, the length
+// of the scriptSig and the sequence.
+const INPUT_BASE_SIZE: u64 = 32 + 4 + 1 + 4;
+// Non-witness bytes of an output without the scriptPubkey: value and the
+// length of the scriptPubkey.
+const OUTPUT_BASE_SIZE: u64 = 8 + 1;
+// Version and lock time.
+const TX_BASE_SIZE: u64 = 4 + 4;
+// The Segwit marker and flag, witness bytes.
+const SEGWIT_MARKER_SIZE: u64 = 2;
+
+impl InputDescriptor {
+    fn is_segwit(&self) -> bool {
+        !matches!(self, InputDescriptor::P2pkh)
+    }
+    // Non-witness bytes of the input.
+    fn size(&self) -> u64 {
+        match self {
+            // length + ECDSA signature, length + compressed public key.
+            InputDescriptor::P2pkh => INPUT_BASE_SIZE + 1 + 72 + 1 + 33,
+            InputDescriptor::P2wpkh | InputDescriptor::P2trKeyPath => INPUT_BASE_SIZE,
+        }
+    }
+    // Witness bytes of the input, including the number of witness items.
+    fn witness_size(&self) -> u64 {
+        match self {
+            // An empty witness, only relevant in Segwit transactions.
+            InputDescriptor::P2pkh => 1,
+            InputDescriptor::P2wpkh => 1 + 1 + 72 + 1 + 33,
+            InputDescriptor::P2trKeyPath => 1 + 1 + 64,
+        }
+    }
+}
+
+impl OutputDescriptor {
+    fn size(&self) -> u64 {
+        let script_pubkey = match self {
+            OutputDescriptor::P2pkh => 25,
+            OutputDescriptor::P2sh => 23,
+            OutputDescriptor::P2wpkh => 22,
+            OutputDescriptor::P2wsh | OutputDescriptor::P2tr => 34,
+        };
+
+        OUTPUT_BASE_SIZE + script_pubkey
+    }
+}
+
+/// Estimates the virtual size and the fee (in satoshis) of a transaction
+/// spending the given mix of input types to the given outputs at the given fee
+/// rate (in sat/vbyte). This sums up the contribution of each input and output
+/// plus the fixed overhead of the transaction (version, lock time, counts and
+/// the Segwit marker, if any).
+pub fn estimate_mixed(
+    inputs: &[InputDescriptor],
+    outputs: &[OutputDescriptor],
+    fee_rate: u64,
+) -> FeeEstimate {
+    let size = TX_BASE_SIZE
+        + compact_size_len(inputs.len() as u64)
+        + compact_size_len(outputs.len() as u64)
+        + inputs.iter().map(InputDescriptor::size).sum::<u64>()
+        + outputs.iter().map(OutputDescriptor::size).sum::<u64>();
+
+    let witness_size = if inputs.iter().any(InputDescriptor::is_segwit) {
+        SEGWIT_MARKER_SIZE
+            + inputs
+                .iter()
+                .map(InputDescriptor::witness_size)
+                .sum::<u64>()
+    } else {
+        0
+    };
+
+    let weight = size * 4 + witness_size;
+    let vsize = (weight + 3) / 4;
+
+    FeeEstimate {
+        vsize,
+        fee: vsize * fee_rate,
+    }
+}
+
+/// Returns whether the given transaction pays at least the minimum relay fee,
+/// i.e. `vsize * min_relay_feerate` (in sat/vbyte). Transactions paying less
+/// are rejected by nodes and are not relayed. A minimum relay fee exceeding the
+/// range of satoshis can never be met.
+pub fn meets_min_relay(tx: &Transaction, actual_fee: u64, min_relay_feerate: u64) -> bool {
+    let vsize = tx.vsize() as u64;
+    matches!(vsize.checked_mul(min_relay_feerate), Some(min_fee) if actual_fee >= min_fee)
+}
+
+/// Returns the confirmation bucket that the given fee rate (in sat/vbyte)
+/// falls into, i.e. the fastest bucket whose minimum fee rate is covered.
+/// Returns `None` if the fee rate is below the minimum of all buckets.
+pub fn confirmation_bucket<'a, 'b>(
+    buckets: &'a [Proto::ConfirmationBucket<'b>],
+    fee_per_vb: u64,
+) -> Option<&'a Proto::ConfirmationBucket<'b>> {
+    buckets
+        .iter()
+        .filter(|bucket| bucket.min_fee_per_vb <= fee_per_vb)
+        .min_by_key(|bucket| bucket.blocks)
+}
+
+// The length of the encoded compact size (Bitcoin "VarInt").
+fn compact_size_len(value: u64) -> u64 {
+    match value {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
@@ -0,0 +1,321 @@
+use crate::modules::consolidate::INPUT_BASE_WEIGHT;
+use crate::modules::transactions::{InputBuilder, OutputBuilder};
+use crate::{Error, Result};
+use bitcoin::{OutPoint, Script, ScriptBuf, Transaction, TxOut, Txid};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// The default incremental relay fee rate of Bitcoin Core (in sat/vbyte).
+pub const INCREMENTAL_RELAY_FEERATE: f64 = 1.0;
+
+/// Returns the minimum fee rate (in sat/vbyte) a replacement transaction of
+/// size `replacement_vsize` must use in order to replace `original_tx` via
+/// RBF (BIP-125). The replacement must:
+///
+/// 1. Pay an absolute fee of at least the fees of the original transaction
+///    and all of its descendants, which get evicted from the mempool.
+/// 2. Additionally pay for its own bandwidth at the incremental relay fee
+///    rate.
+/// 3. Use a higher fee rate than the original transaction, including its
+///    descendants.
+pub fn min_rbf_feerate(
+    original_tx: &Transaction,
+    original_fee: u64,
+    descendant_fees: u64,
+    descendant_vsize: u64,
+    replacement_vsize: u64,
+) -> f64 {
+    let replacement_vsize = replacement_vsize.max(1) as f64;
+    let evicted_fees = (original_fee + descendant_fees) as f64;
+
+    // Rule 1 and 2: absolute fee and incremental relay fee.
+    let min_fee = evicted_fees + INCREMENTAL_RELAY_FEERATE * replacement_vsize;
+    let absolute_rate = min_fee / replacement_vsize;
+
+    // Rule 3: fee rate of the original transaction (package).
+    let original_vsize = (original_tx.vsize() as u64 + descendant_vsize).max(1) as f64;
+    let original_rate = evicted_fees / original_vsize;
+
+    absolute_rate.max(original_rate)
+}
+
+/// Applies the minimum RBF fee rate as a floor to the given fee rate, as used
+/// by `SigningInput.fee_per_vb`.
+pub fn fee_per_vb_with_floor(fee_per_vb: u64, min_feerate: f64) -> u64 {
+    fee_per_vb.max(min_feerate.ceil() as u64)
+}
+
+/// Returns the effective value of the given input at the given fee rate (in
+/// sat/vbyte), i.e. its value minus the fee of spending it. The effective value
+/// is negative if spending the input costs more than it is worth.
+pub fn effective_value(input: &Proto::Input<'_>, fee_per_vb: u64) -> Result<i64> {
+    let weight = INPUT_BASE_WEIGHT + InputBuilder::utxo_from_proto(input)?.weight_estimate;
+    let fee = (weight + 3) / 4 * fee_per_vb;
+
+    Ok(input.value as i64 - fee as i64)
+}
+
+/// Selects the additional inputs to cover the fee increase of a replacement
+/// transaction at the given fee rate (in sat/vbyte). In order to keep the
+/// replacement small, the candidates with the largest effective value are
+/// selected first, so that the fewest inputs are added. Returns the indices
+/// of the selected candidates.
+pub fn select_bump_inputs(
+    candidates: &[Proto::Input<'_>],
+    fee_increase: u64,
+    fee_per_vb: u64,
+) -> Result<Vec<usize>> {
+    let mut by_effective_value = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, input)| Ok((index, effective_value(input, fee_per_vb)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Largest effective value first.
+    by_effective_value.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    let mut selected = vec![];
+    let mut covered: i64 = 0;
+
+    for (index, value) in by_effective_value {
+        if covered >= fee_increase as i64 {
+            break;
+        }
+
+        // Inputs that cost more than they are worth can never help.
+        if value <= 0 {
+            break;
+        }
+
+        selected.push(index);
+        covered += value;
+    }
+
+    if covered < fee_increase as i64 {
+        return Err(Error::from(Proto::Error::Error_utxo_insufficient_inputs));
+    }
+
+    Ok(selected)
+}
+
+/// The transaction to be replaced via RBF, together with its unconfirmed
+/// descendants, which get evicted from the mempool by the replacement.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplacedTransaction<'t> {
+    pub tx: &'t Transaction,
+    pub fee: u64,
+    pub descendant_fees: u64,
+    pub descendant_vsize: u64,
+}
+
+/// Creates the signing input of a replacement transaction that keeps all
+/// outputs of the original transaction identical (same values and order) and
+/// funds the entire fee increase from new inputs, selected from the given
+/// candidates via [`select_bump_inputs`]. The excess of the new inputs goes to
+/// the given, fresh change output, which is appended after the original
+/// outputs. The private key(s) must be set by the caller.
+///
+/// The given fee rate is raised to the minimum RBF fee rate (see
+/// [`min_rbf_feerate`]), if lower.
+pub fn build_replacement_with_new_inputs<'a>(
+    replaced: ReplacedTransaction<'_>,
+    original_inputs: Vec<Proto::Input<'a>>,
+    original_outputs: Vec<Proto::Output<'a>>,
+    candidates: Vec<Proto::Input<'a>>,
+    change_output: Proto::Output<'a>,
+    fee_per_vb: u64,
+) -> Result<Proto::SigningInput<'a>> {
+    // The replacement consists of the original transaction plus the change
+    // output and the new inputs. Since the new inputs are not known yet, the
+    // minimum fee rate is based on the size without them, which results in a
+    // higher (and therefore still sufficient) rate.
+    let base_vsize = replaced.tx.vsize() as u64 + (output_weight(&change_output)? + 3) / 4;
+
+    let min_feerate = min_rbf_feerate(
+        replaced.tx,
+        replaced.fee,
+        replaced.descendant_fees,
+        replaced.descendant_vsize,
+        base_vsize,
+    );
+    let fee_per_vb = fee_per_vb_with_floor(fee_per_vb, min_feerate);
+
+    // The new inputs must cover the fee increase of the original transaction
+    // and the change output, while the fee of spending the new inputs
+    // themselves is considered by their effective value.
+    let fee_increase = base_vsize
+        .checked_mul(fee_per_vb)
+        .ok_or_else(|| Error::from(Proto::Error::Error_utxo_amount_overflow))?
+        .saturating_sub(replaced.fee);
+    let selected = select_bump_inputs(&candidates, fee_increase, fee_per_vb)?;
+
+    let mut inputs = original_inputs;
+    inputs.extend(selected.into_iter().map(|index| candidates[index].clone()));
+
+    Ok(Proto::SigningInput {
+        inputs,
+        outputs: original_outputs,
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb,
+        change_output: Some(change_output),
+        disable_change_output: false,
+        // Sorting would change the position of the original outputs.
+        bip69_sort_outputs: false,
+        ..Default::default()
+    })
+}
+
+/// An output whose value differs between the original and the replacement
+/// transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputChange {
+    pub script_pubkey: ScriptBuf,
+    pub original_value: u64,
+    pub replacement_value: u64,
+}
+
+/// The differences between an original transaction and its RBF replacement.
+///
+/// Outputs are matched by their scriptPubkey (in order, if the same
+/// scriptPubkey is used multiple times), so reordered outputs are not reported
+/// as changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacementDiff {
+    /// Inputs that are only spent by the replacement.
+    pub added_inputs: Vec<OutPoint>,
+    /// Inputs that are only spent by the original transaction.
+    pub removed_inputs: Vec<OutPoint>,
+    /// Outputs that are present in both transactions, but with different
+    /// values.
+    pub changed_outputs: Vec<OutputChange>,
+    /// Outputs that are only present in the replacement.
+    pub added_outputs: Vec<TxOut>,
+    /// Outputs that are only present in the original transaction.
+    pub removed_outputs: Vec<TxOut>,
+    pub original_fee: u64,
+    pub replacement_fee: u64,
+}
+
+impl ReplacementDiff {
+    /// Returns the additional fee (in satoshis) paid by the replacement, or
+    /// `None` if the replacement pays a lower fee, which is not a valid
+    /// replacement (BIP-125).
+    pub fn fee_increase(&self) -> Option<u64> {
+        self.replacement_fee.checked_sub(self.original_fee)
+    }
+
+    /// Returns whether the payment semantics of the original transaction were
+    /// preserved, meaning that every output other than the given change output
+    /// is kept with the same value and no outputs are added. The change output
+    /// may be reduced, but must not be removed or increased.
+    pub fn preserves_payments(&self, change_script: &Script) -> bool {
+        let change_only_reduced = self.changed_outputs.iter().all(|change| {
+            change.script_pubkey.as_script() == change_script
+                && change.replacement_value < change.original_value
+        });
+
+        self.added_outputs.is_empty() && self.removed_outputs.is_empty() && change_only_reduced
+    }
+}
+
+/// Reports which inputs and outputs changed between the `original` transaction
+/// and its `replacement`, together with the fees paid by both. The fees must be
+/// provided by the caller, since they depend on the values of the spent
+/// outputs.
+pub fn diff_replacement(
+    original: &Transaction,
+    original_fee: u64,
+    replacement: &Transaction,
+    replacement_fee: u64,
+) -> ReplacementDiff {
+    let original_outpoints: Vec<OutPoint> = original
+        .input
+        .iter()
+        .map(|txin| txin.previous_output)
+        .collect();
+    let replacement_outpoints: Vec<OutPoint> = replacement
+        .input
+        .iter()
+        .map(|txin| txin.previous_output)
+        .collect();
+
+    let added_inputs = replacement_outpoints
+        .iter()
+        .filter(|outpoint| !original_outpoints.contains(outpoint))
+        .copied()
+        .collect();
+    let removed_inputs = original_outpoints
+        .iter()
+        .filter(|outpoint| !replacement_outpoints.contains(outpoint))
+        .copied()
+        .collect();
+
+    // Match every original output with the first unmatched replacement output
+    // of the same scriptPubkey.
+    let mut unmatched: Vec<Option<&TxOut>> = replacement.output.iter().map(Some).collect();
+    let mut changed_outputs = Vec::new();
+    let mut removed_outputs = Vec::new();
+
+    for txout in &original.output {
+        let matched = unmatched.iter_mut().find(|candidate| {
+            matches!(candidate, Some(candidate) if candidate.script_pubkey == txout.script_pubkey)
+        });
+
+        match matched.and_then(Option::take) {
+            Some(replaced) if replaced.value != txout.value => {
+                changed_outputs.push(OutputChange {
+                    script_pubkey: txout.script_pubkey.clone(),
+                    original_value: txout.value,
+                    replacement_value: replaced.value,
+                });
+            },
+            Some(_) => {},
+            None => removed_outputs.push(txout.clone()),
+        }
+    }
+
+    let added_outputs = unmatched.into_iter().flatten().cloned().collect();
+
+    ReplacementDiff {
+        added_inputs,
+        removed_inputs,
+        changed_outputs,
+        added_outputs,
+        removed_outputs,
+        original_fee,
+        replacement_fee,
+    }
+}
+
+/// Provides information about unconfirmed transactions, for example from a
+/// node or an indexer.
+pub trait MempoolInfo {
+    /// Returns the total fees (in satoshis) and the total size (in vbytes) of
+    /// the given transaction and all of its unconfirmed ancestors.
+    fn ancestor_fees_and_size(&self, txid: &Txid) -> (u64, u64);
+}
+
+/// Returns the fee (in satoshis) a CPFP child of size `child_vsize` must pay,
+/// so that the package of the parent, its unconfirmed ancestors (as reported
+/// by `mempool`) and the child reaches the given target fee rate (in
+/// sat/vbyte). The child pays at least the target fee rate for its own size,
+/// even if the ancestors already pay more.
+pub fn cpfp_child_fee<M: MempoolInfo + ?Sized>(
+    mempool: &M,
+    parent_txid: &Txid,
+    child_vsize: u64,
+    target_feerate: f64,
+) -> u64 {
+    let (ancestor_fees, ancestor_vsize) = mempool.ancestor_fees_and_size(parent_txid);
+
+    let package_fee = (target_feerate * (ancestor_vsize + child_vsize) as f64).ceil() as u64;
+    let own_fee = (target_feerate * child_vsize as f64).ceil() as u64;
+
+    package_fee.saturating_sub(ancestor_fees).max(own_fee)
+}
+
+// Value, length and scriptPubkey (scale factor applied).
+fn output_weight(output: &Proto::Output<'_>) -> Result<u64> {
+    let utxo = OutputBuilder::utxo_from_proto(output)?;
+    Ok(4 * (8 + 1 + utxo.script_pubkey.len() as u64))
+}
@@ -0,0 +1,146 @@
+use crate::aliases::*;
+use crate::modules::transactions::taproot_output_key;
+use crate::{Error, Result};
+use bitcoin::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey, ExtendedPubKey};
+use bitcoin::key::XOnlyPublicKey;
+use bitcoin::{Address, Network, ScriptBuf};
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+/// Returns the BIP-44 coin type of the given network, i.e. zero for Bitcoin
+/// (mainnet) and one for all test networks.
+pub fn coin_type(network: Network) -> u32 {
+    match network {
+        Network::Bitcoin => 0,
+        _ => 1,
+    }
+}
+
+/// The derivation scheme (BIP-44 purpose) of a script type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Purpose {
+    /// P2PKH (BIP-44).
+    Bip44,
+    /// P2SH-P2WPKH (BIP-49).
+    Bip49,
+    /// P2WPKH (BIP-84).
+    Bip84,
+    /// P2TR key-path (BIP-86).
+    Bip86,
+}
+
+impl Purpose {
+    pub fn value(&self) -> u32 {
+        match self {
+            Purpose::Bip44 => 44,
+            Purpose::Bip49 => 49,
+            Purpose::Bip84 => 84,
+            Purpose::Bip86 => 86,
+        }
+    }
+    /// Infers the purpose from the script type of the given input. Only
+    /// single-key script types are supported.
+    pub fn from_input(input: &Proto::Input<'_>) -> Result<Purpose> {
+        let ProtoInputRecipient::builder(builder) = &input.to_recipient else {
+            return Err(Error::from(Proto::Error::Error_hd_unsupported_script_type));
+        };
+
+        match &builder.variant {
+            ProtoInputBuilder::p2pkh(_) => Ok(Purpose::Bip44),
+            // Only nested P2WPKH is derived via BIP-49.
+            ProtoInputBuilder::p2sh(redeem_script)
+                if ScriptBuf::from_bytes(redeem_script.to_vec()).is_v0_p2wpkh() =>
+            {
+                Ok(Purpose::Bip49)
+            },
+            ProtoInputBuilder::p2wpkh(_) => Ok(Purpose::Bip84),
+            ProtoInputBuilder::p2tr_key_path(_) => Ok(Purpose::Bip86),
+            _ => Err(Error::from(Proto::Error::Error_hd_unsupported_script_type)),
+        }
+    }
+}
+
+/// The (non-hardened) position of a key within an account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyIndex {
+    pub account: u32,
+    /// Zero for receiving addresses, one for change addresses.
+    pub change: u32,
+    pub index: u32,
+}
+
+/// Returns the derivation path `m/purpose'/coin_type'/account'/change/index`,
+/// where the coin type depends on the network (see [`coin_type`]).
+pub fn derivation_path(
+    purpose: Purpose,
+    network: Network,
+    key_index: KeyIndex,
+) -> Result<DerivationPath> {
+    let path = vec![
+        ChildNumber::from_hardened_idx(purpose.value()),
+        ChildNumber::from_hardened_idx(coin_type(network)),
+        ChildNumber::from_hardened_idx(key_index.account),
+        ChildNumber::from_normal_idx(key_index.change),
+        ChildNumber::from_normal_idx(key_index.index),
+    ]
+    .into_iter()
+    .collect::<std::result::Result<Vec<_>, _>>()
+    .map_err(|_| Error::from(Proto::Error::Error_hd_derivation_failed))?;
+
+    Ok(DerivationPath::from(path))
+}
+
+/// Derives the private key of each input from the master key, using the
+/// BIP-44/49/84/86 path that matches the script type of the input and the
+/// corresponding key index. The coin type of the path is derived from the
+/// network of the master key. The keys are set as individual input keys.
+pub fn derive_input_keys(
+    proto: &mut Proto::SigningInput<'_>,
+    master: &ExtendedPrivKey,
+    key_indices: &[KeyIndex],
+) -> Result<()> {
+    if proto.inputs.len() != key_indices.len() {
+        return Err(Error::from(Proto::Error::Error_hd_derivation_failed));
+    }
+
+    let secp = secp256k1::Secp256k1::new();
+
+    for (input, key_index) in proto.inputs.iter_mut().zip(key_indices) {
+        let path = derivation_path(Purpose::from_input(input)?, master.network, *key_index)?;
+
+        let derived = master
+            .derive_priv(&secp, &path)
+            .map_err(|_| Error::from(Proto::Error::Error_hd_derivation_failed))?;
+
+        input.private_key = derived.private_key.secret_bytes().to_vec().into();
+    }
+
+    Ok(())
+}
+
+/// Derives the key at `change/index` from the given account-level extended
+/// public key (`m/86'/coin_type'/account'`) and returns its BIP-86 Taproot
+/// address, i.e. the key-path-only P2TR address of the tweaked key (no script
+/// tree).
+pub fn bip86_address(xpub: &str, change: u32, index: u32, network: Network) -> Result<String> {
+    let xpub = ExtendedPubKey::from_str(xpub)
+        .map_err(|_| Error::from(Proto::Error::Error_hd_derivation_failed))?;
+
+    let path = vec![
+        ChildNumber::from_normal_idx(change),
+        ChildNumber::from_normal_idx(index),
+    ]
+    .into_iter()
+    .collect::<std::result::Result<Vec<_>, _>>()
+    .map_err(|_| Error::from(Proto::Error::Error_hd_derivation_failed))?;
+
+    let secp = secp256k1::Secp256k1::new();
+
+    let derived = xpub
+        .derive_pub(&secp, &path)
+        .map_err(|_| Error::from(Proto::Error::Error_hd_derivation_failed))?;
+
+    let internal_key = XOnlyPublicKey::from(derived.public_key);
+
+    Ok(Address::p2tr_tweaked(taproot_output_key(internal_key, None)?, network).to_string())
+}
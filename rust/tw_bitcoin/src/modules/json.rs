@@ -0,0 +1,712 @@
+//! JSON representation of the `SigningInput` protobuf, allowing (web) clients
+//! to construct signing inputs in JSON. The structure mirrors `BitcoinV2.proto`
+//! one-to-one: field names are kept as-is, `bytes` are hex encoded, enums are
+//! referenced by their variant name and `oneof` fields are objects with a
+//! single key naming the variant. For example:
+//!
+//! ```json
+//! {
+//!     "private_key": "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a",
+//!     "inputs": [{
+//!         "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+//!         "vout": 0,
+//!         "value": 200000000,
+//!         "sighash_type": "All",
+//!         "builder": { "p2wpkh": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f" }
+//!     }],
+//!     "outputs": [{
+//!         "value": 100000000,
+//!         "builder": { "p2wpkh": { "pubkey": "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f" } }
+//!     }],
+//!     "input_selector": "UseAll",
+//!     "disable_change_output": true
+//! }
+//! ```
+//!
+//! The `txid`, `vout` and `value` of each input as well as the `value` of each
+//! output are required, all other fields default to the protobuf defaults.
+
+use crate::aliases::*;
+use crate::{Error, Result};
+use serde::{Deserialize, Deserializer};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// Parses the JSON representation of a `SigningInput`.
+pub fn signing_input_from_json(json: &str) -> Result<Proto::SigningInput<'static>> {
+    let input: JsonSigningInput = serde_json::from_str(json).map_err(|err| {
+        Error::from(Proto::Error::Error_invalid_json).with_context(err.to_string())
+    })?;
+
+    input.into_proto()
+}
+
+/// Hex encoded bytes, optionally prefixed with `0x`.
+#[derive(Default)]
+struct Hex(Vec<u8>);
+
+impl<'de> Deserialize<'de> for Hex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        tw_encoding::hex::decode(&string)
+            .map(Hex)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonSigningInput {
+    #[serde(default)]
+    version: i32,
+    #[serde(default)]
+    private_key: Hex,
+    #[serde(default)]
+    lock_time: Option<JsonLockTime>,
+    inputs: Vec<JsonInput>,
+    outputs: Vec<JsonOutput>,
+    #[serde(default)]
+    input_selector: JsonInputSelector,
+    #[serde(default)]
+    fee_per_vb: u64,
+    #[serde(default)]
+    change_output: Option<JsonOutput>,
+    #[serde(default)]
+    disable_change_output: bool,
+    #[serde(default)]
+    dangerous_use_fixed_schnorr_rng: bool,
+    #[serde(default)]
+    min_change: u64,
+    #[serde(default)]
+    network: JsonNetwork,
+    #[serde(default)]
+    include_preimages: bool,
+    #[serde(default)]
+    bip69_sort_outputs: bool,
+    #[serde(default)]
+    dust_threshold: u64,
+    #[serde(default)]
+    excluded_addresses: Vec<String>,
+    #[serde(default)]
+    expected_recipient_total: u64,
+    #[serde(default)]
+    confirmation_buckets: Vec<JsonConfirmationBucket>,
+    #[serde(default)]
+    min_change_amount: u64,
+    #[serde(default)]
+    dust_relay_feerate: u64,
+    #[serde(default)]
+    data_carrier_size: Option<u32>,
+    #[serde(default)]
+    target_change: u64,
+    #[serde(default)]
+    max_witness_stack_items: u32,
+}
+
+#[derive(Deserialize)]
+struct JsonConfirmationBucket {
+    #[serde(default)]
+    min_fee_per_vb: u64,
+    #[serde(default)]
+    blocks: u32,
+    #[serde(default)]
+    label: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonLockTime {
+    Blocks(u32),
+    Seconds(u32),
+}
+
+#[derive(Default, Deserialize)]
+enum JsonInputSelector {
+    #[default]
+    UseAll,
+    SelectInOrder,
+    SelectAscending,
+    SelectChangeless,
+    SelectTargetChange,
+}
+
+#[derive(Default, Deserialize)]
+enum JsonNetwork {
+    #[default]
+    Bitcoin,
+    Testnet,
+    Signet,
+    Regtest,
+}
+
+#[derive(Default, Deserialize)]
+enum JsonSighashType {
+    #[default]
+    UseDefault,
+    All,
+    None,
+    Single,
+    AllPlusAnyoneCanPay,
+    NonePlusAnyoneCanPay,
+    SinglePlusAnyoneCanPay,
+}
+
+#[derive(Default, Deserialize)]
+enum JsonSigningMethod {
+    #[default]
+    Legacy,
+    Segwit,
+    TaprootAll,
+    TaprootOnePrevout,
+}
+
+#[derive(Deserialize)]
+struct JsonInput {
+    #[serde(default)]
+    private_key: Hex,
+    txid: Hex,
+    vout: u32,
+    #[serde(default)]
+    sequence: u32,
+    #[serde(default)]
+    sequence_enable_zero: bool,
+    value: u64,
+    #[serde(default)]
+    sighash_type: JsonSighashType,
+    #[serde(flatten)]
+    to_recipient: JsonInputRecipient,
+    #[serde(default)]
+    script_sig_size: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonInputRecipient {
+    Builder(JsonInputBuilder),
+    CustomScript(JsonInputScriptWitness),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonInputBuilder {
+    P2sh(Hex),
+    P2pkh(Hex),
+    P2wsh(Hex),
+    P2wpkh(Hex),
+    P2trKeyPath {
+        #[serde(default)]
+        one_prevout: bool,
+        public_key: Hex,
+    },
+    P2trScriptPath {
+        #[serde(default)]
+        one_prevout: bool,
+        payload: Hex,
+        control_block: Hex,
+        #[serde(default)]
+        codesep_pos: Option<u32>,
+    },
+    Brc20Inscribe {
+        #[serde(default)]
+        one_prevout: bool,
+        inscribe_to: Hex,
+        ticker: String,
+        transfer_amount: u64,
+    },
+    OrdinalInscribe {
+        #[serde(default)]
+        one_prevout: bool,
+        inscribe_to: Hex,
+        mime_type: String,
+        payload: Hex,
+        #[serde(default)]
+        parent: Option<JsonInscriptionId>,
+    },
+    CltvVault {
+        expiry: u32,
+        public_key: Hex,
+    },
+    Auto {
+        script_pubkey: Hex,
+        #[serde(default)]
+        public_key: Hex,
+    },
+    P2trPayToContract(JsonPayToContract),
+    HtlcRefund {
+        payment_hash: Hex,
+        recipient_public_key: Hex,
+        refund_public_key: Hex,
+        expiry: u32,
+    },
+    P2trUntweakedKeyPath(Hex),
+    P2pk(Hex),
+}
+
+#[derive(Deserialize)]
+struct JsonInputScriptWitness {
+    script_pubkey: Hex,
+    #[serde(default)]
+    script_sig: Hex,
+    #[serde(default)]
+    witness_items: Vec<Hex>,
+    #[serde(default)]
+    signing_method: JsonSigningMethod,
+}
+
+#[derive(Deserialize)]
+struct JsonOutput {
+    value: u64,
+    #[serde(flatten)]
+    to_recipient: JsonOutputRecipient,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    dust_threshold: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonOutputRecipient {
+    Builder(JsonOutputBuilder),
+    CustomScriptPubkey(Hex),
+    FromAddress(String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonOutputBuilder {
+    P2sh(JsonRedeemScriptOrHash),
+    P2pkh(JsonPublicKeyOrHash),
+    P2wsh(JsonRedeemScriptOrHash),
+    P2wpkh(JsonPublicKeyOrHash),
+    P2trKeyPath(Hex),
+    P2trScriptPath {
+        internal_key: Hex,
+        merkle_root: Hex,
+    },
+    P2trDangerousAssumeTweaked(Hex),
+    Brc20Inscribe {
+        inscribe_to: Hex,
+        ticker: String,
+        transfer_amount: u64,
+    },
+    OrdinalInscribe {
+        inscribe_to: Hex,
+        mime_type: String,
+        payload: Hex,
+        #[serde(default)]
+        parent: Option<JsonInscriptionId>,
+    },
+    CltvVault {
+        expiry: u32,
+        public_key: Hex,
+    },
+    OpReturn(Hex),
+    P2trPayToContract(JsonPayToContract),
+    P2pk(Hex),
+}
+
+#[derive(Deserialize)]
+struct JsonPayToContract {
+    public_key: Hex,
+    contract: Hex,
+    #[serde(default)]
+    tweak_order: JsonTweakOrder,
+}
+
+#[derive(Default, Deserialize)]
+enum JsonTweakOrder {
+    #[default]
+    BeforeTaptweak,
+    AfterTaptweak,
+}
+
+impl JsonPayToContract {
+    fn into_proto(self) -> Proto::PayToContract<'static> {
+        let tweak_order = match self.tweak_order {
+            JsonTweakOrder::BeforeTaptweak => Proto::TweakOrder::BeforeTaptweak,
+            JsonTweakOrder::AfterTaptweak => Proto::TweakOrder::AfterTaptweak,
+        };
+
+        Proto::PayToContract {
+            public_key: self.public_key.0.into(),
+            contract: self.contract.0.into(),
+            tweak_order,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonInscriptionId {
+    txid: Hex,
+    index: u32,
+}
+
+impl JsonInscriptionId {
+    fn into_proto(self) -> Proto::InscriptionId<'static> {
+        Proto::InscriptionId {
+            txid: self.txid.0.into(),
+            index: self.index,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonRedeemScriptOrHash {
+    RedeemScript(Hex),
+    Hash(Hex),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JsonPublicKeyOrHash {
+    Pubkey(Hex),
+    Hash(Hex),
+}
+
+impl JsonSigningInput {
+    fn into_proto(self) -> Result<Proto::SigningInput<'static>> {
+        let lock_time = self.lock_time.map(|lock_time| UtxoProto::LockTime {
+            variant: match lock_time {
+                JsonLockTime::Blocks(blocks) => {
+                    UtxoProto::mod_LockTime::OneOfvariant::blocks(blocks)
+                },
+                JsonLockTime::Seconds(seconds) => {
+                    UtxoProto::mod_LockTime::OneOfvariant::seconds(seconds)
+                },
+            },
+        });
+
+        let input_selector = match self.input_selector {
+            JsonInputSelector::UseAll => UtxoProto::InputSelector::UseAll,
+            JsonInputSelector::SelectInOrder => UtxoProto::InputSelector::SelectInOrder,
+            JsonInputSelector::SelectAscending => UtxoProto::InputSelector::SelectAscending,
+            JsonInputSelector::SelectChangeless => UtxoProto::InputSelector::SelectChangeless,
+            JsonInputSelector::SelectTargetChange => UtxoProto::InputSelector::SelectTargetChange,
+        };
+
+        let network = match self.network {
+            JsonNetwork::Bitcoin => Proto::Network::Bitcoin,
+            JsonNetwork::Testnet => Proto::Network::Testnet,
+            JsonNetwork::Signet => Proto::Network::Signet,
+            JsonNetwork::Regtest => Proto::Network::Regtest,
+        };
+
+        Ok(Proto::SigningInput {
+            version: self.version,
+            private_key: self.private_key.0.into(),
+            lock_time,
+            inputs: self
+                .inputs
+                .into_iter()
+                .map(JsonInput::into_proto)
+                .collect::<Result<Vec<_>>>()?,
+            outputs: self
+                .outputs
+                .into_iter()
+                .map(JsonOutput::into_proto)
+                .collect(),
+            input_selector,
+            fee_per_vb: self.fee_per_vb,
+            change_output: self.change_output.map(JsonOutput::into_proto),
+            disable_change_output: self.disable_change_output,
+            dangerous_use_fixed_schnorr_rng: self.dangerous_use_fixed_schnorr_rng,
+            min_change: self.min_change,
+            network,
+            include_preimages: self.include_preimages,
+            bip69_sort_outputs: self.bip69_sort_outputs,
+            dust_threshold: self.dust_threshold,
+            excluded_addresses: self
+                .excluded_addresses
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            expected_recipient_total: self.expected_recipient_total,
+            confirmation_buckets: self
+                .confirmation_buckets
+                .into_iter()
+                .map(JsonConfirmationBucket::into_proto)
+                .collect(),
+            min_change_amount: self.min_change_amount,
+            dust_relay_feerate: self.dust_relay_feerate,
+            data_carrier_size: self
+                .data_carrier_size
+                .map(|value| Proto::DataCarrierSize { value }),
+            target_change: self.target_change,
+            max_witness_stack_items: self.max_witness_stack_items,
+        })
+    }
+}
+
+impl JsonConfirmationBucket {
+    fn into_proto(self) -> Proto::ConfirmationBucket<'static> {
+        Proto::ConfirmationBucket {
+            min_fee_per_vb: self.min_fee_per_vb,
+            blocks: self.blocks,
+            label: self.label.into(),
+        }
+    }
+}
+
+impl JsonInput {
+    fn into_proto(self) -> Result<Proto::Input<'static>> {
+        // The txid must be a valid transaction hash.
+        if self.txid.0.len() != 32 {
+            return Err(
+                Error::from(Proto::Error::Error_invalid_json).with_context("txid must be 32 bytes")
+            );
+        }
+
+        let sighash_type = match self.sighash_type {
+            JsonSighashType::UseDefault => UtxoProto::SighashType::UseDefault,
+            JsonSighashType::All => UtxoProto::SighashType::All,
+            JsonSighashType::None => UtxoProto::SighashType::None_pb,
+            JsonSighashType::Single => UtxoProto::SighashType::Single,
+            JsonSighashType::AllPlusAnyoneCanPay => UtxoProto::SighashType::AllPlusAnyoneCanPay,
+            JsonSighashType::NonePlusAnyoneCanPay => UtxoProto::SighashType::NonePlusAnyoneCanPay,
+            JsonSighashType::SinglePlusAnyoneCanPay => {
+                UtxoProto::SighashType::SinglePlusAnyoneCanPay
+            },
+        };
+
+        let to_recipient = match self.to_recipient {
+            JsonInputRecipient::Builder(builder) => {
+                ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                    variant: builder.into_proto(),
+                })
+            },
+            JsonInputRecipient::CustomScript(custom) => {
+                let signing_method = match custom.signing_method {
+                    JsonSigningMethod::Legacy => UtxoProto::SigningMethod::Legacy,
+                    JsonSigningMethod::Segwit => UtxoProto::SigningMethod::Segwit,
+                    JsonSigningMethod::TaprootAll => UtxoProto::SigningMethod::TaprootAll,
+                    JsonSigningMethod::TaprootOnePrevout => {
+                        UtxoProto::SigningMethod::TaprootOnePrevout
+                    },
+                };
+
+                ProtoInputRecipient::custom_script(Proto::mod_Input::InputScriptWitness {
+                    script_pubkey: custom.script_pubkey.0.into(),
+                    script_sig: custom.script_sig.0.into(),
+                    witness_items: custom
+                        .witness_items
+                        .into_iter()
+                        .map(|item| item.0.into())
+                        .collect(),
+                    signing_method,
+                })
+            },
+        };
+
+        Ok(Proto::Input {
+            private_key: self.private_key.0.into(),
+            txid: self.txid.0.into(),
+            vout: self.vout,
+            sequence: self.sequence,
+            sequence_enable_zero: self.sequence_enable_zero,
+            value: self.value,
+            sighash_type,
+            to_recipient,
+            script_sig_size: self.script_sig_size,
+        })
+    }
+}
+
+impl JsonInputBuilder {
+    fn into_proto(self) -> ProtoInputBuilder<'static> {
+        match self {
+            JsonInputBuilder::P2sh(script) => ProtoInputBuilder::p2sh(script.0.into()),
+            JsonInputBuilder::P2pkh(pubkey) => ProtoInputBuilder::p2pkh(pubkey.0.into()),
+            JsonInputBuilder::P2wsh(script) => ProtoInputBuilder::p2wsh(script.0.into()),
+            JsonInputBuilder::P2wpkh(pubkey) => ProtoInputBuilder::p2wpkh(pubkey.0.into()),
+            JsonInputBuilder::P2trKeyPath {
+                one_prevout,
+                public_key,
+            } => ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                one_prevout,
+                public_key: public_key.0.into(),
+            }),
+            JsonInputBuilder::P2trScriptPath {
+                one_prevout,
+                payload,
+                control_block,
+                codesep_pos,
+            } => ProtoInputBuilder::p2tr_script_path(Proto::mod_Input::InputTaprootScriptPath {
+                one_prevout,
+                payload: payload.0.into(),
+                control_block: control_block.0.into(),
+                codesep_pos: codesep_pos
+                    .map(|position| UtxoProto::CodeSeparatorPosition { position }),
+            }),
+            JsonInputBuilder::Brc20Inscribe {
+                one_prevout,
+                inscribe_to,
+                ticker,
+                transfer_amount,
+            } => ProtoInputBuilder::brc20_inscribe(Proto::mod_Input::InputBrc20Inscription {
+                one_prevout,
+                inscribe_to: inscribe_to.0.into(),
+                ticker: ticker.into(),
+                transfer_amount,
+            }),
+            JsonInputBuilder::OrdinalInscribe {
+                one_prevout,
+                inscribe_to,
+                mime_type,
+                payload,
+                parent,
+            } => ProtoInputBuilder::ordinal_inscribe(Proto::mod_Input::InputOrdinalInscription {
+                one_prevout,
+                inscribe_to: inscribe_to.0.into(),
+                mime_type: mime_type.into(),
+                payload: payload.0.into(),
+                parent: parent.map(JsonInscriptionId::into_proto),
+            }),
+            JsonInputBuilder::CltvVault { expiry, public_key } => {
+                ProtoInputBuilder::cltv_vault(Proto::mod_Input::InputCltvVault {
+                    expiry,
+                    public_key: public_key.0.into(),
+                })
+            },
+            JsonInputBuilder::Auto {
+                script_pubkey,
+                public_key,
+            } => ProtoInputBuilder::auto(Proto::mod_Input::InputAuto {
+                script_pubkey: script_pubkey.0.into(),
+                public_key: public_key.0.into(),
+            }),
+            JsonInputBuilder::P2trPayToContract(p2c) => {
+                ProtoInputBuilder::p2tr_pay_to_contract(p2c.into_proto())
+            },
+            JsonInputBuilder::HtlcRefund {
+                payment_hash,
+                recipient_public_key,
+                refund_public_key,
+                expiry,
+            } => ProtoInputBuilder::htlc_refund(Proto::mod_Input::InputHtlcRefund {
+                payment_hash: payment_hash.0.into(),
+                recipient_public_key: recipient_public_key.0.into(),
+                refund_public_key: refund_public_key.0.into(),
+                expiry,
+            }),
+            JsonInputBuilder::P2trUntweakedKeyPath(pubkey) => {
+                ProtoInputBuilder::p2tr_untweaked_key_path(pubkey.0.into())
+            },
+            JsonInputBuilder::P2pk(pubkey) => ProtoInputBuilder::p2pk(pubkey.0.into()),
+        }
+    }
+}
+
+impl JsonOutput {
+    fn into_proto(self) -> Proto::Output<'static> {
+        let to_recipient = match self.to_recipient {
+            JsonOutputRecipient::Builder(builder) => {
+                ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                    variant: builder.into_proto(),
+                })
+            },
+            JsonOutputRecipient::CustomScriptPubkey(script) => {
+                ProtoOutputRecipient::custom_script_pubkey(script.0.into())
+            },
+            JsonOutputRecipient::FromAddress(address) => {
+                ProtoOutputRecipient::from_address(address.into())
+            },
+        };
+
+        Proto::Output {
+            value: self.value,
+            to_recipient,
+            label: self.label.into(),
+            dust_threshold: self.dust_threshold,
+        }
+    }
+}
+
+impl JsonOutputBuilder {
+    fn into_proto(self) -> ProtoOutputBuilder<'static> {
+        match self {
+            JsonOutputBuilder::P2sh(script_or_hash) => {
+                ProtoOutputBuilder::p2sh(script_or_hash.into_proto())
+            },
+            JsonOutputBuilder::P2pkh(pubkey_or_hash) => {
+                ProtoOutputBuilder::p2pkh(pubkey_or_hash.into_proto())
+            },
+            JsonOutputBuilder::P2wsh(script_or_hash) => {
+                ProtoOutputBuilder::p2wsh(script_or_hash.into_proto())
+            },
+            JsonOutputBuilder::P2wpkh(pubkey_or_hash) => {
+                ProtoOutputBuilder::p2wpkh(pubkey_or_hash.into_proto())
+            },
+            JsonOutputBuilder::P2trKeyPath(pubkey) => {
+                ProtoOutputBuilder::p2tr_key_path(pubkey.0.into())
+            },
+            JsonOutputBuilder::P2trScriptPath {
+                internal_key,
+                merkle_root,
+            } => ProtoOutputBuilder::p2tr_script_path(Proto::mod_Output::OutputTaprootScriptPath {
+                internal_key: internal_key.0.into(),
+                merkle_root: merkle_root.0.into(),
+            }),
+            JsonOutputBuilder::P2trDangerousAssumeTweaked(tweaked) => {
+                ProtoOutputBuilder::p2tr_dangerous_assume_tweaked(tweaked.0.into())
+            },
+            JsonOutputBuilder::Brc20Inscribe {
+                inscribe_to,
+                ticker,
+                transfer_amount,
+            } => ProtoOutputBuilder::brc20_inscribe(Proto::mod_Output::OutputBrc20Inscription {
+                inscribe_to: inscribe_to.0.into(),
+                ticker: ticker.into(),
+                transfer_amount,
+            }),
+            JsonOutputBuilder::OrdinalInscribe {
+                inscribe_to,
+                mime_type,
+                payload,
+                parent,
+            } => {
+                ProtoOutputBuilder::ordinal_inscribe(Proto::mod_Output::OutputOrdinalInscription {
+                    inscribe_to: inscribe_to.0.into(),
+                    mime_type: mime_type.into(),
+                    payload: payload.0.into(),
+                    parent: parent.map(JsonInscriptionId::into_proto),
+                })
+            },
+            JsonOutputBuilder::CltvVault { expiry, public_key } => {
+                ProtoOutputBuilder::cltv_vault(Proto::mod_Output::OutputCltvVault {
+                    expiry,
+                    public_key: public_key.0.into(),
+                })
+            },
+            JsonOutputBuilder::OpReturn(data) => ProtoOutputBuilder::op_return(data.0.into()),
+            JsonOutputBuilder::P2trPayToContract(p2c) => {
+                ProtoOutputBuilder::p2tr_pay_to_contract(p2c.into_proto())
+            },
+            JsonOutputBuilder::P2pk(pubkey) => ProtoOutputBuilder::p2pk(pubkey.0.into()),
+        }
+    }
+}
+
+impl JsonRedeemScriptOrHash {
+    fn into_proto(self) -> Proto::mod_Output::OutputRedeemScriptOrHash<'static> {
+        let variant = match self {
+            JsonRedeemScriptOrHash::RedeemScript(script) => {
+                ProtoRedeemScriptOrHash::redeem_script(script.0.into())
+            },
+            JsonRedeemScriptOrHash::Hash(hash) => ProtoRedeemScriptOrHash::hash(hash.0.into()),
+        };
+
+        Proto::mod_Output::OutputRedeemScriptOrHash { variant }
+    }
+}
+
+impl JsonPublicKeyOrHash {
+    fn into_proto(self) -> Proto::ToPublicKeyOrHash<'static> {
+        let to_address = match self {
+            JsonPublicKeyOrHash::Pubkey(pubkey) => ProtoPubkeyOrHash::pubkey(pubkey.0.into()),
+            JsonPublicKeyOrHash::Hash(hash) => ProtoPubkeyOrHash::hash(hash.0.into()),
+        };
+
+        Proto::ToPublicKeyOrHash { to_address }
+    }
+}
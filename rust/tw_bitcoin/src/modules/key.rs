@@ -0,0 +1,75 @@
+use crate::modules::address::ScriptType;
+use crate::modules::transactions::taproot_output_key;
+use crate::{Error, Result};
+use bitcoin::{Address, Network, PrivateKey};
+use secp256k1::rand::Rng;
+use secp256k1::{PublicKey, Secp256k1};
+use tw_proto::BitcoinV2::Proto;
+
+/// Converts the given uncompressed (65-byte) public key into its compressed
+/// (33-byte) form. The point must be on the curve.
+pub fn compress(pubkey: &[u8]) -> Result<[u8; 33]> {
+    if pubkey.len() != 65 {
+        return Err(Error::from(Proto::Error::Error_invalid_public_key)
+            .with_context("expected a 65-byte uncompressed public key"));
+    }
+
+    let pubkey = PublicKey::from_slice(pubkey)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+    Ok(pubkey.serialize())
+}
+
+/// Converts the given compressed (33-byte) public key into its uncompressed
+/// (65-byte) form, as used by legacy P2PKH addresses. The point must be on the
+/// curve.
+pub fn decompress(pubkey: &[u8]) -> Result<[u8; 65]> {
+    if pubkey.len() != 33 {
+        return Err(Error::from(Proto::Error::Error_invalid_public_key)
+            .with_context("expected a 33-byte compressed public key"));
+    }
+
+    let pubkey = PublicKey::from_slice(pubkey)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?;
+
+    Ok(pubkey.serialize_uncompressed())
+}
+
+/// Generates a new random private key, for example for ephemeral or refund
+/// keys. Returns the private key in the Wallet Import Format (WIF) and its
+/// address of the given script type. `P2sh` refers to nested P2WPKH.
+pub fn generate(network: Network, script_type: ScriptType) -> Result<(String, String)> {
+    generate_with_rng(&mut secp256k1::rand::thread_rng(), network, script_type)
+}
+
+/// Like [`generate`], but draws the private key from the given RNG.
+pub fn generate_with_rng<R: Rng + ?Sized>(
+    rng: &mut R,
+    network: Network,
+    script_type: ScriptType,
+) -> Result<(String, String)> {
+    let secp = Secp256k1::new();
+    let (secret, pubkey) = secp.generate_keypair(rng);
+
+    let private_key = PrivateKey::new(secret, network);
+    let pubkey = bitcoin::PublicKey::new(pubkey);
+
+    let addr = match script_type {
+        ScriptType::P2pkh => Address::p2pkh(&pubkey, network),
+        ScriptType::P2sh => Address::p2shwpkh(&pubkey, network)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?,
+        ScriptType::P2wpkh => Address::p2wpkh(&pubkey, network)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_public_key))?,
+        ScriptType::P2tr => {
+            Address::p2tr_tweaked(taproot_output_key(pubkey.inner.into(), None)?, network)
+        },
+        ScriptType::P2wsh | ScriptType::UnknownWitness => {
+            return Err(
+                Error::from(Proto::Error::Error_unsupported_address_recipient)
+                    .with_context(format!("{script_type:?} is not a single-key script type")),
+            )
+        },
+    };
+
+    Ok((private_key.to_wif(), addr.to_string()))
+}
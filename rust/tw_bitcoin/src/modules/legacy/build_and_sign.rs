@@ -66,6 +66,7 @@ pub fn taproot_build_and_sign_transaction(
         outputs.push(Proto::Output {
             value: output.amount as u64,
             to_recipient: ProtoOutputRecipient::custom_script_pubkey(output.script),
+            ..Default::default()
         })
     }
 
@@ -90,6 +91,7 @@ pub fn taproot_build_and_sign_transaction(
         change_output: None,
         disable_change_output: true,
         dangerous_use_fixed_schnorr_rng: false,
+        ..Default::default()
     };
 
     // Build and sign the Bitcoin transaction.
@@ -150,6 +152,89 @@ pub fn taproot_build_and_sign_transaction(
     Ok(legacy_output)
 }
 
+/// Creates the signing input of the reveal transaction, which spends the commit
+/// output as returned by the (deprecated)
+/// `tw_bitcoin_legacy_build_brc20_transfer_inscription` to the given
+/// destination output. The Taproot payload of the inscription is carried in
+/// the `spendingScript` field of the legacy output, while `txid` (in REVERSED
+/// order) and `vout` reference the funded commit output. The private key must
+/// be set by the caller.
+pub fn brc20_reveal_signing_input(
+    my_pubkey: PublicKey,
+    inscription: &LegacyProto::TransactionOutput,
+    txid: &[u8],
+    vout: u32,
+    destination: &LegacyProto::TransactionOutput,
+    fee_per_vb: u64,
+) -> Result<Proto::SigningInput<'static>> {
+    let input = Proto::Input {
+        txid: txid.to_vec().into(),
+        vout,
+        value: inscription.value as u64,
+        sequence: u32::MAX,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(script_path_input_builder(
+            my_pubkey,
+            &inscription.spendingScript,
+        )?),
+        ..Default::default()
+    };
+
+    let output = Proto::Output {
+        value: destination.value as u64,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(
+            destination.script.to_vec().into(),
+        ),
+        ..Default::default()
+    };
+
+    Ok(Proto::SigningInput {
+        version: 2,
+        inputs: vec![input],
+        outputs: vec![output],
+        input_selector: UtxoProto::InputSelector::SelectInOrder,
+        fee_per_vb,
+        disable_change_output: true,
+        ..Default::default()
+    })
+}
+
+/// Convenience function, creates the Taproot script-path input builder that
+/// spends the given (legacy) spending script.
+fn script_path_input_builder(
+    my_pubkey: PublicKey,
+    spending_script: &[u8],
+) -> Result<Proto::mod_Input::InputBuilder<'static>> {
+    // The spending script must to be empty.
+    if spending_script.is_empty() {
+        return Err(Error::from(
+            Proto::Error::Error_legacy_no_spending_script_provided,
+        ));
+    }
+
+    let payload = ScriptBuf::from_bytes(spending_script.to_vec());
+
+    let xonly = XOnlyPublicKey::from(my_pubkey.inner);
+    let spend_info = TaprootSpendInfo::from_node_info(
+        &secp256k1::Secp256k1::new(),
+        xonly,
+        NodeInfo::new_leaf_with_ver(payload.clone(), LeafVersion::TapScript),
+    );
+
+    let control_block = spend_info
+        .control_block(&(payload, LeafVersion::TapScript))
+        .expect("failed to construct control block");
+
+    Ok(Proto::mod_Input::InputBuilder {
+        variant: ProtoInputBuilder::p2tr_script_path(Proto::mod_Input::InputTaprootScriptPath {
+            one_prevout: false,
+            payload: spending_script.to_vec().into(),
+            control_block: control_block.serialize().into(),
+            codesep_pos: None,
+        }),
+    })
+}
+
 /// Convenience function.
 fn input_from_legacy_utxo(
     my_pubkey: PublicKey,
@@ -173,35 +258,7 @@ fn input_from_legacy_utxo(
         },
         LegacyProto::TransactionVariant::BRC20TRANSFER
         | LegacyProto::TransactionVariant::NFTINSCRIPTION => {
-            // The spending script must to be empty.
-            if utxo.spendingScript.is_empty() {
-                return Err(Error::from(
-                    Proto::Error::Error_legacy_no_spending_script_provided,
-                ));
-            }
-
-            let spending_script = ScriptBuf::from_bytes(utxo.spendingScript.to_vec());
-
-            let xonly = XOnlyPublicKey::from(my_pubkey.inner);
-            let spend_info = TaprootSpendInfo::from_node_info(
-                &secp256k1::Secp256k1::new(),
-                xonly,
-                NodeInfo::new_leaf_with_ver(spending_script.clone(), LeafVersion::TapScript),
-            );
-
-            let control_block = spend_info
-                .control_block(&(spending_script, LeafVersion::TapScript))
-                .expect("failed to construct control block");
-
-            Proto::mod_Input::InputBuilder {
-                variant: ProtoInputBuilder::p2tr_script_path(
-                    Proto::mod_Input::InputTaprootScriptPath {
-                        one_prevout: false,
-                        payload: utxo.spendingScript.to_vec().into(),
-                        control_block: control_block.serialize().into(),
-                    },
-                ),
-            }
+            script_path_input_builder(my_pubkey, &utxo.spendingScript)?
         },
     };
 
@@ -241,5 +298,6 @@ fn input_from_legacy_utxo(
         sequence_enable_zero: false,
         sighash_type,
         to_recipient: ProtoInputRecipient::builder(input_builder),
+        script_sig_size: 0,
     })
 }
@@ -1,5 +1,35 @@
+pub mod address;
+pub mod anchor;
+pub mod anti_fee_sniping;
+pub mod bip21;
+pub mod coinjoin;
+pub mod commitment;
+pub mod consolidate;
+pub mod decode;
+pub mod descriptor;
+pub mod fee;
+pub mod fee_bump;
+pub mod hd;
+pub mod json;
+pub mod key;
 pub mod legacy;
+pub mod multi_account;
+pub mod network;
+pub mod pay_to_contract;
 pub mod plan_builder;
+pub mod provenance;
+pub mod psbt;
+pub mod runes;
+pub mod sighash;
 pub mod signer;
+pub mod signing_status;
+pub mod silent_payments;
+pub mod size;
+pub mod split;
+pub mod standardness;
+pub mod swap;
+pub mod sweep;
 pub mod transactions;
+pub mod transfer;
 mod utils;
+pub mod varint;
@@ -0,0 +1,134 @@
+use crate::aliases::*;
+use crate::modules::consolidate::{INPUT_BASE_WEIGHT, TX_OVERHEAD_WEIGHT};
+use crate::modules::standardness::{dust_threshold, DEFAULT_DUST_RELAY_FEERATE};
+use crate::modules::transactions::{InputBuilder, OutputBuilder};
+use crate::{Error, Result};
+use bitcoin::{Script, ScriptBuf};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// An account of a multi-account wallet, spending its own UTXOs.
+#[derive(Debug, Clone)]
+pub struct Account<'a> {
+    /// The UTXOs of the account.
+    pub inputs: Vec<Proto::Input<'a>>,
+    /// The outputs paid for by the account.
+    pub outputs: Vec<Proto::Output<'a>>,
+    /// The scriptPubkey of the account's change output.
+    pub change_script: ScriptBuf,
+}
+
+/// How the fee of the transaction is split among the accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeAllocation {
+    /// Each account pays the fee of its own inputs and outputs (including the
+    /// change output) plus an equal share of the transaction overhead.
+    Proportional,
+    /// The account at the given index pays the whole fee.
+    Payer(usize),
+}
+
+/// Creates the signing input of a transaction that spends the UTXOs of
+/// multiple accounts, with the outputs of the accounts followed by one change
+/// output per account, in the given order. This way, the funds of the
+/// accounts are not commingled. The fee (at `fee_rate`, in sat/vbyte) is
+/// allocated as specified by `allocation`, change below the dust threshold is
+/// donated to the miner fee.
+pub fn build<'a>(
+    accounts: Vec<Account<'a>>,
+    fee_rate: u64,
+    allocation: FeeAllocation,
+) -> Result<Proto::SigningInput<'a>> {
+    if accounts.is_empty() {
+        return Err(Error::from(Proto::Error::Error_missing_recipient));
+    }
+
+    if let FeeAllocation::Payer(payer) = allocation {
+        if payer >= accounts.len() {
+            return Err(
+                Error::from(Proto::Error::Error_invalid_fee_payer).with_context(format!(
+                    "fee payer {payer} is out of range for {} accounts",
+                    accounts.len()
+                )),
+            );
+        }
+    }
+
+    let mut account_weights = vec![];
+    for account in &accounts {
+        let mut weight = output_weight(&account.change_script);
+        for input in &account.inputs {
+            weight += INPUT_BASE_WEIGHT + InputBuilder::utxo_from_proto(input)?.weight_estimate;
+        }
+        for output in &account.outputs {
+            let utxo = OutputBuilder::utxo_from_proto(output)?;
+            weight += output_weight(Script::from_bytes(&utxo.script_pubkey));
+        }
+        account_weights.push(weight);
+    }
+
+    let overhead_fee = (TX_OVERHEAD_WEIGHT + 3) / 4 * fee_rate;
+    let overhead_share = (overhead_fee + accounts.len() as u64 - 1) / accounts.len() as u64;
+    let total_weight = TX_OVERHEAD_WEIGHT + account_weights.iter().sum::<u64>();
+
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    let mut change_outputs = vec![];
+
+    for (index, (account, weight)) in accounts.into_iter().zip(account_weights).enumerate() {
+        let fee = match allocation {
+            FeeAllocation::Proportional => (weight + 3) / 4 * fee_rate + overhead_share,
+            FeeAllocation::Payer(payer) if payer == index => (total_weight + 3) / 4 * fee_rate,
+            FeeAllocation::Payer(_) => 0,
+        };
+
+        let total_input = sum_values(account.inputs.iter().map(|input| input.value))?;
+        let total_output = sum_values(account.outputs.iter().map(|output| output.value))?;
+
+        let change = total_input
+            .checked_sub(total_output)
+            .and_then(|change| change.checked_sub(fee))
+            .ok_or_else(|| {
+                Error::from(Proto::Error::Error_utxo_insufficient_inputs).with_context(format!(
+                    "account {index} can not cover its outputs and the fee of {fee} sats"
+                ))
+            })?;
+
+        inputs.extend(account.inputs);
+        outputs.extend(account.outputs);
+
+        if change >= dust_threshold(&account.change_script, DEFAULT_DUST_RELAY_FEERATE) {
+            change_outputs.push(Proto::Output {
+                value: change,
+                to_recipient: ProtoOutputRecipient::custom_script_pubkey(
+                    account.change_script.to_bytes().into(),
+                ),
+                ..Default::default()
+            });
+        }
+    }
+
+    outputs.extend(change_outputs);
+
+    Ok(Proto::SigningInput {
+        inputs,
+        outputs,
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: fee_rate,
+        disable_change_output: true,
+        ..Default::default()
+    })
+}
+
+fn sum_values(values: impl Iterator<Item = u64>) -> Result<u64> {
+    values.try_fold(0u64, |total, value| {
+        total
+            .checked_add(value)
+            .ok_or_else(|| Error::from(Proto::Error::Error_utxo_amount_overflow))
+    })
+}
+
+// Value, length and scriptPubkey (scale factor applied).
+fn output_weight(script: &Script) -> u64 {
+    4 * (8 + 1 + script.len() as u64)
+}
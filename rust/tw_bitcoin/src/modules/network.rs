@@ -0,0 +1,39 @@
+use crate::{Error, Result};
+use bitcoin::bip32::ExtendedPrivKey;
+use bitcoin::{Network, PrivateKey};
+use std::str::FromStr;
+use tw_proto::BitcoinV2::Proto;
+
+pub fn network_from_proto(network: Proto::Network) -> Network {
+    match network {
+        Proto::Network::Bitcoin => Network::Bitcoin,
+        Proto::Network::Testnet => Network::Testnet,
+        Proto::Network::Signet => Network::Signet,
+        Proto::Network::Regtest => Network::Regtest,
+    }
+}
+
+/// Returns the network of the given Segwit HRP. Note that Signet shares the
+/// `tb` HRP with Testnet, which resolves to Testnet.
+pub fn network_from_hrp(hrp: &str) -> Option<Network> {
+    match hrp {
+        "bc" => Some(Network::Bitcoin),
+        "tb" => Some(Network::Testnet),
+        "bcrt" => Some(Network::Regtest),
+        _ => None,
+    }
+}
+
+/// Decodes a private key in the Wallet Import Format (WIF). Mainnet keys start
+/// with `5` (uncompressed), `K` or `L` (compressed), Testnet/Signet keys with
+/// `9` (uncompressed) or `c` (compressed).
+pub fn parse_wif(wif: &str) -> Result<PrivateKey> {
+    PrivateKey::from_wif(wif).map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))
+}
+
+/// Decodes an extended private key, either `xprv` (mainnet) or `tprv`
+/// (Testnet/Signet).
+pub fn parse_xprv(xprv: &str) -> Result<ExtendedPrivKey> {
+    ExtendedPrivKey::from_str(xprv)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_private_key))
+}
@@ -0,0 +1,85 @@
+use crate::modules::transactions::{taproot_keypair, taproot_output_key};
+use crate::{Error, Result};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::key::TweakedPublicKey;
+use secp256k1::{KeyPair, Scalar, Secp256k1, XOnlyPublicKey};
+use tw_proto::BitcoinV2::Proto;
+
+/// Returns the pay-to-contract tweak `SHA256(P || contract)` of the given
+/// (x-only) key.
+pub fn contract_tweak(key: &XOnlyPublicKey, contract: &[u8]) -> Result<Scalar> {
+    let mut engine = sha256::Hash::engine();
+    engine.input(&key.serialize());
+    engine.input(contract);
+    let hash = sha256::Hash::from_engine(engine);
+
+    Scalar::from_be_bytes(hash.to_byte_array())
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_contract_tweak))
+}
+
+/// Returns the Taproot output key that commits to the given contract.
+///
+/// With `BeforeTaptweak`, the contract is committed to the internal key
+/// `P' = P + SHA256(P || c)G`, which is then tweaked as a regular key-path
+/// output (without script tree). With `AfterTaptweak`, the regular output key
+/// `Q` is derived first and then committed to the contract as
+/// `Q' = Q + SHA256(Q || c)G`.
+pub fn output_key(
+    internal_key: XOnlyPublicKey,
+    contract: &[u8],
+    tweak_order: Proto::TweakOrder,
+) -> Result<TweakedPublicKey> {
+    let secp = Secp256k1::new();
+
+    match tweak_order {
+        Proto::TweakOrder::BeforeTaptweak => {
+            let tweak = contract_tweak(&internal_key, contract)?;
+            let (committed, _) = internal_key
+                .add_tweak(&secp, &tweak)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_contract_tweak))?;
+
+            taproot_output_key(committed, None)
+        },
+        Proto::TweakOrder::AfterTaptweak => {
+            let tweaked = taproot_output_key(internal_key, None)?.to_inner();
+            let tweak = contract_tweak(&tweaked, contract)?;
+            let (committed, _) = tweaked
+                .add_tweak(&secp, &tweak)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_contract_tweak))?;
+
+            Ok(TweakedPublicKey::dangerous_assume_tweaked(committed))
+        },
+    }
+}
+
+/// Returns the keypair that signs for the output key as returned by
+/// [`output_key`]. The keypair is fully tweaked, meaning that no additional
+/// Taproot tweak must be applied when signing.
+pub fn signing_keypair(
+    keypair: &KeyPair,
+    contract: &[u8],
+    tweak_order: Proto::TweakOrder,
+) -> Result<KeyPair> {
+    let secp = Secp256k1::new();
+
+    // Note that the x-only tweaks negate the secret key if required, so the
+    // resulting keypair always matches the (even) x-only output key.
+    match tweak_order {
+        Proto::TweakOrder::BeforeTaptweak => {
+            let tweak = contract_tweak(&keypair.x_only_public_key().0, contract)?;
+            let committed = keypair
+                .add_xonly_tweak(&secp, &tweak)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_contract_tweak))?;
+
+            taproot_keypair(&committed, None)
+        },
+        Proto::TweakOrder::AfterTaptweak => {
+            let tweaked = taproot_keypair(keypair, None)?;
+            let tweak = contract_tweak(&tweaked.x_only_public_key().0, contract)?;
+
+            tweaked
+                .add_xonly_tweak(&secp, &tweak)
+                .map_err(|_| Error::from(Proto::Error::Error_invalid_contract_tweak))
+        },
+    }
+}
@@ -117,6 +117,7 @@ impl BitcoinPlanBuilder {
                     },
                 ),
             }),
+            ..Default::default()
         };
 
         let brc20_output_value = brc20_output.value;
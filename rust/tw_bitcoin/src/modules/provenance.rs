@@ -0,0 +1,49 @@
+use crate::aliases::*;
+use crate::{Error, Result};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// Creates the signing input of the reveal transaction of a child inscription
+/// (provenance). The reveal input must be an `ordinal_inscribe` builder that
+/// references the parent inscription.
+///
+/// The UTXO holding the parent inscription is spent as first input and
+/// returned to `parent_recipient` as first output with its value unchanged,
+/// so the parent inscription stays on the same sat. The child is inscribed on
+/// the first sat of the reveal input, which becomes the first sat of
+/// `child_output`. The remainder of the reveal input pays the fee.
+pub fn build_child_reveal<'a>(
+    parent_utxo: Proto::Input<'a>,
+    parent_recipient: ProtoOutputRecipient<'a>,
+    reveal_input: Proto::Input<'a>,
+    child_output: Proto::Output<'a>,
+    fee_rate: u64,
+) -> Result<Proto::SigningInput<'a>> {
+    let references_parent = match &reveal_input.to_recipient {
+        ProtoInputRecipient::builder(builder) => matches!(
+            &builder.variant,
+            ProtoInputBuilder::ordinal_inscribe(ordinal) if ordinal.parent.is_some()
+        ),
+        _ => false,
+    };
+
+    if !references_parent {
+        return Err(Error::from(Proto::Error::Error_missing_inscription)
+            .with_context("the reveal input must inscribe a child of the parent inscription"));
+    }
+
+    let parent_output = Proto::Output {
+        value: parent_utxo.value,
+        to_recipient: parent_recipient,
+        ..Default::default()
+    };
+
+    Ok(Proto::SigningInput {
+        inputs: vec![parent_utxo, reveal_input],
+        outputs: vec![parent_output, child_output],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: fee_rate,
+        disable_change_output: true,
+        ..Default::default()
+    })
+}
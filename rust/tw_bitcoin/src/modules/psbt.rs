@@ -0,0 +1,388 @@
+use crate::{Error, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::{deserialize, serialize, Decodable};
+use bitcoin::psbt::PartiallySignedTransaction;
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, VarInt, Witness};
+use tw_proto::BitcoinV2::Proto;
+
+const PSBT_MAGIC: &[u8] = b"psbt\xff";
+
+// Global key types.
+const PSBT_GLOBAL_UNSIGNED_TX: u8 = 0x00;
+const PSBT_GLOBAL_TX_VERSION: u8 = 0x02;
+const PSBT_GLOBAL_FALLBACK_LOCKTIME: u8 = 0x03;
+const PSBT_GLOBAL_INPUT_COUNT: u8 = 0x04;
+const PSBT_GLOBAL_OUTPUT_COUNT: u8 = 0x05;
+const PSBT_GLOBAL_TX_MODIFIABLE: u8 = 0x06;
+const PSBT_GLOBAL_VERSION: u8 = 0xfb;
+
+// Input key types.
+const PSBT_IN_PREVIOUS_TXID: u8 = 0x0e;
+const PSBT_IN_OUTPUT_INDEX: u8 = 0x0f;
+const PSBT_IN_SEQUENCE: u8 = 0x10;
+const PSBT_IN_REQUIRED_TIME_LOCKTIME: u8 = 0x11;
+const PSBT_IN_REQUIRED_HEIGHT_LOCKTIME: u8 = 0x12;
+
+// Output key types.
+const PSBT_OUT_AMOUNT: u8 = 0x03;
+const PSBT_OUT_SCRIPT: u8 = 0x04;
+
+// A key-value pair of a PSBT map, where the key starts with the key type.
+type Pair = (Vec<u8>, Vec<u8>);
+
+// The key-value maps of a PSBT, independent of its version.
+struct RawPsbt {
+    global: Vec<Pair>,
+    inputs: Vec<Vec<Pair>>,
+    outputs: Vec<Vec<Pair>>,
+}
+
+/// Encodes the PSBT as version 2 (BIP-370), as required by some hardware
+/// wallets. Unlike version 0, the unsigned transaction is not embedded but
+/// described by explicit fields, i.e. the transaction version, lock time and
+/// the number of inputs and outputs in the global map, the outpoint and
+/// sequence of each input and the amount and scriptPubkey of each output. All
+/// other fields are retained.
+pub fn to_psbt_v2(psbt: &PartiallySignedTransaction) -> Result<Vec<u8>> {
+    let tx = &psbt.unsigned_tx;
+    let raw = RawPsbt::decode(&psbt.serialize(), |_| Ok((tx.input.len(), tx.output.len())))?;
+
+    let mut global = vec![
+        pair(PSBT_GLOBAL_TX_VERSION, tx.version.to_le_bytes().to_vec()),
+        pair(
+            PSBT_GLOBAL_FALLBACK_LOCKTIME,
+            tx.lock_time.to_consensus_u32().to_le_bytes().to_vec(),
+        ),
+        pair(
+            PSBT_GLOBAL_INPUT_COUNT,
+            serialize(&VarInt(tx.input.len() as u64)),
+        ),
+        pair(
+            PSBT_GLOBAL_OUTPUT_COUNT,
+            serialize(&VarInt(tx.output.len() as u64)),
+        ),
+        pair(PSBT_GLOBAL_VERSION, 2u32.to_le_bytes().to_vec()),
+    ];
+    global.extend(
+        raw.global
+            .into_iter()
+            .filter(|(key, _)| !matches!(key[0], PSBT_GLOBAL_UNSIGNED_TX | PSBT_GLOBAL_VERSION)),
+    );
+
+    let inputs = raw
+        .inputs
+        .into_iter()
+        .zip(&tx.input)
+        .map(|(pairs, txin)| {
+            let mut input = vec![
+                pair(PSBT_IN_PREVIOUS_TXID, serialize(&txin.previous_output.txid)),
+                pair(
+                    PSBT_IN_OUTPUT_INDEX,
+                    txin.previous_output.vout.to_le_bytes().to_vec(),
+                ),
+                pair(PSBT_IN_SEQUENCE, txin.sequence.0.to_le_bytes().to_vec()),
+            ];
+            input.extend(pairs);
+            input
+        })
+        .collect();
+
+    let outputs = raw
+        .outputs
+        .into_iter()
+        .zip(&tx.output)
+        .map(|(pairs, txout)| {
+            let mut output = vec![
+                pair(PSBT_OUT_AMOUNT, txout.value.to_le_bytes().to_vec()),
+                pair(PSBT_OUT_SCRIPT, txout.script_pubkey.to_bytes()),
+            ];
+            output.extend(pairs);
+            output
+        })
+        .collect();
+
+    Ok(RawPsbt {
+        global,
+        inputs,
+        outputs,
+    }
+    .encode())
+}
+
+/// Decodes a version 2 (BIP-370) PSBT and reconstructs the unsigned
+/// transaction, i.e. converts it to a version 0 PSBT. The lock time is derived
+/// from the required lock times of the inputs, if any, or the fallback lock
+/// time otherwise.
+pub fn from_psbt_v2(bytes: &[u8]) -> Result<PartiallySignedTransaction> {
+    let raw = RawPsbt::decode(bytes, |global| {
+        if find_u32(global, PSBT_GLOBAL_VERSION)? != Some(2) {
+            return Err(invalid_psbt("expected a version 2 PSBT"));
+        }
+
+        let count = |key_type: u8| -> Result<usize> {
+            let value = find(global, key_type)
+                .ok_or_else(|| invalid_psbt("missing input or output count"))?;
+            let count: VarInt =
+                deserialize(value).map_err(|_| invalid_psbt("invalid input or output count"))?;
+            Ok(count.0 as usize)
+        };
+
+        Ok((
+            count(PSBT_GLOBAL_INPUT_COUNT)?,
+            count(PSBT_GLOBAL_OUTPUT_COUNT)?,
+        ))
+    })?;
+
+    let version = find_u32(&raw.global, PSBT_GLOBAL_TX_VERSION)?
+        .ok_or_else(|| invalid_psbt("missing transaction version"))?;
+
+    let input = raw
+        .inputs
+        .iter()
+        .map(|pairs| {
+            let txid = find(pairs, PSBT_IN_PREVIOUS_TXID)
+                .and_then(|value| deserialize::<Txid>(value).ok())
+                .ok_or_else(|| invalid_psbt("missing or invalid previous txid"))?;
+            let vout = find_u32(pairs, PSBT_IN_OUTPUT_INDEX)?
+                .ok_or_else(|| invalid_psbt("missing output index"))?;
+            let sequence = find_u32(pairs, PSBT_IN_SEQUENCE)?.unwrap_or(u32::MAX);
+
+            Ok(TxIn {
+                previous_output: OutPoint { txid, vout },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence(sequence),
+                witness: Witness::new(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let output = raw
+        .outputs
+        .iter()
+        .map(|pairs| {
+            let value = find(pairs, PSBT_OUT_AMOUNT)
+                .and_then(|value| <[u8; 8]>::try_from(value).ok())
+                .map(i64::from_le_bytes)
+                .and_then(|value| u64::try_from(value).ok())
+                .ok_or_else(|| invalid_psbt("missing or invalid output amount"))?;
+            let script_pubkey = find(pairs, PSBT_OUT_SCRIPT)
+                .ok_or_else(|| invalid_psbt("missing output script"))?;
+
+            Ok(TxOut {
+                value,
+                script_pubkey: ScriptBuf::from_bytes(script_pubkey.to_vec()),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let tx = Transaction {
+        version: version as i32,
+        lock_time: lock_time(&raw)?,
+        input,
+        output,
+    };
+
+    // Drop the fields that are replaced by the unsigned transaction.
+    let retain = |pairs: Vec<Pair>, key_types: &[u8]| -> Vec<Pair> {
+        pairs
+            .into_iter()
+            .filter(|(key, _)| !key_types.contains(&key[0]))
+            .collect()
+    };
+
+    let mut global = vec![pair(PSBT_GLOBAL_UNSIGNED_TX, serialize(&tx))];
+    global.extend(retain(
+        raw.global,
+        &[
+            PSBT_GLOBAL_TX_VERSION,
+            PSBT_GLOBAL_FALLBACK_LOCKTIME,
+            PSBT_GLOBAL_INPUT_COUNT,
+            PSBT_GLOBAL_OUTPUT_COUNT,
+            PSBT_GLOBAL_TX_MODIFIABLE,
+            PSBT_GLOBAL_VERSION,
+        ],
+    ));
+
+    let v0 = RawPsbt {
+        global,
+        inputs: raw
+            .inputs
+            .into_iter()
+            .map(|pairs| {
+                retain(
+                    pairs,
+                    &[
+                        PSBT_IN_PREVIOUS_TXID,
+                        PSBT_IN_OUTPUT_INDEX,
+                        PSBT_IN_SEQUENCE,
+                        PSBT_IN_REQUIRED_TIME_LOCKTIME,
+                        PSBT_IN_REQUIRED_HEIGHT_LOCKTIME,
+                    ],
+                )
+            })
+            .collect(),
+        outputs: raw
+            .outputs
+            .into_iter()
+            .map(|pairs| retain(pairs, &[PSBT_OUT_AMOUNT, PSBT_OUT_SCRIPT]))
+            .collect(),
+    };
+
+    PartiallySignedTransaction::deserialize(&v0.encode())
+        .map_err(|err| invalid_psbt(format!("invalid PSBT fields: {err}")))
+}
+
+impl RawPsbt {
+    // Decodes the maps of the PSBT, where `counts` returns the number of
+    // inputs and outputs based on the global map.
+    fn decode(
+        mut bytes: &[u8],
+        counts: impl FnOnce(&[Pair]) -> Result<(usize, usize)>,
+    ) -> Result<RawPsbt> {
+        if !bytes.starts_with(PSBT_MAGIC) {
+            return Err(invalid_psbt("missing PSBT magic bytes"));
+        }
+        bytes = &bytes[PSBT_MAGIC.len()..];
+
+        let global = read_map(&mut bytes)?;
+        let (input_count, output_count) = counts(&global)?;
+
+        let inputs = (0..input_count)
+            .map(|_| read_map(&mut bytes))
+            .collect::<Result<Vec<_>>>()?;
+        let outputs = (0..output_count)
+            .map(|_| read_map(&mut bytes))
+            .collect::<Result<Vec<_>>>()?;
+
+        if !bytes.is_empty() {
+            return Err(invalid_psbt("trailing bytes after the output maps"));
+        }
+
+        Ok(RawPsbt {
+            global,
+            inputs,
+            outputs,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = PSBT_MAGIC.to_vec();
+
+        for map in std::iter::once(&self.global)
+            .chain(&self.inputs)
+            .chain(&self.outputs)
+        {
+            for (key, value) in map {
+                bytes.extend(serialize(&VarInt(key.len() as u64)));
+                bytes.extend(key);
+                bytes.extend(serialize(&VarInt(value.len() as u64)));
+                bytes.extend(value);
+            }
+            // The separator of the map.
+            bytes.push(0x00);
+        }
+
+        bytes
+    }
+}
+
+// Derives the lock time as specified by BIP-370: the maximum of the required
+// height (or, if not all inputs support heights, time) lock times, or the
+// fallback lock time if no input requires one.
+fn lock_time(raw: &RawPsbt) -> Result<LockTime> {
+    let required = raw
+        .inputs
+        .iter()
+        .map(|pairs| {
+            Ok((
+                find_u32(pairs, PSBT_IN_REQUIRED_HEIGHT_LOCKTIME)?,
+                find_u32(pairs, PSBT_IN_REQUIRED_TIME_LOCKTIME)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|(height, time)| height.is_some() || time.is_some())
+        .collect::<Vec<_>>();
+
+    let lock_time = if required.is_empty() {
+        find_u32(&raw.global, PSBT_GLOBAL_FALLBACK_LOCKTIME)?.unwrap_or(0)
+    } else if let Some(heights) = required
+        .iter()
+        .map(|(height, _)| *height)
+        .collect::<Option<Vec<_>>>()
+    {
+        heights.into_iter().max().unwrap_or_default()
+    } else if let Some(times) = required
+        .iter()
+        .map(|(_, time)| *time)
+        .collect::<Option<Vec<_>>>()
+    {
+        times.into_iter().max().unwrap_or_default()
+    } else {
+        return Err(invalid_psbt(
+            "the inputs require both height and time based lock times",
+        ));
+    };
+
+    Ok(LockTime::from_consensus(lock_time))
+}
+
+fn read_map(bytes: &mut &[u8]) -> Result<Vec<Pair>> {
+    let mut pairs: Vec<Pair> = vec![];
+
+    loop {
+        let key = read_bytes(bytes)?;
+        // An empty key marks the end of the map.
+        if key.is_empty() {
+            return Ok(pairs);
+        }
+        let value = read_bytes(bytes)?;
+
+        if pairs.iter().any(|(existing, _)| *existing == key) {
+            return Err(invalid_psbt("duplicate key"));
+        }
+        pairs.push((key, value));
+    }
+}
+
+// Reads bytes prefixed by their compact size length.
+fn read_bytes(bytes: &mut &[u8]) -> Result<Vec<u8>> {
+    let len = VarInt::consensus_decode(bytes)
+        .map_err(|_| invalid_psbt("unexpected end of data"))?
+        .0;
+
+    if len > bytes.len() as u64 {
+        return Err(invalid_psbt("unexpected end of data"));
+    }
+
+    let (data, rest) = bytes.split_at(len as usize);
+    *bytes = rest;
+
+    Ok(data.to_vec())
+}
+
+// Returns the value of the key without key data.
+fn find(pairs: &[Pair], key_type: u8) -> Option<&[u8]> {
+    pairs
+        .iter()
+        .find(|(key, _)| key.as_slice() == [key_type])
+        .map(|(_, value)| value.as_slice())
+}
+
+fn find_u32(pairs: &[Pair], key_type: u8) -> Result<Option<u32>> {
+    find(pairs, key_type)
+        .map(|value| {
+            <[u8; 4]>::try_from(value)
+                .map(u32::from_le_bytes)
+                .map_err(|_| invalid_psbt(format!("invalid value of key type {key_type:#04x}")))
+        })
+        .transpose()
+}
+
+fn pair(key_type: u8, value: Vec<u8>) -> Pair {
+    (vec![key_type], value)
+}
+
+fn invalid_psbt(context: impl Into<String>) -> Error {
+    Error::from(Proto::Error::Error_invalid_psbt).with_context(context)
+}
@@ -0,0 +1,222 @@
+use crate::modules::varint::decode_leb128;
+use crate::{Error, Result};
+use bitcoin::opcodes::all::{OP_PUSHNUM_13, OP_RETURN};
+use bitcoin::script::Instruction;
+use bitcoin::Script;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use tw_proto::BitcoinV2::Proto;
+
+// Tags of the runestone fields.
+const TAG_BODY: u128 = 0;
+const TAG_FLAGS: u128 = 2;
+const TAG_RUNE: u128 = 4;
+const TAG_PREMINE: u128 = 6;
+const TAG_CAP: u128 = 8;
+const TAG_AMOUNT: u128 = 10;
+const TAG_HEIGHT_START: u128 = 12;
+const TAG_HEIGHT_END: u128 = 14;
+const TAG_OFFSET_START: u128 = 16;
+const TAG_OFFSET_END: u128 = 18;
+const TAG_MINT: u128 = 20;
+const TAG_POINTER: u128 = 22;
+const TAG_CENOTAPH: u128 = 126;
+
+// Known flags (bit positions) of the `Flags` field.
+const FLAG_ETCHING: u32 = 0;
+const FLAG_TERMS: u32 = 1;
+const FLAG_TURBO: u32 = 2;
+
+/// The reason why a runestone is malformed, i.e. a cenotaph. All runes
+/// inputs of a transaction with a cenotaph get burned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CenotaphWarning {
+    /// A non-push opcode follows the runestone marker.
+    Opcode,
+    /// The payload contains a truncated or overflowing LEB128 integer.
+    Varint,
+    /// A field tag without a value.
+    TruncatedField,
+    /// An even (mandatory) tag that is not recognized.
+    UnrecognizedEvenTag,
+    /// A flag that is not recognized.
+    UnrecognizedFlag,
+    /// The number of integers of the edicts is not a multiple of four.
+    TrailingIntegers,
+    /// An edict with an invalid rune ID.
+    EdictRuneId,
+    /// An edict that refers to a non-existing output.
+    EdictOutput,
+}
+
+impl Display for CenotaphWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "runestone is a cenotaph: {:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuneId {
+    pub block: u64,
+    pub tx: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edict {
+    pub id: RuneId,
+    pub amount: u128,
+    pub output: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Runestone {
+    /// The values of each field, by tag (excluding the body).
+    pub fields: BTreeMap<u128, Vec<u128>>,
+    pub edicts: Vec<Edict>,
+}
+
+/// Returns whether the given script is a runestone output, i.e. starts with
+/// `OP_RETURN OP_13`.
+pub fn is_runestone(script: &Script) -> bool {
+    let bytes = script.as_bytes();
+    bytes.len() >= 2 && bytes[0] == OP_RETURN.to_u8() && bytes[1] == OP_PUSHNUM_13.to_u8()
+}
+
+/// Decodes the given runestone output of a transaction with `output_count`
+/// outputs, returning the reason if the runestone is a cenotaph.
+pub fn decode_runestone(
+    script: &Script,
+    output_count: usize,
+) -> std::result::Result<Runestone, CenotaphWarning> {
+    // Concatenate the data pushes after the `OP_RETURN OP_13` marker.
+    let mut payload = vec![];
+    for instruction in Script::from_bytes(&script.as_bytes()[2..]).instructions() {
+        match instruction {
+            Ok(Instruction::PushBytes(push)) => payload.extend_from_slice(push.as_bytes()),
+            Ok(Instruction::Op(_)) | Err(_) => return Err(CenotaphWarning::Opcode),
+        }
+    }
+
+    let integers = decode_integers(&payload)?;
+
+    let mut runestone = Runestone::default();
+    let mut rest = integers.as_slice();
+
+    // Parse the fields (tag/value pairs) up to the body.
+    while let Some((&tag, tail)) = rest.split_first() {
+        if tag == TAG_BODY {
+            rest = tail;
+            break;
+        }
+
+        let Some((&value, tail)) = tail.split_first() else {
+            return Err(CenotaphWarning::TruncatedField);
+        };
+
+        runestone.fields.entry(tag).or_default().push(value);
+        rest = tail;
+    }
+
+    check_fields(&runestone.fields)?;
+
+    // The body consists of edicts, with the rune IDs being delta-encoded.
+    if rest.len() % 4 != 0 {
+        return Err(CenotaphWarning::TrailingIntegers);
+    }
+
+    let mut id = RuneId { block: 0, tx: 0 };
+    for edict in rest.chunks(4) {
+        let (block, tx) = (edict[0], edict[1]);
+
+        id = if block == 0 {
+            RuneId {
+                block: id.block,
+                tx: u32::try_from(tx)
+                    .ok()
+                    .and_then(|tx| id.tx.checked_add(tx))
+                    .ok_or(CenotaphWarning::EdictRuneId)?,
+            }
+        } else {
+            RuneId {
+                block: u64::try_from(block)
+                    .ok()
+                    .and_then(|block| id.block.checked_add(block))
+                    .ok_or(CenotaphWarning::EdictRuneId)?,
+                tx: u32::try_from(tx).map_err(|_| CenotaphWarning::EdictRuneId)?,
+            }
+        };
+
+        // Only the genesis rune may have a zero block height.
+        if id.block == 0 && id.tx > 0 {
+            return Err(CenotaphWarning::EdictRuneId);
+        }
+
+        // An output index equal to the output count splits the amount
+        // across all outputs.
+        let output = u32::try_from(edict[3])
+            .ok()
+            .filter(|output| *output as usize <= output_count)
+            .ok_or(CenotaphWarning::EdictOutput)?;
+
+        runestone.edicts.push(Edict {
+            id,
+            amount: edict[2],
+            output,
+        });
+    }
+
+    Ok(runestone)
+}
+
+/// Checks the runestone outputs (if any) of a transaction with the given
+/// output scripts. Only the first runestone output is considered.
+pub fn check_outputs<'a>(
+    scripts: impl IntoIterator<Item = &'a Script>,
+    output_count: usize,
+) -> Result<()> {
+    let Some(script) = scripts.into_iter().find(|script| is_runestone(script)) else {
+        return Ok(());
+    };
+
+    decode_runestone(script, output_count).map_err(|warning| {
+        Error::from(Proto::Error::Error_runestone_cenotaph).with_context(warning.to_string())
+    })?;
+
+    Ok(())
+}
+
+fn check_fields(fields: &BTreeMap<u128, Vec<u128>>) -> std::result::Result<(), CenotaphWarning> {
+    for (tag, values) in fields {
+        match *tag {
+            TAG_FLAGS => {
+                let known = (1 << FLAG_ETCHING) | (1 << FLAG_TERMS) | (1 << FLAG_TURBO);
+                if values.iter().any(|flags| flags & !known != 0) {
+                    return Err(CenotaphWarning::UnrecognizedFlag);
+                }
+            },
+            TAG_RUNE | TAG_PREMINE | TAG_CAP | TAG_AMOUNT | TAG_HEIGHT_START | TAG_HEIGHT_END
+            | TAG_OFFSET_START | TAG_OFFSET_END | TAG_MINT | TAG_POINTER => {},
+            // The cenotaph tag deliberately marks the runestone as a cenotaph.
+            TAG_CENOTAPH => return Err(CenotaphWarning::UnrecognizedEvenTag),
+            // Odd tags are optional and may be ignored.
+            tag if tag % 2 == 0 => return Err(CenotaphWarning::UnrecognizedEvenTag),
+            _ => {},
+        }
+    }
+
+    Ok(())
+}
+
+// Decodes the payload as a sequence of LEB128 encoded integers.
+fn decode_integers(payload: &[u8]) -> std::result::Result<Vec<u128>, CenotaphWarning> {
+    let mut integers = vec![];
+    let mut remaining = payload;
+
+    while !remaining.is_empty() {
+        let (value, len) = decode_leb128(remaining).ok_or(CenotaphWarning::Varint)?;
+        integers.push(value);
+        remaining = &remaining[len..];
+    }
+
+    Ok(integers)
+}
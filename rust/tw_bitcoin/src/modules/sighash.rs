@@ -0,0 +1,218 @@
+use crate::{Error, Result};
+use bitcoin::consensus::{serialize, Encodable};
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::sighash::{TapSighash, TapSighashType};
+use bitcoin::taproot::TapLeafHash;
+use bitcoin::{OutPoint, ScriptBuf, Transaction, TxOut};
+use tw_proto::BitcoinV2::Proto;
+
+/// The individual components of a BIP-341 signature message, for diagnosing
+/// sighash mismatches with external signers. The single SHA256 hashes are
+/// `None` if the sighash type does not commit to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaprootSighashComponents {
+    /// The sighash epoch, always zero.
+    pub epoch: u8,
+    pub hash_type: u8,
+    pub version: i32,
+    pub lock_time: u32,
+    /// `None` for `ANYONECANPAY`.
+    pub sha_prevouts: Option<[u8; 32]>,
+    /// `None` for `ANYONECANPAY`.
+    pub sha_amounts: Option<[u8; 32]>,
+    /// `None` for `ANYONECANPAY`.
+    pub sha_scriptpubkeys: Option<[u8; 32]>,
+    /// `None` for `ANYONECANPAY`.
+    pub sha_sequences: Option<[u8; 32]>,
+    /// `None` for `NONE` and `SINGLE`.
+    pub sha_outputs: Option<[u8; 32]>,
+    /// `2 * ext_flag + annex_present`, where `ext_flag` is set for script-path
+    /// spends.
+    pub spend_type: u8,
+    pub input: TaprootSighashInput,
+    /// The hash of the (length prefixed) annex, if present.
+    pub sha_annex: Option<[u8; 32]>,
+    /// The hash of the output with the same index, for `SINGLE` only.
+    pub sha_single_output: Option<[u8; 32]>,
+    /// The leaf hash of script-path spends, followed by the key version (zero)
+    /// and the code separator position in the signature message.
+    pub tapleaf_hash: Option<TapLeafHash>,
+    /// The position of the last executed `OP_CODESEPARATOR` of script-path
+    /// spends, `None` (`0xFFFFFFFF`) if there is none.
+    pub codesep_pos: Option<u32>,
+}
+
+/// How the signature message identifies the signed input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaprootSighashInput {
+    /// `ANYONECANPAY`: the data of the signed input only.
+    Data {
+        outpoint: OutPoint,
+        amount: u64,
+        script_pubkey: ScriptBuf,
+        sequence: u32,
+    },
+    /// The index of the signed input.
+    Index(u32),
+}
+
+impl TaprootSighashComponents {
+    /// Reassembles the signature message, including the epoch.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut msg = vec![self.epoch, self.hash_type];
+        msg.extend_from_slice(&self.version.to_le_bytes());
+        msg.extend_from_slice(&self.lock_time.to_le_bytes());
+
+        for hash in [
+            self.sha_prevouts,
+            self.sha_amounts,
+            self.sha_scriptpubkeys,
+            self.sha_sequences,
+            self.sha_outputs,
+        ]
+        .iter()
+        .flatten()
+        {
+            msg.extend_from_slice(hash);
+        }
+
+        msg.push(self.spend_type);
+
+        match &self.input {
+            TaprootSighashInput::Data {
+                outpoint,
+                amount,
+                script_pubkey,
+                sequence,
+            } => {
+                msg.extend_from_slice(&serialize(outpoint));
+                msg.extend_from_slice(&amount.to_le_bytes());
+                msg.extend_from_slice(&serialize(script_pubkey));
+                msg.extend_from_slice(&sequence.to_le_bytes());
+            },
+            TaprootSighashInput::Index(index) => msg.extend_from_slice(&index.to_le_bytes()),
+        }
+
+        for hash in [self.sha_annex, self.sha_single_output].iter().flatten() {
+            msg.extend_from_slice(hash);
+        }
+
+        if let Some(leaf_hash) = self.tapleaf_hash {
+            msg.extend_from_slice(leaf_hash.as_byte_array());
+            // key version
+            msg.push(0);
+            let codesep_pos = self.codesep_pos.unwrap_or(u32::MAX);
+            msg.extend_from_slice(&codesep_pos.to_le_bytes());
+        }
+
+        msg
+    }
+
+    /// The sighash, i.e. the `TapSighash` tagged hash of the signature message.
+    pub fn sighash(&self) -> TapSighash {
+        TapSighash::hash(&self.serialize())
+    }
+}
+
+/// Returns the BIP-341 signature message components of the given input. The
+/// `prevouts` must contain the spent outputs of all inputs, in order. Pass the
+/// `leaf_hash` (and the `codesep_pos`, if any) for script-path spends and the
+/// `annex` (starting with `0x50`) if the witness contains one.
+pub fn taproot_components(
+    tx: &Transaction,
+    input_index: usize,
+    prevouts: &[TxOut],
+    sighash_type: TapSighashType,
+    leaf_hash: Option<TapLeafHash>,
+    codesep_pos: Option<u32>,
+    annex: Option<&[u8]>,
+) -> Result<TaprootSighashComponents> {
+    let txin = tx.input.get(input_index).ok_or_else(|| {
+        Error::from(Proto::Error::Error_utxo_sighash_failed)
+            .with_context(format!("input index {input_index} is out of range"))
+    })?;
+
+    if prevouts.len() != tx.input.len() {
+        return Err(Error::from(Proto::Error::Error_missing_taproot_prevout)
+            .with_context("a prevout is required for every input"));
+    }
+
+    let hash_type = sighash_type as u8;
+    let anyone_can_pay = hash_type & 0x80 != 0;
+    let (none, single) = match hash_type & 0x03 {
+        0x02 => (true, false),
+        0x03 => (false, true),
+        _ => (false, false),
+    };
+
+    let (sha_prevouts, sha_amounts, sha_scriptpubkeys, sha_sequences) = if anyone_can_pay {
+        (None, None, None, None)
+    } else {
+        (
+            Some(sha(tx.input.iter().map(|txin| &txin.previous_output))),
+            Some(sha(prevouts.iter().map(|prevout| &prevout.value))),
+            Some(sha(prevouts.iter().map(|prevout| &prevout.script_pubkey))),
+            Some(sha(tx.input.iter().map(|txin| &txin.sequence))),
+        )
+    };
+
+    let sha_outputs = if none || single {
+        None
+    } else {
+        Some(sha(tx.output.iter()))
+    };
+
+    let sha_single_output = if single {
+        let output = tx
+            .output
+            .get(input_index)
+            .ok_or_else(|| Error::from(Proto::Error::Error_utxo_sighash_single_missing_output))?;
+        Some(sha(std::iter::once(output)))
+    } else {
+        None
+    };
+
+    let input = if anyone_can_pay {
+        TaprootSighashInput::Data {
+            outpoint: txin.previous_output,
+            amount: prevouts[input_index].value,
+            script_pubkey: prevouts[input_index].script_pubkey.clone(),
+            sequence: txin.sequence.0,
+        }
+    } else {
+        TaprootSighashInput::Index(input_index as u32)
+    };
+
+    // The annex is hashed with its compact size prefix.
+    let sha_annex = annex.map(|annex| sha(std::iter::once(&annex.to_vec())));
+
+    let spend_type = 2 * u8::from(leaf_hash.is_some()) + u8::from(annex.is_some());
+
+    Ok(TaprootSighashComponents {
+        epoch: 0,
+        hash_type,
+        version: tx.version,
+        lock_time: tx.lock_time.to_consensus_u32(),
+        sha_prevouts,
+        sha_amounts,
+        sha_scriptpubkeys,
+        sha_sequences,
+        sha_outputs,
+        spend_type,
+        input,
+        sha_annex,
+        sha_single_output,
+        tapleaf_hash: leaf_hash,
+        codesep_pos: leaf_hash.and(codesep_pos),
+    })
+}
+
+// The single SHA256 hash of the concatenated serializations.
+fn sha<'a, T: Encodable + 'a>(items: impl Iterator<Item = &'a T>) -> [u8; 32] {
+    let mut engine = sha256::Hash::engine();
+    for item in items {
+        item.consensus_encode(&mut engine)
+            .expect("engines don't error");
+    }
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
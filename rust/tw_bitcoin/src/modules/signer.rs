@@ -1,8 +1,10 @@
+use crate::aliases::*;
+use crate::modules::pay_to_contract;
+use crate::modules::transactions::taproot_keypair;
 use crate::{BitcoinEntry, Error, Result};
-use bitcoin::key::{TapTweak, TweakedKeyPair};
 use bitcoin::sighash::{EcdsaSighashType, TapSighashType};
 use secp256k1::{KeyPair, Message, Secp256k1};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tw_coin_entry::coin_context::CoinContext;
 use tw_coin_entry::coin_entry::{PrivateKeyBytes, SignatureBytes};
 use tw_misc::traits::ToBytesVec;
@@ -22,6 +24,19 @@ impl Signer {
         // not get accidentally forgotten.
         let proto = crate::entry::pre_processor(proto);
 
+        // Generate the sighashes.
+        let pre_signed = BitcoinEntry.preimage_hashes_impl(_coin, proto.clone())?;
+
+        // Check for error.
+        if pre_signed.error != Proto::Error::OK {
+            return Err(Error::from(pre_signed.error));
+        }
+
+        // The sighashes only commit to the selected inputs and (potentially)
+        // to an automatically created change output, so we update the signing
+        // input accordingly before compiling the final transaction.
+        let proto = Self::apply_selection(proto, &pre_signed)?;
+
         // Collect individual private keys per input, if there are any.
         let mut individual_keys = HashMap::new();
         for (index, txin) in proto.inputs.iter().enumerate() {
@@ -30,30 +45,125 @@ impl Signer {
             }
         }
 
-        // Generate the sighashes.
-        let pre_signed = BitcoinEntry.preimage_hashes_impl(_coin, proto.clone())?;
-
-        // Check for error.
-        if pre_signed.error != Proto::Error::OK {
-            return Err(Error::from(pre_signed.error));
+        // Collect the pay-to-contract commitments and the untweaked key-path
+        // inputs, if there are any.
+        let mut contracts = HashMap::new();
+        let mut untweaked = HashSet::new();
+        for (index, txin) in proto.inputs.iter().enumerate() {
+            if let ProtoInputRecipient::builder(builder) = &txin.to_recipient {
+                match &builder.variant {
+                    ProtoInputBuilder::p2tr_pay_to_contract(p2c) => {
+                        contracts.insert(index, (p2c.contract.to_vec(), p2c.tweak_order));
+                    },
+                    ProtoInputBuilder::p2tr_untweaked_key_path(_) => {
+                        untweaked.insert(index);
+                    },
+                    _ => {},
+                }
+            }
         }
 
         // Sign the sighashes.
-        let signatures = crate::modules::signer::Signer::signatures_from_proto(
+        let signatures = crate::modules::signer::Signer::signatures_from_proto_with_tweaks(
             &pre_signed,
             proto.private_key.to_vec(),
             individual_keys,
+            contracts,
+            untweaked,
             proto.dangerous_use_fixed_schnorr_rng,
         )?;
 
         // Construct the final transaction.
-        BitcoinEntry.compile_impl(_coin, proto, signatures, vec![])
+        let mut signed = BitcoinEntry.compile_impl(_coin, proto, signatures, vec![])?;
+        signed.donated_change = pre_signed.donated_change;
+
+        Ok(signed)
+    }
+    /// Updates the signing input with the inputs as selected by the input
+    /// selector (in the selected order) and appends the change output, if one
+    /// was created.
+    pub fn apply_selection<'a>(
+        mut proto: Proto::SigningInput<'a>,
+        pre_signed: &Proto::PreSigningOutput<'_>,
+    ) -> Result<Proto::SigningInput<'a>> {
+        proto.inputs =
+            crate::entry::selected_input_indices(&proto.inputs, &pre_signed.utxo_inputs)?
+                .into_iter()
+                .map(|index| proto.inputs[index].clone())
+                .collect();
+
+        // If a change output was created by the Utxo compiler, we add it to
+        // the outputs with the calculated amount. Note that the change output
+        // is not necessarily the last output if the outputs are sorted, and
+        // the final order is applied when compiling.
+        if !proto.disable_change_output && pre_signed.utxo_outputs.len() == proto.outputs.len() + 1
+        {
+            let total_output: u64 = proto.outputs.iter().map(|output| output.value).sum();
+            let change_amount = pre_signed
+                .utxo_outputs
+                .iter()
+                .map(|output| output.value)
+                .sum::<u64>()
+                - total_output;
+
+            let mut change_output = proto
+                .change_output
+                .take()
+                .ok_or_else(|| Error::from(Proto::Error::Error_invalid_change_output))?;
+            change_output.value = change_amount;
+
+            proto.outputs.push(change_output);
+        }
+
+        // The selection is final at this point.
+        proto.input_selector = UtxoProto::InputSelector::UseAll;
+        proto.disable_change_output = true;
+
+        Ok(proto)
     }
     pub fn signatures_from_proto(
         input: &Proto::PreSigningOutput<'_>,
         private_key: PrivateKeyBytes,
         individual_keys: HashMap<usize, PrivateKeyBytes>,
         dangerous_use_fixed_schnorr_rng: bool,
+    ) -> Result<Vec<SignatureBytes>> {
+        Self::signatures_from_proto_with_contracts(
+            input,
+            private_key,
+            individual_keys,
+            HashMap::new(),
+            dangerous_use_fixed_schnorr_rng,
+        )
+    }
+    /// Like [`Signer::signatures_from_proto`], but tweaks the key of the given
+    /// P2TR key-path inputs with their pay-to-contract commitment (contract
+    /// and tweak order) instead of the plain Taproot tweak.
+    pub fn signatures_from_proto_with_contracts(
+        input: &Proto::PreSigningOutput<'_>,
+        private_key: PrivateKeyBytes,
+        individual_keys: HashMap<usize, PrivateKeyBytes>,
+        contracts: HashMap<usize, (Vec<u8>, Proto::TweakOrder)>,
+        dangerous_use_fixed_schnorr_rng: bool,
+    ) -> Result<Vec<SignatureBytes>> {
+        Self::signatures_from_proto_with_tweaks(
+            input,
+            private_key,
+            individual_keys,
+            contracts,
+            HashSet::new(),
+            dangerous_use_fixed_schnorr_rng,
+        )
+    }
+    /// Like [`Signer::signatures_from_proto_with_contracts`], but additionally
+    /// signs the given P2TR key-path inputs with the untweaked key, as used by
+    /// the `p2tr_untweaked_key_path` builder (e.g. for silent payments).
+    pub fn signatures_from_proto_with_tweaks(
+        input: &Proto::PreSigningOutput<'_>,
+        private_key: PrivateKeyBytes,
+        individual_keys: HashMap<usize, PrivateKeyBytes>,
+        contracts: HashMap<usize, (Vec<u8>, Proto::TweakOrder)>,
+        untweaked: HashSet<usize>,
+        dangerous_use_fixed_schnorr_rng: bool,
     ) -> Result<Vec<SignatureBytes>> {
         let secp = Secp256k1::new();
 
@@ -105,9 +215,17 @@ impl Signer {
 
                     // Any empty leaf hash implies P2TR key-path (balance transfer)
                     if utxo.leaf_hash.is_empty() {
-                        // Tweak keypair for P2TR key-path (ie. zeroed Merkle root).
-                        let tapped: TweakedKeyPair = keypair.tap_tweak(&secp, None);
-                        let tweaked = KeyPair::from(tapped);
+                        // Tweak keypair for P2TR key-path (ie. zeroed Merkle root),
+                        // committing to the contract for pay-to-contract inputs.
+                        // Inputs spending the untweaked key itself (e.g. silent
+                        // payments) are signed with the keypair as is.
+                        let tweaked = if let Some((contract, tweak_order)) = contracts.get(&index) {
+                            pay_to_contract::signing_keypair(&keypair, contract, *tweak_order)?
+                        } else if untweaked.contains(&index) {
+                            keypair
+                        } else {
+                            taproot_keypair(&keypair, None)?
+                        };
 
                         // Construct the Schnorr signature.
                         let schnorr = if dangerous_use_fixed_schnorr_rng {
@@ -0,0 +1,180 @@
+use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_CHECKSIG, OP_CHECKSIGADD, OP_NUMEQUAL};
+use bitcoin::script::{read_scriptint, Instruction, Script};
+use bitcoin::taproot::{ControlBlock, LeafVersion};
+use tw_proto::BitcoinV2::Proto;
+
+/// The signing status of an input of a (partially) signed transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputSigningStatus {
+    /// The number of signatures the input carries.
+    pub signatures: usize,
+    /// The number of signatures the spending condition requires.
+    pub required: usize,
+}
+
+impl InputSigningStatus {
+    pub fn is_signed(&self) -> bool {
+        self.signatures >= self.required
+    }
+}
+
+/// Returns the signing status of each input of the signed transaction.
+///
+/// Multisig inputs (P2SH and P2WSH `OP_CHECKMULTISIG` scripts, Taproot
+/// `OP_CHECKSIGADD` leaves) represent missing signatures by empty items, as
+/// done by partial-signing flows. Taproot script-path inputs with other leaves
+/// require at least one stack item, all other inputs require a non-empty
+/// scriptSig or witness.
+pub fn input_statuses(output: &Proto::SigningOutput<'_>) -> Vec<InputSigningStatus> {
+    output
+        .transaction
+        .as_ref()
+        .map(|tx| tx.inputs.iter().map(input_status).collect())
+        .unwrap_or_default()
+}
+
+/// Returns the indices of the inputs that lack signatures.
+pub fn unsigned_inputs(output: &Proto::SigningOutput<'_>) -> Vec<usize> {
+    input_statuses(output)
+        .iter()
+        .enumerate()
+        .filter(|(_, status)| !status.is_signed())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Whether the transaction was signed successfully and all of its inputs
+/// carry the required signatures.
+pub fn is_complete(output: &Proto::SigningOutput<'_>) -> bool {
+    output.error == Proto::Error::OK
+        && output.transaction.is_some()
+        && unsigned_inputs(output).is_empty()
+}
+
+fn input_status(input: &Proto::TransactionInput<'_>) -> InputSigningStatus {
+    let witness: Vec<&[u8]> = input
+        .witness_items
+        .iter()
+        .map(|item| item.as_ref())
+        .collect();
+    let script_sig = Script::from_bytes(input.script_sig.as_ref());
+
+    if let Some((last, rest)) = witness.split_last() {
+        // P2WSH: the dummy element, the signatures and the witness script.
+        if let Some(required) = checkmultisig_threshold(Script::from_bytes(last)) {
+            return multisig_status(rest.get(1..).unwrap_or_default(), required);
+        }
+
+        // Taproot script-path: the stack items, the leaf script and the
+        // control block.
+        let is_control_block = ControlBlock::decode(last)
+            .map(|control_block| control_block.leaf_version == LeafVersion::TapScript)
+            .unwrap_or(false);
+
+        if is_control_block {
+            if let Some((leaf_script, stack)) = rest.split_last() {
+                let required = checksigadd_threshold(Script::from_bytes(leaf_script)).unwrap_or(1);
+                return multisig_status(stack, required);
+            }
+        }
+    }
+
+    // P2SH: the dummy element, the signatures and the redeem script.
+    let pushes: Option<Vec<&[u8]>> = script_sig
+        .instructions()
+        .map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes()),
+            _ => None,
+        })
+        .collect();
+
+    if let Some([_dummy, signatures @ .., redeem_script]) = pushes.as_deref() {
+        if let Some(required) = checkmultisig_threshold(Script::from_bytes(redeem_script)) {
+            return multisig_status(signatures, required);
+        }
+    }
+
+    let is_signed = !script_sig.is_empty() || witness.iter().any(|item| !item.is_empty());
+
+    InputSigningStatus {
+        signatures: usize::from(is_signed),
+        required: 1,
+    }
+}
+
+fn multisig_status(signatures: &[&[u8]], required: usize) -> InputSigningStatus {
+    InputSigningStatus {
+        signatures: signatures.iter().filter(|sig| !sig.is_empty()).count(),
+        required,
+    }
+}
+
+// Returns the threshold `k` of a `OP_k <pubkey_1> ... <pubkey_n> OP_n
+// OP_CHECKMULTISIG` script.
+fn checkmultisig_threshold(script: &Script) -> Option<usize> {
+    let instructions: Vec<Instruction> = script.instructions().collect::<Result<_, _>>().ok()?;
+
+    let [threshold, pubkeys @ .., count, Instruction::Op(op)] = instructions.as_slice() else {
+        return None;
+    };
+
+    let threshold = read_number(threshold)?;
+    let all_pushes = pubkeys
+        .iter()
+        .all(|instruction| matches!(instruction, Instruction::PushBytes(_)));
+
+    if *op != OP_CHECKMULTISIG
+        || !all_pushes
+        || read_number(count)? != pubkeys.len()
+        || threshold == 0
+        || threshold > pubkeys.len()
+    {
+        return None;
+    }
+
+    Some(threshold)
+}
+
+// Returns the threshold `k` of a `<pubkey_1> OP_CHECKSIG <pubkey_2>
+// OP_CHECKSIGADD ... <k> OP_NUMEQUAL` leaf.
+fn checksigadd_threshold(script: &Script) -> Option<usize> {
+    let instructions: Vec<Instruction> = script.instructions().collect::<Result<_, _>>().ok()?;
+
+    let [pubkeys @ .., threshold, Instruction::Op(op)] = instructions.as_slice() else {
+        return None;
+    };
+
+    if *op != OP_NUMEQUAL || pubkeys.is_empty() || pubkeys.len() % 2 != 0 {
+        return None;
+    }
+
+    let well_formed = pubkeys.chunks(2).enumerate().all(|(index, pair)| {
+        let expected = if index == 0 {
+            OP_CHECKSIG
+        } else {
+            OP_CHECKSIGADD
+        };
+        matches!(pair, [Instruction::PushBytes(_), Instruction::Op(op)] if *op == expected)
+    });
+
+    let threshold = read_number(threshold)?;
+    if !well_formed || threshold == 0 || threshold > pubkeys.len() / 2 {
+        return None;
+    }
+
+    Some(threshold)
+}
+
+// Reads a small number, pushed either as `OP_1` to `OP_16` or as script
+// integer.
+fn read_number(instruction: &Instruction) -> Option<usize> {
+    let number = match instruction {
+        Instruction::Op(op) => match op.to_u8() {
+            code @ 0x51..=0x60 => i64::from(code - 0x50),
+            _ => return None,
+        },
+        Instruction::PushBytes(bytes) => read_scriptint(bytes.as_bytes()).ok()?,
+    };
+
+    usize::try_from(number).ok()
+}
@@ -0,0 +1,170 @@
+use crate::{Error, Result};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::OutPoint;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey};
+use std::collections::HashMap;
+use tw_proto::BitcoinV2::Proto;
+
+/// The recipient of a silent payment (BIP-352), as encoded in its `sp1...`
+/// address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SilentPaymentRecipient {
+    pub scan_pubkey: PublicKey,
+    pub spend_pubkey: PublicKey,
+}
+
+/// The private key of an input that is eligible for silent payments.
+#[derive(Debug, Clone, Copy)]
+pub struct SilentPaymentInput {
+    pub private_key: SecretKey,
+    /// Whether the input is spent via Taproot key-path, in which case the
+    /// x-only (even) public key is used.
+    pub is_taproot: bool,
+}
+
+/// Returns the Taproot output keys paying to the given silent payment
+/// recipients, in the same order. All outputs are derived from the sum of
+/// the input private keys `a`, the input hash (which commits to the smallest
+/// of the given outpoints) and a per-output tweak: the outputs to the same
+/// scan key are numbered with `k = 0, 1, ...`, so that paying the same
+/// recipient multiple times still results in distinct outputs.
+///
+/// The output keys must be used as is, i.e. as the witness program of the
+/// P2TR output, without applying the Taproot tweak.
+pub fn derive_outputs(
+    outpoints: &[OutPoint],
+    inputs: &[SilentPaymentInput],
+    recipients: &[SilentPaymentRecipient],
+) -> Result<Vec<XOnlyPublicKey>> {
+    let secp = Secp256k1::new();
+
+    let input_key = input_private_key_sum(inputs)?;
+    let input_hash = input_hash(outpoints, &input_key.public_key(&secp))?;
+    let ecdh_key = input_key
+        .mul_tweak(&input_hash)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+
+    let mut counters: HashMap<PublicKey, u32> = HashMap::new();
+    let mut outputs = Vec::with_capacity(recipients.len());
+
+    for recipient in recipients {
+        let k = counters.entry(recipient.scan_pubkey).or_insert(0);
+
+        let shared_secret = recipient
+            .scan_pubkey
+            .mul_tweak(&secp, &Scalar::from(ecdh_key))
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+
+        let tweak = output_tweak(&shared_secret, *k)?;
+        let output = recipient
+            .spend_pubkey
+            .add_exp_tweak(&secp, &tweak)
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+
+        outputs.push(output.x_only_public_key().0);
+        *k += 1;
+    }
+
+    Ok(outputs)
+}
+
+/// Returns the sum `a` of the private keys of the given inputs. The private
+/// keys of Taproot inputs are negated if required, so that they match the
+/// even x-only public key.
+pub fn input_private_key_sum(inputs: &[SilentPaymentInput]) -> Result<SecretKey> {
+    let secp = Secp256k1::new();
+
+    let mut keys = inputs.iter().map(|input| {
+        let (_, parity) = input.private_key.public_key(&secp).x_only_public_key();
+
+        if input.is_taproot && parity == secp256k1::Parity::Odd {
+            input.private_key.negate()
+        } else {
+            input.private_key
+        }
+    });
+
+    let first = keys.next().ok_or_else(|| {
+        Error::from(Proto::Error::Error_invalid_silent_payment)
+            .with_context("no eligible inputs provided")
+    })?;
+
+    keys.try_fold(first, |sum, key| {
+        sum.add_tweak(&Scalar::from(key)).map_err(|_| {
+            Error::from(Proto::Error::Error_invalid_silent_payment)
+                .with_context("the input private keys sum up to zero")
+        })
+    })
+}
+
+/// Returns the input hash `hash_BIP0352/Inputs(outpoint_L || A)`, where
+/// `outpoint_L` is the (lexicographically) smallest serialized outpoint and
+/// `A` the public key of the input private key sum.
+pub fn input_hash(outpoints: &[OutPoint], input_pubkey: &PublicKey) -> Result<Scalar> {
+    let smallest = outpoints
+        .iter()
+        .map(bitcoin::consensus::serialize)
+        .min()
+        .ok_or_else(|| {
+            Error::from(Proto::Error::Error_invalid_silent_payment)
+                .with_context("no outpoints provided")
+        })?;
+
+    let hash = tagged_hash("BIP0352/Inputs", &[&smallest, &input_pubkey.serialize()]);
+    Scalar::from_be_bytes(hash).map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))
+}
+
+/// Returns the tweak `t_k` of the k-th output received by the given scan
+/// key, as computed by the recipient from the public key `A` of the input
+/// key sum and the input hash of the sending transaction.
+pub fn receiver_tweak(
+    scan_key: &SecretKey,
+    input_pubkey: &PublicKey,
+    input_hash: &Scalar,
+    k: u32,
+) -> Result<Scalar> {
+    let secp = Secp256k1::new();
+
+    let ecdh_key = scan_key
+        .mul_tweak(input_hash)
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+    let shared_secret = input_pubkey
+        .mul_tweak(&secp, &Scalar::from(ecdh_key))
+        .map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))?;
+
+    output_tweak(&shared_secret, k)
+}
+
+/// Returns the private key `b_spend + t_k` that spends a received silent
+/// payment output. The output is spent via the `p2tr_untweaked_key_path`
+/// input builder, since its output key is not tweaked (BIP-341).
+pub fn spending_private_key(spend_key: &SecretKey, tweak: &Scalar) -> Result<SecretKey> {
+    spend_key.add_tweak(tweak).map_err(|_| {
+        Error::from(Proto::Error::Error_invalid_silent_payment)
+            .with_context("the derived spending private key is invalid")
+    })
+}
+
+// Returns the tweak `t_k = hash_BIP0352/SharedSecret(ser_P(ecdh) || ser_32(k))`
+// of the k-th output to a scan key.
+fn output_tweak(shared_secret: &PublicKey, k: u32) -> Result<Scalar> {
+    let hash = tagged_hash(
+        "BIP0352/SharedSecret",
+        &[&shared_secret.serialize(), &k.to_be_bytes()],
+    );
+    Scalar::from_be_bytes(hash).map_err(|_| Error::from(Proto::Error::Error_invalid_silent_payment))
+}
+
+// The BIP-340 tagged hash `SHA256(SHA256(tag) || SHA256(tag) || msg)`.
+fn tagged_hash(tag: &str, msg: &[&[u8]]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(tag.as_bytes());
+
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    for part in msg {
+        engine.input(part);
+    }
+
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
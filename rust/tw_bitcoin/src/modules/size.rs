@@ -0,0 +1,66 @@
+use bitcoin::Transaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxSize {
+    /// The size (in bytes) of the fully serialized transaction, including the
+    /// Segwit marker/flag and the witnesses.
+    pub full: u64,
+    /// The size (in bytes) of the transaction serialized without any witness
+    /// data, as seen by pre-Segwit nodes.
+    pub stripped: u64,
+}
+
+impl TxSize {
+    /// The size (in bytes) of the witness data, which benefits from the
+    /// witness discount.
+    pub fn witness(&self) -> u64 {
+        self.full - self.stripped
+    }
+}
+
+/// Returns both the full and the stripped size of the given transaction.
+pub fn tx_size(tx: &Transaction) -> TxSize {
+    TxSize {
+        full: tx.size() as u64,
+        stripped: tx.strippedsize() as u64,
+    }
+}
+
+/// Returns the size (in bytes) of the witness data of the given transaction,
+/// i.e. the full size minus the stripped size. Zero for legacy transactions.
+pub fn witness_size(tx: &Transaction) -> u64 {
+    tx_size(tx).witness()
+}
+
+/// The weight of a transaction, split into the non-witness (base) bytes and
+/// the witness bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeightBreakdown {
+    /// The size (in bytes) of the non-witness data, counted at four weight
+    /// units per byte.
+    pub base_bytes: u64,
+    /// The size (in bytes) of the witness data, including the Segwit
+    /// marker/flag, counted at one weight unit per byte.
+    pub witness_bytes: u64,
+    /// The weight (in weight units) of the transaction.
+    pub weight: u64,
+    /// The virtual size (in vbytes) of the transaction, i.e. the weight divided
+    /// by four, rounded up.
+    pub vsize: u64,
+}
+
+/// Returns the weight breakdown of the given transaction, showing how much the
+/// witness data contributes to the virtual size.
+pub fn weight_breakdown(tx: &Transaction) -> WeightBreakdown {
+    let size = tx_size(tx);
+    let base_bytes = size.stripped;
+    let witness_bytes = size.witness();
+    let weight = base_bytes * 4 + witness_bytes;
+
+    WeightBreakdown {
+        base_bytes,
+        witness_bytes,
+        weight,
+        vsize: (weight + 3) / 4,
+    }
+}
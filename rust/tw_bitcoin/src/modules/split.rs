@@ -0,0 +1,68 @@
+use crate::aliases::*;
+use crate::modules::consolidate::{INPUT_BASE_WEIGHT, TX_OVERHEAD_WEIGHT};
+use crate::modules::standardness::{dust_threshold, DEFAULT_DUST_RELAY_FEERATE};
+use crate::modules::transactions::InputBuilder;
+use crate::{Error, Result};
+use bitcoin::Script;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// Creates the signing input that splits the given UTXO into `num_outputs`
+/// outputs paying to `destination_script`, e.g. for fanning out a large UTXO
+/// ahead of payment batching. The value of the UTXO minus the fee (at
+/// `fee_rate`, in sat/vbyte) is divided equally, while the remainder of the
+/// division is distributed across the first outputs, one satoshi each. The
+/// private key must be set by the caller.
+pub fn build<'a>(
+    utxo: Proto::Input<'a>,
+    num_outputs: u32,
+    destination_script: &Script,
+    fee_rate: u64,
+) -> Result<Proto::SigningInput<'a>> {
+    if num_outputs == 0 {
+        return Err(Error::from(Proto::Error::Error_missing_recipient));
+    }
+
+    let input_weight = INPUT_BASE_WEIGHT + InputBuilder::utxo_from_proto(&utxo)?.weight_estimate;
+    // Value, length and scriptPubkey of each output.
+    let output_weight = 4 * (8 + 1 + destination_script.len() as u64);
+
+    let weight = TX_OVERHEAD_WEIGHT + input_weight + num_outputs as u64 * output_weight;
+    let fee = (weight + 3) / 4 * fee_rate;
+
+    let amount = utxo
+        .value
+        .checked_sub(fee)
+        .ok_or_else(|| Error::from(Proto::Error::Error_utxo_insufficient_inputs))?;
+
+    let per_output = amount / num_outputs as u64;
+    let remainder = amount % num_outputs as u64;
+
+    let dust = dust_threshold(destination_script, DEFAULT_DUST_RELAY_FEERATE);
+    if per_output < dust {
+        return Err(
+            Error::from(Proto::Error::Error_dust_output).with_context(format!(
+                "{per_output} sats per output is below the dust threshold of {dust} sats"
+            )),
+        );
+    }
+
+    let outputs = (0..num_outputs as u64)
+        .map(|index| Proto::Output {
+            value: per_output + u64::from(index < remainder),
+            to_recipient: ProtoOutputRecipient::custom_script_pubkey(
+                destination_script.to_bytes().into(),
+            ),
+            ..Default::default()
+        })
+        .collect();
+
+    Ok(Proto::SigningInput {
+        inputs: vec![utxo],
+        outputs,
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: fee_rate,
+        disable_change_output: true,
+        ..Default::default()
+    })
+}
@@ -0,0 +1,295 @@
+use crate::{Error, Result};
+use bitcoin::opcodes::all::{
+    OP_CHECKMULTISIG, OP_CHECKMULTISIGVERIFY, OP_CHECKSIG, OP_CHECKSIGVERIFY, OP_PUSHNUM_16,
+};
+use bitcoin::script::Instruction;
+use bitcoin::{Script, Transaction, TxOut, VarInt};
+use tw_proto::BitcoinV2::Proto;
+
+/// The maximum size of an OP_RETURN output script that gets relayed by
+/// default (Bitcoin Core `-datacarriersize`).
+pub const MAX_OP_RETURN_RELAY: usize = 83;
+
+/// The maximum payload size of an OP_RETURN output that gets relayed by
+/// default, i.e. [`MAX_OP_RETURN_RELAY`] without the OP_RETURN and push
+/// opcodes.
+pub const DEFAULT_DATA_CARRIER_SIZE: usize = 80;
+
+/// The version of TRUC ("Topologically Restricted Until Confirmation")
+/// transactions (BIP-431).
+pub const TRUC_VERSION: i32 = 3;
+
+/// The maximum virtual size of a TRUC transaction.
+pub const TRUC_MAX_VSIZE: u64 = 10_000;
+
+/// The maximum virtual size of a TRUC transaction that spends an unconfirmed
+/// TRUC transaction (its only allowed child).
+pub const TRUC_CHILD_MAX_VSIZE: u64 = 1_000;
+
+/// The default dust relay fee rate (in sat/kvB) of Bitcoin Core
+/// (`-dustrelayfee`).
+pub const DEFAULT_DUST_RELAY_FEERATE: u64 = 3_000;
+
+/// The maximum sigop cost of a transaction that gets relayed by default
+/// (Bitcoin Core `MAX_STANDARD_TX_SIGOPS_COST`).
+pub const MAX_STANDARD_TX_SIGOPS_COST: u64 = 16_000;
+
+/// The sigop cost of a `OP_CHECKMULTISIG` whose number of public keys is not
+/// known, i.e. the maximum number of public keys.
+const MAX_PUBKEYS_PER_MULTISIG: u64 = 20;
+
+/// The factor by which legacy (non-witness) sigops are scaled, analogous to
+/// the weight of non-witness data.
+const WITNESS_SCALE_FACTOR: u64 = 4;
+
+/// Checks the outputs of the given transaction against the standardness
+/// (relay) rules.
+pub fn check_transaction(tx: &Transaction) -> Result<()> {
+    for output in &tx.output {
+        check_output_script(&output.script_pubkey)?;
+    }
+
+    Ok(())
+}
+
+/// Checks the static TRUC (BIP-431) rules of the given transaction, i.e. the
+/// rules that do not depend on the mempool. Since a TRUC transaction may only
+/// have a single unconfirmed child, it must not carry more than one anchor
+/// output for fee-bumping via CPFP.
+pub fn check_truc(tx: &Transaction) -> Result<()> {
+    if tx.version != TRUC_VERSION {
+        return Err(
+            Error::from(Proto::Error::Error_non_standard_truc).with_context(format!(
+                "expected version {TRUC_VERSION}, got {}",
+                tx.version
+            )),
+        );
+    }
+
+    if tx.vsize() as u64 > TRUC_MAX_VSIZE {
+        return Err(Error::from(Proto::Error::Error_non_standard_truc)
+            .with_context(format!("vsize exceeds {TRUC_MAX_VSIZE} vbytes")));
+    }
+
+    let anchors = tx
+        .output
+        .iter()
+        .filter(|output| crate::modules::anchor::is_anchor(&output.script_pubkey))
+        .count();
+
+    if anchors > 1 {
+        return Err(Error::from(Proto::Error::Error_non_standard_truc)
+            .with_context("at most one anchor output is allowed"));
+    }
+
+    check_transaction(tx)
+}
+
+/// Checks the static TRUC rules of a transaction that spends an unconfirmed
+/// TRUC transaction (such as the CPFP child spending the anchor output).
+pub fn check_truc_child(tx: &Transaction) -> Result<()> {
+    check_truc(tx)?;
+
+    if tx.vsize() as u64 > TRUC_CHILD_MAX_VSIZE {
+        return Err(Error::from(Proto::Error::Error_non_standard_truc)
+            .with_context(format!("child vsize exceeds {TRUC_CHILD_MAX_VSIZE} vbytes")));
+    }
+
+    Ok(())
+}
+
+/// Returns the sigop cost of the given transaction (BIP-141), matching
+/// `GetTransactionSigOpCost` of Bitcoin Core. The `prevouts` must contain the
+/// spent outputs of all inputs, in order.
+///
+/// Legacy sigops of the scriptSigs and scriptPubkeys count four times, with
+/// each `OP_CHECKMULTISIG` counting as 20 since the number of public keys is
+/// not evaluated. P2SH redeem scripts (times four) and P2WSH witness scripts
+/// are counted accurately, using the actual number of public keys.
+pub fn count_sigops(tx: &Transaction, prevouts: &[TxOut]) -> Result<u64> {
+    if prevouts.len() != tx.input.len() {
+        return Err(Error::from(Proto::Error::Error_malformed_transaction)
+            .with_context("a prevout is required for every input"));
+    }
+
+    let legacy: u64 = tx
+        .input
+        .iter()
+        .map(|txin| script_sigops(&txin.script_sig, false))
+        .chain(
+            tx.output
+                .iter()
+                .map(|output| script_sigops(&output.script_pubkey, false)),
+        )
+        .sum();
+
+    let mut cost = legacy * WITNESS_SCALE_FACTOR;
+
+    for (txin, prevout) in tx.input.iter().zip(prevouts) {
+        let mut witness_program = prevout.script_pubkey.as_script();
+
+        if prevout.script_pubkey.is_p2sh() {
+            // The redeem script is the last push of the scriptSig.
+            let redeem_script = match txin.script_sig.instructions().last() {
+                Some(Ok(Instruction::PushBytes(bytes))) => Script::from_bytes(bytes.as_bytes()),
+                _ => continue,
+            };
+
+            cost += script_sigops(redeem_script, true) * WITNESS_SCALE_FACTOR;
+            witness_program = redeem_script;
+        }
+
+        if witness_program.is_v0_p2wpkh() {
+            cost += 1;
+        } else if witness_program.is_v0_p2wsh() {
+            if let Some(witness_script) = txin.witness.last() {
+                cost += script_sigops(Script::from_bytes(witness_script), true);
+            }
+        }
+    }
+
+    Ok(cost)
+}
+
+/// Checks that the sigop cost of the given transaction does not exceed the
+/// standardness limit. See [`count_sigops`].
+pub fn check_sigops(tx: &Transaction, prevouts: &[TxOut]) -> Result<()> {
+    let cost = count_sigops(tx, prevouts)?;
+
+    if cost > MAX_STANDARD_TX_SIGOPS_COST {
+        return Err(
+            Error::from(Proto::Error::Error_non_standard_sigops).with_context(format!(
+                "sigop cost {cost} exceeds {MAX_STANDARD_TX_SIGOPS_COST}"
+            )),
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the dust threshold of an output with the given scriptPubkey at the
+/// given dust relay fee rate (in sat/kvB), matching `GetDustThreshold` of
+/// Bitcoin Core: the fee of the output plus the input spending it, assumed to
+/// be 148 bytes or 67 vbytes for witness programs. For example, P2PKH outputs
+/// are dust below 546 sats and P2WPKH outputs below 294 sats at the default
+/// rate. OP_RETURN outputs are unspendable and never dust.
+pub fn dust_threshold(script_pubkey: &Script, dust_relay_feerate: u64) -> u64 {
+    if script_pubkey.is_op_return() {
+        return 0;
+    }
+
+    let len = script_pubkey.len() as u64;
+    // Value, length and scriptPubkey.
+    let output_size = 8 + VarInt(len).len() as u64 + len;
+
+    // Outpoint, scriptSig length, sequence and the signature data (scriptSig
+    // or witness, with the witness discount applied).
+    let input_size = if script_pubkey.is_witness_program() {
+        32 + 4 + 1 + 107 / 4 + 4
+    } else {
+        32 + 4 + 1 + 107 + 4
+    };
+
+    (output_size + input_size) * dust_relay_feerate / 1_000
+}
+
+/// Checks the given output script against the standardness (relay) rules.
+pub fn check_output_script(script: &Script) -> Result<()> {
+    check_output_script_with_data_carrier_size(script, DEFAULT_DATA_CARRIER_SIZE)
+}
+
+/// Checks the given output script against the standardness (relay) rules,
+/// allowing OP_RETURN outputs with a payload of up to `data_carrier_size`
+/// bytes. Since the size limit is policy rather than consensus, nodes with a
+/// relaxed policy relay larger payloads. Zero rejects all OP_RETURN outputs,
+/// as `-datacarrier=0` of Bitcoin Core.
+pub fn check_output_script_with_data_carrier_size(
+    script: &Script,
+    data_carrier_size: usize,
+) -> Result<()> {
+    if script.is_op_return() {
+        check_op_return(script, data_carrier_size)?;
+    }
+
+    Ok(())
+}
+
+// OP_RETURN outputs must not exceed the size of the OP_RETURN and a single push
+// of `data_carrier_size` bytes, and may only contain push opcodes after the
+// OP_RETURN (with `OP_0` to `OP_16` being considered push opcodes, matching
+// `IsPushOnly` of Bitcoin Core).
+fn check_op_return(script: &Script, data_carrier_size: usize) -> Result<()> {
+    if data_carrier_size == 0 {
+        return Err(Error::from(Proto::Error::Error_non_standard_op_return)
+            .with_context("OP_RETURN outputs are disabled"));
+    }
+
+    let push_opcode_size = match data_carrier_size {
+        0..=0x4b => 1,
+        0x4c..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5,
+    };
+    let max_size = 1 + push_opcode_size + data_carrier_size;
+
+    if script.len() > max_size {
+        return Err(
+            Error::from(Proto::Error::Error_non_standard_op_return).with_context(format!(
+                "OP_RETURN script of {} bytes exceeds the limit of {max_size} bytes",
+                script.len()
+            )),
+        );
+    }
+
+    // Skip the OP_RETURN itself.
+    let push_only = Script::from_bytes(&script.as_bytes()[1..])
+        .instructions()
+        .all(|instruction| match instruction {
+            Ok(Instruction::PushBytes(_)) => true,
+            Ok(Instruction::Op(op)) => op.to_u8() <= OP_PUSHNUM_16.to_u8(),
+            // Malformed push.
+            Err(_) => false,
+        });
+
+    if !push_only {
+        return Err(Error::from(Proto::Error::Error_non_standard_op_return));
+    }
+
+    Ok(())
+}
+
+// Counts the sigops of the script, matching `GetSigOpCount` of Bitcoin Core.
+// If `accurate`, a `OP_CHECKMULTISIG` preceded by `OP_1` to `OP_16` counts as
+// that number of sigops. Counting stops at the first malformed push.
+fn script_sigops(script: &Script, accurate: bool) -> u64 {
+    let mut count = 0;
+    // The number pushed by the previous `OP_1` to `OP_16`, if any.
+    let mut last_pushnum = None;
+
+    for instruction in script.instructions() {
+        let Ok(instruction) = instruction else {
+            break;
+        };
+
+        let Instruction::Op(op) = instruction else {
+            last_pushnum = None;
+            continue;
+        };
+
+        if op == OP_CHECKSIG || op == OP_CHECKSIGVERIFY {
+            count += 1;
+        } else if op == OP_CHECKMULTISIG || op == OP_CHECKMULTISIGVERIFY {
+            count += match last_pushnum {
+                Some(pubkeys) if accurate => pubkeys,
+                _ => MAX_PUBKEYS_PER_MULTISIG,
+            };
+        }
+
+        last_pushnum = match op.to_u8() {
+            code @ 0x51..=0x60 => Some(u64::from(code - 0x50)),
+            _ => None,
+        };
+    }
+
+    count
+}
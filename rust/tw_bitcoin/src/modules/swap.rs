@@ -0,0 +1,61 @@
+use crate::aliases::*;
+use crate::modules::anti_fee_sniping::SEQUENCE_NO_RBF;
+use crate::modules::consolidate;
+use bitcoin::absolute::LOCK_TIME_THRESHOLD;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// The HTLC output of an atomic swap, see [`Htlc`](crate::modules::transactions::Htlc).
+#[derive(Debug, Clone)]
+pub struct HtlcUtxo {
+    /// The txid of the HTLC output in REVERSED order.
+    pub txid: Vec<u8>,
+    pub vout: u32,
+    pub value: u64,
+    /// The SHA256 hash of the preimage that the recipient can claim with.
+    pub payment_hash: Vec<u8>,
+    /// The public key of the recipient of the HTLC.
+    pub recipient_pubkey: Vec<u8>,
+}
+
+/// Creates the signing input that refunds the given HTLC output to the
+/// sender via the timeout path, once the CLTV expiry has been reached. The
+/// lock time of the transaction is set to the expiry and the input uses a
+/// non-final sequence, so that `OP_CHECKLOCKTIMEVERIFY` is satisfied. The
+/// destination output receives the HTLC value minus the fee. The private key
+/// of `refund_pubkey` must be set by the caller.
+pub fn refund_tx<'a>(
+    htlc_utxo: HtlcUtxo,
+    refund_pubkey: &[u8],
+    cltv_expiry: u32,
+    destination: Proto::Output<'a>,
+    fee_rate: u64,
+) -> Proto::SigningInput<'a> {
+    let input = Proto::Input {
+        txid: htlc_utxo.txid.into(),
+        vout: htlc_utxo.vout,
+        value: htlc_utxo.value,
+        sequence: SEQUENCE_NO_RBF,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::htlc_refund(Proto::mod_Input::InputHtlcRefund {
+                payment_hash: htlc_utxo.payment_hash.into(),
+                recipient_public_key: htlc_utxo.recipient_pubkey.into(),
+                refund_public_key: refund_pubkey.to_vec().into(),
+                expiry: cltv_expiry,
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let lock_time = if cltv_expiry < LOCK_TIME_THRESHOLD {
+        UtxoProto::mod_LockTime::OneOfvariant::blocks(cltv_expiry)
+    } else {
+        UtxoProto::mod_LockTime::OneOfvariant::seconds(cltv_expiry)
+    };
+
+    Proto::SigningInput {
+        lock_time: Some(UtxoProto::LockTime { variant: lock_time }),
+        ..consolidate::build_signing_input(vec![input], destination, fee_rate)
+    }
+}
@@ -0,0 +1,16 @@
+use crate::modules::consolidate;
+use tw_proto::BitcoinV2::Proto;
+
+/// Creates the signing input that sweeps the spendable balance into the given
+/// destination output, which receives the total amount minus the fee. The
+/// UTXOs at the `protected` indices carry inscriptions or runes and are
+/// excluded, so that they stay with the sender. The private key(s) must be
+/// set by the caller.
+pub fn build_signing_input<'a>(
+    utxos: Vec<Proto::Input<'a>>,
+    protected: &[usize],
+    destination: Proto::Output<'a>,
+    fee_rate: u64,
+) -> Proto::SigningInput<'a> {
+    consolidate::build_signing_input_excluding(utxos, protected, destination, fee_rate)
+}
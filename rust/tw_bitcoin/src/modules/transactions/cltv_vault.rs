@@ -0,0 +1,77 @@
+use crate::{Error, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::script::{Script, ScriptBuf};
+use bitcoin::{PublicKey, Sequence};
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// A simple time-locked vault, spendable by the owner once the expiry (block
+/// height or timestamp) has been reached. The witness script is wrapped in
+/// P2WSH:
+///
+/// `<expiry> OP_CHECKLOCKTIMEVERIFY OP_DROP <pubkey> OP_CHECKSIG`
+pub struct CltvVault {
+    expiry: LockTime,
+    witness_script: ScriptBuf,
+}
+
+impl CltvVault {
+    pub fn new(expiry: u32, owner: PublicKey) -> CltvVault {
+        use bitcoin::opcodes::all::*;
+
+        let expiry = LockTime::from_consensus(expiry);
+
+        let witness_script = ScriptBuf::builder()
+            .push_lock_time(expiry)
+            .push_opcode(OP_CLTV)
+            .push_opcode(OP_DROP)
+            .push_key(&owner)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        CltvVault {
+            expiry,
+            witness_script,
+        }
+    }
+    pub fn witness_script(&self) -> &Script {
+        self.witness_script.as_script()
+    }
+    pub fn expiry(&self) -> LockTime {
+        self.expiry
+    }
+    /// Checks whether a transaction with the given lock time can spend the
+    /// vault with the given input sequence. The lock time must be of the same
+    /// kind (block height or timestamp) as the expiry and at least that value.
+    /// A final sequence (`0xFFFFFFFF`) disables the lock time entirely, so
+    /// `OP_CHECKLOCKTIMEVERIFY` would fail.
+    pub fn check_spend(&self, lock_time: LockTime, sequence: u32) -> Result<()> {
+        if !self.expiry.is_implied_by(lock_time) {
+            return Err(Error::from(
+                Proto::Error::Error_cltv_vault_lock_time_not_reached,
+            ));
+        }
+
+        if Sequence(sequence) == Sequence::MAX {
+            return Err(Error::from(Proto::Error::Error_cltv_vault_final_sequence));
+        }
+
+        Ok(())
+    }
+}
+
+// Convenience function to retrieve the transaction lock time. If none is
+// provided, the lock time is zero (immediately spendable).
+pub(crate) fn lock_time_from_proto(proto: &Option<UtxoProto::LockTime>) -> Result<LockTime> {
+    let lock_time = match proto.as_ref().map(|lock_time| &lock_time.variant) {
+        Some(UtxoProto::mod_LockTime::OneOfvariant::blocks(blocks)) => {
+            LockTime::from_height(*blocks)
+        },
+        Some(UtxoProto::mod_LockTime::OneOfvariant::seconds(seconds)) => {
+            LockTime::from_time(*seconds)
+        },
+        _ => return Ok(LockTime::ZERO),
+    };
+
+    lock_time.map_err(|_| Error::from(Proto::Error::Error_utxo_invalid_lock_time))
+}
@@ -0,0 +1,94 @@
+use super::segwit_public_key;
+use crate::{Error, Result};
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::script::{Script, ScriptBuf};
+use bitcoin::{PublicKey, Sequence};
+use tw_proto::BitcoinV2::Proto;
+
+/// A hash time-locked contract (HTLC) as used by atomic swaps. The recipient
+/// can claim the funds by revealing the preimage of the payment hash,
+/// otherwise the sender is refunded once the expiry (block height or
+/// timestamp) has been reached. The witness script is wrapped in P2WSH:
+///
+/// ```txt
+/// OP_IF
+///     OP_SHA256 <payment_hash> OP_EQUALVERIFY <recipient>
+/// OP_ELSE
+///     <expiry> OP_CHECKLOCKTIMEVERIFY OP_DROP <refund>
+/// OP_ENDIF
+/// OP_CHECKSIG
+/// ```
+pub struct Htlc {
+    expiry: LockTime,
+    witness_script: ScriptBuf,
+}
+
+impl Htlc {
+    pub fn new(
+        payment_hash: sha256::Hash,
+        recipient: PublicKey,
+        refund: PublicKey,
+        expiry: u32,
+    ) -> Htlc {
+        use bitcoin::opcodes::all::*;
+
+        let expiry = LockTime::from_consensus(expiry);
+
+        let witness_script = ScriptBuf::builder()
+            .push_opcode(OP_IF)
+            .push_opcode(OP_SHA256)
+            .push_slice(payment_hash.to_byte_array())
+            .push_opcode(OP_EQUALVERIFY)
+            .push_key(&recipient)
+            .push_opcode(OP_ELSE)
+            .push_lock_time(expiry)
+            .push_opcode(OP_CLTV)
+            .push_opcode(OP_DROP)
+            .push_key(&refund)
+            .push_opcode(OP_ENDIF)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        Htlc {
+            expiry,
+            witness_script,
+        }
+    }
+    /// Creates the HTLC from the given proto, as used by the refund input.
+    pub fn from_proto(refund: &Proto::mod_Input::InputHtlcRefund<'_>) -> Result<Htlc> {
+        let payment_hash = sha256::Hash::from_slice(refund.payment_hash.as_ref())
+            .map_err(|_| Error::from(Proto::Error::Error_invalid_payment_hash))?;
+
+        let recipient = segwit_public_key(refund.recipient_public_key.as_ref())?;
+        let refund_key = segwit_public_key(refund.refund_public_key.as_ref())?;
+
+        Ok(Htlc::new(
+            payment_hash,
+            recipient,
+            refund_key,
+            refund.expiry,
+        ))
+    }
+    pub fn witness_script(&self) -> &Script {
+        self.witness_script.as_script()
+    }
+    pub fn expiry(&self) -> LockTime {
+        self.expiry
+    }
+    /// Checks whether a transaction with the given lock time can refund the
+    /// HTLC with the given input sequence, see [`super::CltvVault::check_spend`].
+    pub fn check_refund(&self, lock_time: LockTime, sequence: u32) -> Result<()> {
+        if !self.expiry.is_implied_by(lock_time) {
+            return Err(Error::from(Proto::Error::Error_htlc_not_expired)
+                .with_context("the HTLC has not expired yet"));
+        }
+
+        if Sequence(sequence) == Sequence::MAX {
+            return Err(Error::from(Proto::Error::Error_htlc_final_sequence)
+                .with_context("a final sequence disables the HTLC expiry"));
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,15 @@
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
 use crate::aliases::*;
-use crate::modules::transactions::OrdinalNftInscription;
+use crate::modules::pay_to_contract;
+use crate::modules::transactions::{
+    check_tapscript, segwit_public_key, taproot_output_key, CltvVault, Htlc, InscriptionId,
+    OrdinalNftInscription,
+};
 use crate::{Error, Result};
-use bitcoin::taproot::{LeafVersion, TapLeafHash};
-use bitcoin::ScriptBuf;
+use bitcoin::key::TweakedPublicKey;
+use bitcoin::script::Instruction;
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapLeafHash, TapNodeHash};
+use bitcoin::{Script, ScriptBuf, VarInt};
 use secp256k1::XOnlyPublicKey;
 use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
@@ -56,6 +62,22 @@ impl InputBuilder {
                         ),
                     )
                 },
+                ProtoInputBuilder::p2pk(pubkey) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+                    let script_pubkey = ScriptBuf::new_p2pk(&pubkey);
+
+                    (
+                        UtxoProto::SigningMethod::Legacy,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // scale factor applied to non-witness bytes
+                        4 * (
+                            // length + ECDSA signature
+                            1 + 72
+                            // NO public key
+                        ),
+                    )
+                },
                 ProtoInputBuilder::p2wsh(redeem_script) => {
                     // The scriptPubkey is the redeem script directly.
                     let script_pubkey = ScriptBuf::from_bytes(redeem_script.to_vec());
@@ -72,7 +94,7 @@ impl InputBuilder {
                     )
                 },
                 ProtoInputBuilder::p2wpkh(pubkey) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+                    let pubkey = segwit_public_key(pubkey.as_ref())?;
 
                     let script_pubkey =
                         ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().ok_or_else(|| {
@@ -104,8 +126,11 @@ impl InputBuilder {
                     )
                 },
                 ProtoInputBuilder::p2tr_key_path(key_path) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(key_path.public_key.as_ref())?;
-                    let xonly = XOnlyPublicKey::from(pubkey.inner);
+                    let pubkey = segwit_public_key(key_path.public_key.as_ref())?;
+                    // The y-parity is dropped, the Taproot tweak is always
+                    // applied to the even-y key (BIP-340). The signer negates
+                    // the private key of an odd-y public key accordingly.
+                    let (xonly, _parity) = pubkey.inner.x_only_public_key();
 
                     let signing_method = if key_path.one_prevout {
                         UtxoProto::SigningMethod::TaprootOnePrevout
@@ -114,7 +139,7 @@ impl InputBuilder {
                     };
 
                     let script_pubkey =
-                        ScriptBuf::new_v1_p2tr(&secp256k1::Secp256k1::new(), xonly, None);
+                        ScriptBuf::new_v1_p2tr_tweaked(taproot_output_key(xonly, None)?);
 
                     (
                         signing_method,
@@ -130,12 +155,65 @@ impl InputBuilder {
                         ),
                     )
                 },
+                ProtoInputBuilder::p2tr_untweaked_key_path(pubkey) => {
+                    let pubkey = segwit_public_key(pubkey.as_ref())?;
+                    let xonly = XOnlyPublicKey::from(pubkey.inner);
+
+                    // The output key is the public key itself.
+                    let output_key = TweakedPublicKey::dangerous_assume_tweaked(xonly);
+                    let script_pubkey = ScriptBuf::new_v1_p2tr_tweaked(output_key);
+
+                    (
+                        UtxoProto::SigningMethod::TaprootAll,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item (1)
+                            1 +
+                            // length + Schnorr signature (can be 71 or 72)
+                            1 + 72
+                            // NO public key
+                        ),
+                    )
+                },
+                ProtoInputBuilder::p2tr_pay_to_contract(p2c) => {
+                    let pubkey = segwit_public_key(p2c.public_key.as_ref())?;
+                    let xonly = XOnlyPublicKey::from(pubkey.inner);
+
+                    let output_key =
+                        pay_to_contract::output_key(xonly, p2c.contract.as_ref(), p2c.tweak_order)?;
+                    let script_pubkey = ScriptBuf::new_v1_p2tr_tweaked(output_key);
+
+                    (
+                        UtxoProto::SigningMethod::TaprootAll,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item (1)
+                            1 +
+                            // length + Schnorr signature (can be 71 or 72)
+                            1 + 72
+                            // NO public key
+                        ),
+                    )
+                },
                 ProtoInputBuilder::p2tr_script_path(complex) => {
-                    let script_pubkey = ScriptBuf::from_bytes(complex.payload.to_vec());
-                    let leaf_hash = Some(TapLeafHash::from_script(
-                        script_pubkey.as_script(),
-                        bitcoin::taproot::LeafVersion::TapScript,
-                    ));
+                    let payload = ScriptBuf::from_bytes(complex.payload.to_vec());
+                    check_tapscript(&payload)?;
+
+                    let leaf_hash =
+                        TapLeafHash::from_script(payload.as_script(), LeafVersion::TapScript);
+
+                    // The Taproot sighash commits to the scriptPubkeys of
+                    // all spent outputs, including those spent via key-path,
+                    // so we reconstruct the actual P2TR scriptPubkey from the
+                    // control block (rather than using the payload).
+                    let control_block = ControlBlock::decode(complex.control_block.as_ref())
+                        .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
+                    let script_pubkey = p2tr_script_pubkey(&control_block, leaf_hash)?;
+                    let leaf_hash = Some(leaf_hash);
 
                     let signing_method = if complex.one_prevout {
                         UtxoProto::SigningMethod::TaprootOnePrevout
@@ -157,12 +235,22 @@ impl InputBuilder {
                     )
                 },
                 ProtoInputBuilder::ordinal_inscribe(ordinal) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(ordinal.inscribe_to.as_ref())?;
+                    let pubkey = segwit_public_key(ordinal.inscribe_to.as_ref())?;
                     let mime_type = ordinal.mime_type.as_ref();
                     let data = ordinal.payload.as_ref();
 
-                    let nft = OrdinalNftInscription::new(mime_type.as_bytes(), data, pubkey)
-                        .expect("badly constructed Ordinal inscription");
+                    let parent = ordinal
+                        .parent
+                        .as_ref()
+                        .map(InscriptionId::from_proto)
+                        .transpose()?;
+
+                    let nft = OrdinalNftInscription::with_parent(
+                        mime_type.as_bytes(),
+                        data,
+                        parent.as_ref(),
+                        pubkey,
+                    )?;
 
                     // We construct a control block to estimate the fee,
                     // otherwise we do not need it here.
@@ -210,7 +298,7 @@ impl InputBuilder {
                 },
                 // TODO: Unify this and `ordinal_inscribe` somehow
                 ProtoInputBuilder::brc20_inscribe(brc20) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(brc20.inscribe_to.as_ref())?;
+                    let pubkey = segwit_public_key(brc20.inscribe_to.as_ref())?;
                     let ticker = Brc20Ticker::new(brc20.ticker.to_string())?;
 
                     let transfer =
@@ -258,6 +346,55 @@ impl InputBuilder {
                         ),
                     )
                 },
+                ProtoInputBuilder::cltv_vault(vault) => {
+                    let pubkey = segwit_public_key(vault.public_key.as_ref())?;
+                    let vault = CltvVault::new(vault.expiry, pubkey);
+
+                    // The scriptPubkey is the witness script directly.
+                    let script_pubkey = ScriptBuf::from(vault.witness_script());
+
+                    (
+                        UtxoProto::SigningMethod::Segwit,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item (2)
+                            1 +
+                            // length + ECDSA signature (can be 71 or 72)
+                            1 + 72 +
+                            // length + witness script
+                            1 + vault.witness_script().len() as u64
+                        ),
+                    )
+                },
+                ProtoInputBuilder::htlc_refund(refund) => {
+                    let htlc = Htlc::from_proto(refund)?;
+
+                    // The scriptPubkey is the witness script directly.
+                    let script_pubkey = ScriptBuf::from(htlc.witness_script());
+
+                    (
+                        UtxoProto::SigningMethod::Segwit,
+                        script_pubkey,
+                        NO_LEAF_HASH,
+                        // witness bytes, scale factor NOT applied.
+                        (
+                            // indicator of witness item (3)
+                            1 +
+                            // length + ECDSA signature (can be 71 or 72)
+                            1 + 72 +
+                            // length of the empty item, which selects the
+                            // `OP_ELSE` branch
+                            1 +
+                            // length + witness script
+                            1 + htlc.witness_script().len() as u64
+                        ),
+                    )
+                },
+                ProtoInputBuilder::auto(_) => {
+                    return Err(Error::from(Proto::Error::Error_unsupported_auto_input))
+                },
                 ProtoInputBuilder::None => {
                     return Err(Error::from(Proto::Error::Error_missing_input_builder))
                 },
@@ -300,6 +437,31 @@ impl InputBuilder {
             },
         };
 
+        // Use the expected scriptSig size of legacy inputs, if provided.
+        let weight = if input.script_sig_size != 0
+            && matches!(signing_method, UtxoProto::SigningMethod::Legacy)
+        {
+            let script_sig_size = input.script_sig_size as u64;
+
+            // scale factor applied to non-witness bytes
+            4 * (
+                // the unsigned input already accounts for a single byte of the
+                // length prefix.
+                VarInt(script_sig_size).len() as u64 - 1 + script_sig_size
+            )
+        } else {
+            weight
+        };
+
+        // Only leaf scripts of script-path spends contain code separators.
+        let codesep_pos = match &input.to_recipient {
+            ProtoInputRecipient::builder(builder) => match &builder.variant {
+                ProtoInputBuilder::p2tr_script_path(complex) => complex.codesep_pos.clone(),
+                _ => None,
+            },
+            _ => None,
+        };
+
         // Create Utxo.proto structure.
         let utxo = UtxoProto::TxIn {
             txid: input.txid.to_vec().into(),
@@ -313,8 +475,178 @@ impl InputBuilder {
             leaf_hash: leaf_hash
                 .map(|hash| hash.to_vec().into())
                 .unwrap_or_default(),
+            codesep_pos,
         };
 
         Ok(utxo)
     }
+    /// Detects the spend path of the given `auto` input from the scriptPubkey
+    /// of the spent output. The public key is taken from the input or, if
+    /// empty, derived from the given private key. Returns `None` if the
+    /// scriptPubkey is not a P2PKH, P2WPKH or P2TR (key-path) output of that
+    /// public key.
+    pub fn resolve_auto(
+        auto: &Proto::mod_Input::InputAuto<'_>,
+        private_key: &[u8],
+    ) -> Option<ProtoInputBuilder<'static>> {
+        let pubkey = if auto.public_key.is_empty() {
+            let secret = secp256k1::SecretKey::from_slice(private_key).ok()?;
+            bitcoin::PublicKey::new(secret.public_key(&secp256k1::Secp256k1::new()))
+        } else {
+            bitcoin::PublicKey::from_slice(auto.public_key.as_ref()).ok()?
+        };
+
+        let script = Script::from_bytes(auto.script_pubkey.as_ref());
+        let pubkey_bytes = pubkey.to_bytes();
+
+        if script.is_p2pkh() && script == ScriptBuf::new_p2pkh(&pubkey.pubkey_hash()).as_script() {
+            Some(ProtoInputBuilder::p2pkh(pubkey_bytes.into()))
+        } else if script.is_v0_p2wpkh()
+            && script == ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash()?).as_script()
+        {
+            Some(ProtoInputBuilder::p2wpkh(pubkey_bytes.into()))
+        } else if script.is_v1_p2tr()
+            && script
+                == ScriptBuf::new_v1_p2tr_tweaked(
+                    taproot_output_key(XOnlyPublicKey::from(pubkey.inner), None).ok()?,
+                )
+                .as_script()
+        {
+            Some(ProtoInputBuilder::p2tr_key_path(
+                Proto::mod_Input::InputTaprootKeyPath {
+                    one_prevout: false,
+                    public_key: pubkey_bytes.into(),
+                },
+            ))
+        } else {
+            None
+        }
+    }
+    /// Returns the public key(s) expected to sign the given input, as derived
+    /// from the builder or the provided spending condition.
+    pub fn signing_keys_from_proto(
+        input: &Proto::Input<'_>,
+    ) -> Result<Proto::mod_PreSigningOutput::SigningKeys<'static>> {
+        let (public_keys, public_key_hash) = match &input.to_recipient {
+            ProtoInputRecipient::builder(builder) => match &builder.variant {
+                ProtoInputBuilder::p2pkh(pubkey) | ProtoInputBuilder::p2wpkh(pubkey) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+
+                    (vec![pubkey.to_bytes()], Some(pubkey.pubkey_hash().to_vec()))
+                },
+                // Commits to the public key itself, not its hash.
+                ProtoInputBuilder::p2pk(pubkey) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::p2sh(redeem_script)
+                | ProtoInputBuilder::p2wsh(redeem_script) => {
+                    let script = ScriptBuf::from_bytes(redeem_script.to_vec());
+                    (public_keys_from_script(&script, false), None)
+                },
+                ProtoInputBuilder::p2tr_key_path(key_path) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(key_path.public_key.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::p2tr_pay_to_contract(p2c) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(p2c.public_key.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::p2tr_untweaked_key_path(pubkey) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::p2tr_script_path(complex) => {
+                    let script = ScriptBuf::from_bytes(complex.payload.to_vec());
+                    (public_keys_from_script(&script, true), None)
+                },
+                ProtoInputBuilder::ordinal_inscribe(ordinal) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(ordinal.inscribe_to.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::brc20_inscribe(brc20) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(brc20.inscribe_to.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::cltv_vault(vault) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(vault.public_key.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::htlc_refund(refund) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(refund.refund_public_key.as_ref())?;
+                    (vec![pubkey.to_bytes()], None)
+                },
+                ProtoInputBuilder::auto(_) => {
+                    return Err(Error::from(Proto::Error::Error_unsupported_auto_input))
+                },
+                ProtoInputBuilder::None => {
+                    return Err(Error::from(Proto::Error::Error_missing_input_builder))
+                },
+            },
+            ProtoInputRecipient::custom_script(custom) => {
+                let script = ScriptBuf::from_bytes(custom.script_pubkey.to_vec());
+                let bytes = script.as_bytes();
+
+                if script.is_p2pkh() {
+                    // OP_DUP OP_HASH160 <20-byte hash> OP_EQUALVERIFY OP_CHECKSIG
+                    (vec![], Some(bytes[3..23].to_vec()))
+                } else if script.is_v0_p2wpkh() {
+                    // OP_0 <20-byte hash>
+                    (vec![], Some(bytes[2..22].to_vec()))
+                } else {
+                    let is_taproot = matches!(
+                        custom.signing_method,
+                        UtxoProto::SigningMethod::TaprootAll
+                            | UtxoProto::SigningMethod::TaprootOnePrevout
+                    );
+
+                    (public_keys_from_script(&script, is_taproot), None)
+                }
+            },
+            ProtoInputRecipient::None => {
+                return Err(Error::from(Proto::Error::Error_missing_input_builder))
+            },
+        };
+
+        Ok(Proto::mod_PreSigningOutput::SigningKeys {
+            public_keys: public_keys.into_iter().map(Into::into).collect(),
+            public_key_hash: public_key_hash.map(Into::into).unwrap_or_default(),
+        })
+    }
+}
+
+// Convenience function: collects all public keys pushed by the given script.
+// Tapscripts use x-only public keys, other scripts use compressed or
+// uncompressed public keys.
+fn public_keys_from_script(script: &Script, xonly: bool) -> Vec<Vec<u8>> {
+    script
+        .instructions()
+        .filter_map(|instruction| match instruction {
+            Ok(Instruction::PushBytes(bytes)) => Some(bytes.as_bytes()),
+            _ => None,
+        })
+        .filter(|bytes| match bytes.len() {
+            32 if xonly => XOnlyPublicKey::from_slice(bytes).is_ok(),
+            33 | 65 if !xonly => bitcoin::PublicKey::from_slice(bytes).is_ok(),
+            _ => false,
+        })
+        .map(|bytes| bytes.to_vec())
+        .collect()
+}
+
+// Convenience function: returns the P2TR scriptPubkey that is committed to by
+// the given control block and leaf.
+fn p2tr_script_pubkey(control_block: &ControlBlock, leaf_hash: TapLeafHash) -> Result<ScriptBuf> {
+    let merkle_root = control_block
+        .merkle_branch
+        .as_inner()
+        .iter()
+        .fold(TapNodeHash::from(leaf_hash), |node, sibling| {
+            TapNodeHash::from_node_hashes(node, *sibling)
+        });
+
+    Ok(ScriptBuf::new_v1_p2tr_tweaked(taproot_output_key(
+        control_block.internal_key,
+        Some(merkle_root),
+    )?))
 }
@@ -1,8 +1,10 @@
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
-use super::OrdinalNftInscription;
+use super::{CltvVault, Htlc, InscriptionId, OrdinalNftInscription};
 use crate::aliases::*;
 use crate::{Error, Result};
 use bitcoin::consensus::Decodable;
+use bitcoin::script::Instruction;
+use bitcoin::sighash::TapSighashType;
 use bitcoin::taproot::{ControlBlock, LeafVersion};
 use bitcoin::{ScriptBuf, Witness};
 use std::borrow::Cow;
@@ -11,6 +13,17 @@ use tw_misc::traits::ToBytesVec;
 use tw_proto::BitcoinV2::Proto;
 use tw_proto::Utxo::Proto as UtxoProto;
 
+/// The maximum size of a stack element (in bytes), as enforced by consensus
+/// for legacy and Segwit scripts.
+pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+
+/// The maximum number of witness stack items (excluding the witness script) of
+/// a P2WSH spend, as enforced by the relay policy of Bitcoin Core.
+pub const MAX_STANDARD_P2WSH_STACK_ITEMS: usize = 100;
+
+/// The maximum number of stack items, as enforced by consensus.
+pub const MAX_STACK_SIZE: usize = 1000;
+
 pub struct InputClaimBuilder;
 
 impl InputClaimBuilder {
@@ -19,13 +32,29 @@ impl InputClaimBuilder {
     pub fn utxo_claim_from_proto(
         input: &Proto::Input<'_>,
         signature: SignatureBytes,
+    ) -> Result<UtxoProto::TxInClaim<'static>> {
+        Self::utxo_claim_from_proto_with_max_stack_items(
+            input,
+            signature,
+            MAX_STANDARD_P2WSH_STACK_ITEMS,
+        )
+    }
+    /// Like [`InputClaimBuilder::utxo_claim_from_proto`], but with the given
+    /// maximum number of witness stack items for P2WSH and custom Segwit
+    /// inputs.
+    pub fn utxo_claim_from_proto_with_max_stack_items(
+        input: &Proto::Input<'_>,
+        signature: SignatureBytes,
+        max_stack_items: usize,
     ) -> Result<UtxoProto::TxInClaim<'static>> {
         let (script_sig, witness) = match &input.to_recipient {
             ProtoInputRecipient::builder(variant) => match &variant.variant {
-                ProtoInputBuilder::p2sh(redeem_script) => (
-                    ScriptBuf::from_bytes(redeem_script.to_vec()),
-                    Witness::new(),
-                ),
+                ProtoInputBuilder::p2sh(redeem_script) => {
+                    let script_sig = ScriptBuf::from_bytes(redeem_script.to_vec());
+                    check_push_sizes(&script_sig)?;
+
+                    (script_sig, Witness::new())
+                },
                 ProtoInputBuilder::p2pkh(pubkey) => {
                     let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
                     let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
@@ -39,10 +68,27 @@ impl InputClaimBuilder {
                         Witness::new(),
                     )
                 },
+                ProtoInputBuilder::p2pk(_) => {
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+
+                    // The public key is part of the scriptPubkey already.
+                    (
+                        ScriptBuf::builder()
+                            .push_slice(sig.serialize())
+                            .into_script(),
+                        Witness::new(),
+                    )
+                },
                 ProtoInputBuilder::p2wsh(redeem_script) => {
                     let witness = Witness::consensus_decode(&mut redeem_script.as_ref())
                         .map_err(|_| Error::from(Proto::Error::Error_invalid_witness_encoding))?;
 
+                    // The last item is the witness script itself, which is not
+                    // pushed onto the stack.
+                    let stack_items = witness.len().saturating_sub(1);
+                    check_witness_stack_items(stack_items, max_stack_items)?;
+                    check_witness_element_sizes(witness.iter().take(stack_items))?;
+
                     (ScriptBuf::new(), witness)
                 },
                 ProtoInputBuilder::p2wpkh(pubkey) => {
@@ -57,8 +103,10 @@ impl InputClaimBuilder {
                         w
                     })
                 },
-                ProtoInputBuilder::p2tr_key_path(_) => {
-                    let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
+                ProtoInputBuilder::p2tr_key_path(_)
+                | ProtoInputBuilder::p2tr_pay_to_contract(_)
+                | ProtoInputBuilder::p2tr_untweaked_key_path(_) => {
+                    let sig = taproot_signature_from_proto(input.sighash_type, &signature)?;
 
                     // The spending script itself.
                     (ScriptBuf::new(), {
@@ -71,7 +119,7 @@ impl InputClaimBuilder {
                     let control_block = ControlBlock::decode(taproot.control_block.as_ref())
                         .map_err(|_| Error::from(Proto::Error::Error_invalid_control_block))?;
 
-                    let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
+                    let sig = taproot_signature_from_proto(input.sighash_type, &signature)?;
 
                     // The spending script itself.
                     (ScriptBuf::new(), {
@@ -87,8 +135,18 @@ impl InputClaimBuilder {
                     let mime_type = ordinal.mime_type.as_ref();
                     let data = ordinal.payload.as_ref();
 
-                    let nft = OrdinalNftInscription::new(mime_type.as_bytes(), data, pubkey)
-                        .expect("badly constructed Ordinal inscription");
+                    let parent = ordinal
+                        .parent
+                        .as_ref()
+                        .map(InscriptionId::from_proto)
+                        .transpose()?;
+
+                    let nft = OrdinalNftInscription::with_parent(
+                        mime_type.as_bytes(),
+                        data,
+                        parent.as_ref(),
+                        pubkey,
+                    )?;
 
                     // Create a control block for that inscription.
                     let control_block = nft
@@ -100,7 +158,7 @@ impl InputClaimBuilder {
                         ))
                         .expect("badly constructed control block");
 
-                    let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
+                    let sig = taproot_signature_from_proto(input.sighash_type, &signature)?;
 
                     // The spending script itself.
                     (ScriptBuf::new(), {
@@ -130,7 +188,7 @@ impl InputClaimBuilder {
                         ))
                         .expect("badly constructed control block");
 
-                    let sig = bitcoin::taproot::Signature::from_slice(signature.as_ref())?;
+                    let sig = taproot_signature_from_proto(input.sighash_type, &signature)?;
 
                     // The spending script itself.
                     (ScriptBuf::new(), {
@@ -141,14 +199,64 @@ impl InputClaimBuilder {
                         w
                     })
                 },
+                ProtoInputBuilder::cltv_vault(vault) => {
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+                    let pubkey = bitcoin::PublicKey::from_slice(vault.public_key.as_ref())?;
+                    let vault = CltvVault::new(vault.expiry, pubkey);
+
+                    // The spending script itself.
+                    (ScriptBuf::new(), {
+                        let mut w = Witness::new();
+                        w.push(sig.serialize());
+                        w.push(vault.witness_script());
+                        w
+                    })
+                },
+                ProtoInputBuilder::htlc_refund(refund) => {
+                    let sig = bitcoin::ecdsa::Signature::from_slice(signature.as_ref())?;
+                    let htlc = Htlc::from_proto(refund)?;
+
+                    // The spending script itself. The empty item selects the
+                    // `OP_ELSE` (refund) branch.
+                    (ScriptBuf::new(), {
+                        let mut w = Witness::new();
+                        w.push(sig.serialize());
+                        w.push(Vec::<u8>::new());
+                        w.push(htlc.witness_script());
+                        w
+                    })
+                },
+                ProtoInputBuilder::auto(_) => {
+                    return Err(Error::from(Proto::Error::Error_unsupported_auto_input))
+                },
                 ProtoInputBuilder::None => {
                     return Err(Error::from(Proto::Error::Error_missing_input_builder))
                 },
             },
-            ProtoInputRecipient::custom_script(custom) => (
-                ScriptBuf::from_bytes(custom.script_sig.to_vec()),
-                Witness::from_slice(&custom.witness_items),
-            ),
+            ProtoInputRecipient::custom_script(custom) => {
+                let script_sig = ScriptBuf::from_bytes(custom.script_sig.to_vec());
+                check_push_sizes(&script_sig)?;
+
+                let witness = Witness::from_slice(&custom.witness_items);
+
+                // The element size limit differs for Taproot (BIP-342).
+                if let UtxoProto::SigningMethod::Legacy | UtxoProto::SigningMethod::Segwit =
+                    custom.signing_method
+                {
+                    // For P2WSH, the last item is the witness script itself.
+                    let script_pubkey = ScriptBuf::from_bytes(custom.script_pubkey.to_vec());
+                    let stack_items = if script_pubkey.is_v0_p2wsh() {
+                        witness.len().saturating_sub(1)
+                    } else {
+                        witness.len()
+                    };
+
+                    check_witness_stack_items(stack_items, max_stack_items)?;
+                    check_witness_element_sizes(witness.iter().take(stack_items))?;
+                }
+
+                (script_sig, witness)
+            },
             ProtoInputRecipient::None => {
                 return Err(Error::from(Proto::Error::Error_missing_recipient))
             },
@@ -170,3 +278,81 @@ impl InputClaimBuilder {
         Ok(claim)
     }
 }
+
+// Checks that none of the pushes of the given scriptSig exceeds the maximum
+// (consensus) size of stack elements.
+fn check_push_sizes(script_sig: &ScriptBuf) -> Result<()> {
+    for instruction in script_sig.instructions() {
+        let Ok(Instruction::PushBytes(push)) = instruction else {
+            continue;
+        };
+
+        if push.len() > MAX_SCRIPT_ELEMENT_SIZE {
+            return Err(
+                Error::from(Proto::Error::Error_script_element_too_large).with_context(format!(
+                    "scriptSig push of {} bytes exceeds {MAX_SCRIPT_ELEMENT_SIZE} bytes",
+                    push.len()
+                )),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Checks that none of the given witness elements exceeds the maximum
+// (consensus) size of stack elements.
+fn check_witness_element_sizes<'a>(elements: impl Iterator<Item = &'a [u8]>) -> Result<()> {
+    for element in elements {
+        if element.len() > MAX_SCRIPT_ELEMENT_SIZE {
+            return Err(
+                Error::from(Proto::Error::Error_script_element_too_large).with_context(format!(
+                    "witness element of {} bytes exceeds {MAX_SCRIPT_ELEMENT_SIZE} bytes",
+                    element.len()
+                )),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Checks the number of witness stack items against the (policy) maximum.
+fn check_witness_stack_items(stack_items: usize, max_stack_items: usize) -> Result<()> {
+    if stack_items > max_stack_items {
+        return Err(
+            Error::from(Proto::Error::Error_too_many_witness_stack_items).with_context(format!(
+                "witness of {stack_items} stack items exceeds the maximum of {max_stack_items}"
+            )),
+        );
+    }
+
+    Ok(())
+}
+
+// Convenience function: constructs the Schnorr signature as it must be pushed
+// into the witness. The sighash type byte is only appended (65 bytes) if the
+// input uses a non-default sighash type; for `SIGHASH_DEFAULT` (0x00) the plain
+// 64-byte signature is used. This also covers external signers that only
+// return the 64-byte Schnorr signature regardless of the sighash type.
+fn taproot_signature_from_proto(
+    sighash_type: UtxoProto::SighashType,
+    signature: &[u8],
+) -> Result<bitcoin::taproot::Signature> {
+    let sig = bitcoin::taproot::Signature::from_slice(signature)?;
+
+    // Note that `UseDefault = 0` maps to `TapSighashType::Default`.
+    let hash_ty = TapSighashType::from_consensus_u8(sighash_type as u8)
+        .map_err(|_| Error::from(Proto::Error::Error_utxo_invalid_sighash_type))?;
+
+    // If the signature already carries a sighash type byte, it must match
+    // the one of the input.
+    if signature.len() == 65 && sig.hash_ty != hash_ty {
+        return Err(Error::from(Proto::Error::Error_invalid_schnorr_signature));
+    }
+
+    Ok(bitcoin::taproot::Signature {
+        sig: sig.sig,
+        hash_ty,
+    })
+}
@@ -1,19 +1,41 @@
-use bitcoin::key::PublicKey;
+use crate::{Error, Result};
+use bitcoin::hashes::Hash;
+use bitcoin::key::{PublicKey, TweakedPublicKey};
 use bitcoin::script::ScriptBuf;
-use bitcoin::taproot::{TapNodeHash, TaprootSpendInfo};
+use bitcoin::taproot::{TapNodeHash, TapTweakHash, TaprootSpendInfo};
+use secp256k1::{KeyPair, Scalar, Secp256k1, XOnlyPublicKey};
+use tw_proto::BitcoinV2::Proto;
 
 mod brc20;
+mod cltv_vault;
+mod htlc;
 mod input_builder;
 mod input_claim_builder;
 mod ordinals;
 mod output_builder;
+mod taproot_multisig;
+mod taproot_script_only;
+mod taproot_tree;
+mod tapscript;
 
 // Re-exports
 pub use brc20::{BRC20TransferInscription, Brc20Ticker};
+pub(crate) use cltv_vault::lock_time_from_proto;
+pub use cltv_vault::CltvVault;
+pub use htlc::Htlc;
 pub use input_builder::InputBuilder;
-pub use input_claim_builder::InputClaimBuilder;
-pub use ordinals::{OrdinalNftInscription, OrdinalsInscription};
+pub use input_claim_builder::{
+    InputClaimBuilder, MAX_SCRIPT_ELEMENT_SIZE, MAX_STACK_SIZE, MAX_STANDARD_P2WSH_STACK_ITEMS,
+};
+pub use ordinals::{
+    inscription_parent, InscriptionId, OrdinalNftInscription, OrdinalsInscription,
+    MAX_INSCRIPTION_PAYLOAD_SIZE,
+};
 pub use output_builder::OutputBuilder;
+pub use taproot_multisig::TaprootMultisig;
+pub use taproot_script_only::{TaprootScriptOnly, NUMS_INTERNAL_KEY};
+pub use taproot_tree::TaprootTree;
+pub use tapscript::check_tapscript;
 
 pub struct TaprootScript {
     pub pubkey: PublicKey,
@@ -24,3 +46,67 @@ pub struct TaprootProgram {
     pub script: ScriptBuf,
     pub spend_info: TaprootSpendInfo,
 }
+
+/// Parses the public key of a Segwit or Taproot builder. Uncompressed public
+/// keys are non-standard in Segwit (BIP-143) and would result in an output
+/// that can not be spent, so they are rejected upfront.
+pub(crate) fn segwit_public_key(pubkey: &[u8]) -> Result<PublicKey> {
+    let pubkey = PublicKey::from_slice(pubkey)?;
+
+    if !pubkey.compressed {
+        return Err(Error::from(Proto::Error::Error_uncompressed_public_key)
+            .with_context("Segwit and Taproot require a compressed (33-byte) public key"));
+    }
+
+    Ok(pubkey)
+}
+
+/// Returns the Taproot output key `Q = P + tG` of the given internal key and
+/// optional Merkle root, with `t = hashTapTweak(P || merkle_root)` (BIP-341).
+///
+/// Unlike `TapTweak`, which panics, this fails if the tweak is not a valid
+/// scalar or the output key is invalid, see [`tweak_output_key`].
+pub fn taproot_output_key(
+    internal_key: XOnlyPublicKey,
+    merkle_root: Option<TapNodeHash>,
+) -> Result<TweakedPublicKey> {
+    let tweak_hash = TapTweakHash::from_key_and_tweak(internal_key, merkle_root);
+    let tweak = Scalar::from_be_bytes(tweak_hash.to_byte_array()).map_err(|_| {
+        Error::from(Proto::Error::Error_invalid_taproot_tweaked_pubkey)
+            .with_context("the Taproot tweak exceeds the curve order")
+    })?;
+
+    tweak_output_key(internal_key, tweak)
+}
+
+/// Returns the keypair that signs for the Taproot output key as returned by
+/// [`taproot_output_key`]. The secret key is negated first if the public key
+/// has an odd y-coordinate, matching the (even) x-only internal key.
+///
+/// Unlike `TapTweak`, which panics, this fails if the tweak is not a valid
+/// scalar or the tweaked secret key is invalid.
+pub fn taproot_keypair(keypair: &KeyPair, merkle_root: Option<TapNodeHash>) -> Result<KeyPair> {
+    let invalid_tweak = || Error::from(Proto::Error::Error_invalid_taproot_tweaked_pubkey);
+
+    let (internal_key, _parity) = keypair.x_only_public_key();
+    let tweak_hash = TapTweakHash::from_key_and_tweak(internal_key, merkle_root);
+    let tweak = Scalar::from_be_bytes(tweak_hash.to_byte_array())
+        .map_err(|_| invalid_tweak().with_context("the Taproot tweak exceeds the curve order"))?;
+
+    keypair
+        .add_xonly_tweak(&Secp256k1::new(), &tweak)
+        .map_err(|_| invalid_tweak().with_context("the Taproot tweak results in an invalid key"))
+}
+
+/// Applies the given tweak to the internal key, which fails if `P + tG` is the
+/// point at infinity.
+pub fn tweak_output_key(internal_key: XOnlyPublicKey, tweak: Scalar) -> Result<TweakedPublicKey> {
+    let (output_key, _parity) = internal_key
+        .add_tweak(&Secp256k1::verification_only(), &tweak)
+        .map_err(|_| {
+            Error::from(Proto::Error::Error_invalid_taproot_tweaked_pubkey)
+                .with_context("the Taproot tweak results in an invalid output key")
+        })?;
+
+    Ok(TweakedPublicKey::dangerous_assume_tweaked(output_key))
+}
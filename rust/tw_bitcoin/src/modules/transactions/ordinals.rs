@@ -1,11 +1,74 @@
 use super::TaprootProgram;
 use crate::{Error, Result};
-use bitcoin::script::{PushBytesBuf, ScriptBuf};
+use bitcoin::hashes::Hash;
+use bitcoin::opcodes::all::OP_IF;
+use bitcoin::script::{Instruction, PushBytesBuf, ScriptBuf};
 use bitcoin::secp256k1::XOnlyPublicKey;
 use bitcoin::taproot::{TaprootBuilder, TaprootSpendInfo};
-use bitcoin::{PublicKey, Script};
+use bitcoin::{PublicKey, Script, Txid};
 use tw_proto::BitcoinV2::Proto;
 
+/// The maximum size of an inscription payload that fits into a single reveal
+/// transaction without exceeding the standard transaction weight of 400,000
+/// weight units. Witness data counts one weight unit per byte, the remainder
+/// is reserved for the envelope, the control block and the rest of the reveal
+/// transaction. Larger payloads must be split across multiple inscriptions.
+pub const MAX_INSCRIPTION_PAYLOAD_SIZE: usize = 390_000;
+
+/// The envelope tag of the parent inscription.
+const PARENT_TAG: u8 = 3;
+
+/// The ID of an inscription, `<txid>i<index>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InscriptionId {
+    /// The ID of the reveal transaction.
+    pub txid: Txid,
+    /// The index of the inscription within the reveal transaction.
+    pub index: u32,
+}
+
+impl InscriptionId {
+    pub fn from_proto(id: &Proto::InscriptionId<'_>) -> Result<InscriptionId> {
+        let txid = Txid::from_slice(id.txid.as_ref()).map_err(|_| {
+            Error::from(Proto::Error::Error_utxo_invalid_txid)
+                .with_context("the txid of the parent inscription must be 32 bytes")
+        })?;
+
+        Ok(InscriptionId {
+            txid,
+            index: id.index,
+        })
+    }
+    /// Serializes the ID as value of the parent tag: the txid, followed by the
+    /// little-endian index without trailing zero bytes.
+    pub fn to_tag_value(&self) -> Vec<u8> {
+        let index = self.index.to_le_bytes();
+        let index_len = index
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |pos| pos + 1);
+
+        let mut value = self.txid.to_byte_array().to_vec();
+        value.extend_from_slice(&index[..index_len]);
+        value
+    }
+    /// The inverse of [`InscriptionId::to_tag_value`].
+    pub fn from_tag_value(value: &[u8]) -> Option<InscriptionId> {
+        if value.len() < 32 || value.len() > 36 {
+            return None;
+        }
+
+        let (txid, index) = value.split_at(32);
+        let mut index_bytes = [0; 4];
+        index_bytes[..index.len()].copy_from_slice(index);
+
+        Some(InscriptionId {
+            txid: Txid::from_slice(txid).ok()?,
+            index: u32::from_le_bytes(index_bytes),
+        })
+    }
+}
+
 pub struct OrdinalsInscription {
     envelope: TaprootProgram,
 }
@@ -13,8 +76,19 @@ pub struct OrdinalsInscription {
 impl OrdinalsInscription {
     /// Creates a new Ordinals Inscription ("commit stage").
     pub fn new(mime: &[u8], data: &[u8], recipient: PublicKey) -> Result<OrdinalsInscription> {
+        Self::with_parent(mime, data, None, recipient)
+    }
+    /// Creates a new Ordinals Inscription that is a child of the given parent
+    /// inscription (provenance). The reveal transaction must spend the UTXO
+    /// holding the parent inscription, otherwise the parent is ignored.
+    pub fn with_parent(
+        mime: &[u8],
+        data: &[u8],
+        parent: Option<&InscriptionId>,
+        recipient: PublicKey,
+    ) -> Result<OrdinalsInscription> {
         // Create the envelope, containing the inscription content.
-        let envelope = create_envelope(mime, data, recipient)?;
+        let envelope = create_envelope(mime, data, parent, recipient)?;
 
         Ok(OrdinalsInscription { envelope })
     }
@@ -41,10 +115,26 @@ impl OrdinalsInscription {
 /// could also be the same entity. Stage one, the `internal_key` is the
 /// recipient. Stage two, the `internal_key` is the claimer of the transaction
 /// (where the Inscription script is available in the Witness).
-fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<TaprootProgram> {
+fn create_envelope(
+    mime: &[u8],
+    data: &[u8],
+    parent: Option<&InscriptionId>,
+    internal_key: PublicKey,
+) -> Result<TaprootProgram> {
     use bitcoin::opcodes::all::*;
     use bitcoin::opcodes::*;
 
+    if data.len() > MAX_INSCRIPTION_PAYLOAD_SIZE {
+        let chunks = (data.len() + MAX_INSCRIPTION_PAYLOAD_SIZE - 1) / MAX_INSCRIPTION_PAYLOAD_SIZE;
+
+        return Err(
+            Error::from(Proto::Error::Error_ordinal_payload_too_large).with_context(format!(
+                "payload of {} bytes exceeds the single transaction limit of {MAX_INSCRIPTION_PAYLOAD_SIZE} bytes, split it into {chunks} chunks",
+                data.len()
+            )),
+        );
+    }
+
     // Create MIME buffer.
     let mut mime_buf = PushBytesBuf::new();
     mime_buf
@@ -67,9 +157,17 @@ fn create_envelope(mime: &[u8], data: &[u8], internal_key: PublicKey) -> Result<
         // inconsistent, it's the current requirement.
         .push_opcode(OP_PUSHBYTES_1)
         // MIME type identifying the data
-        .push_slice(mime_buf.as_push_bytes())
-        // Separator.
-        .push_opcode(OP_PUSHBYTES_0);
+        .push_slice(mime_buf.as_push_bytes());
+
+    if let Some(parent) = parent {
+        let parent_buf = PushBytesBuf::try_from(parent.to_tag_value())
+            .expect("inscription ID must fit into a single push");
+
+        builder = builder.push_slice(&[PARENT_TAG]).push_slice(parent_buf);
+    }
+
+    // Separator.
+    builder = builder.push_opcode(OP_PUSHBYTES_0);
 
     // Push the actual data in chunks.
     for chunk in data.chunks(520) {
@@ -119,7 +217,52 @@ impl OrdinalNftInscription {
     pub fn new(mime_type: &[u8], data: &[u8], recipient: PublicKey) -> Result<Self> {
         OrdinalsInscription::new(mime_type, data, recipient).map(OrdinalNftInscription)
     }
+    pub fn with_parent(
+        mime_type: &[u8],
+        data: &[u8],
+        parent: Option<&InscriptionId>,
+        recipient: PublicKey,
+    ) -> Result<Self> {
+        OrdinalsInscription::with_parent(mime_type, data, parent, recipient)
+            .map(OrdinalNftInscription)
+    }
     pub fn inscription(&self) -> &OrdinalsInscription {
         &self.0
     }
 }
+
+/// Returns the parent inscription (provenance) referenced by the given
+/// inscription envelope, if any. Only the tags in front of the content are
+/// considered.
+pub fn inscription_parent(envelope: &Script) -> Option<InscriptionId> {
+    let instructions: Vec<Instruction> = envelope
+        .instructions()
+        .collect::<core::result::Result<_, _>>()
+        .ok()?;
+
+    // `OP_FALSE OP_IF "ord" (<tag> <value>)* OP_0 <data>* OP_ENDIF`
+    let [Instruction::PushBytes(op_false), Instruction::Op(op_if), Instruction::PushBytes(protocol), fields @ ..] =
+        instructions.as_slice()
+    else {
+        return None;
+    };
+
+    if !op_false.is_empty() || *op_if != OP_IF || protocol.as_bytes() != b"ord" {
+        return None;
+    }
+
+    for field in fields.chunks(2) {
+        match field {
+            // The separator of the content.
+            [Instruction::PushBytes(tag), ..] if tag.is_empty() => return None,
+            [Instruction::PushBytes(tag), Instruction::PushBytes(value)] => {
+                if tag.as_bytes() == [PARENT_TAG] {
+                    return InscriptionId::from_tag_value(value.as_bytes());
+                }
+            },
+            _ => return None,
+        }
+    }
+
+    None
+}
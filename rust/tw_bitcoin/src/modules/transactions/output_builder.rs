@@ -1,13 +1,19 @@
 use std::str::FromStr;
 
 use super::brc20::{BRC20TransferInscription, Brc20Ticker};
-use super::OrdinalNftInscription;
+use super::{
+    segwit_public_key, taproot_output_key, CltvVault, InscriptionId, OrdinalNftInscription,
+};
 use crate::aliases::*;
+use crate::modules::pay_to_contract;
 use crate::{Error, Result};
 use bitcoin::address::{Payload, WitnessVersion};
 use bitcoin::key::TweakedPublicKey;
+use bitcoin::script::PushBytesBuf;
 use bitcoin::taproot::{LeafVersion, TapNodeHash};
-use bitcoin::{Address, PubkeyHash, ScriptBuf, ScriptHash, WPubkeyHash, WScriptHash};
+use bitcoin::{
+    Address, Network, PubkeyHash, Script, ScriptBuf, ScriptHash, WPubkeyHash, WScriptHash,
+};
 use secp256k1::hashes::Hash;
 use secp256k1::XOnlyPublicKey;
 use tw_misc::traits::ToBytesVec;
@@ -21,18 +27,27 @@ const NO_TAPROOT_PAYLOAD: Option<Vec<u8>> = None;
 
 impl OutputBuilder {
     /// Creates the spending condition (_scriptPubkey_) for a given output.
+    /// Addresses are expected to be mainnet addresses.
     pub fn utxo_from_proto(
         output: &Proto::Output<'_>,
     ) -> Result<Proto::mod_PreSigningOutput::TxOut<'static>> {
-        let secp = secp256k1::Secp256k1::new();
+        Self::utxo_from_proto_for_network(output, Network::Bitcoin)
+    }
 
+    /// Creates the spending condition (_scriptPubkey_) for a given output,
+    /// where addresses must belong to the given network.
+    pub fn utxo_from_proto_for_network(
+        output: &Proto::Output<'_>,
+        network: Network,
+    ) -> Result<Proto::mod_PreSigningOutput::TxOut<'static>> {
         let (script_pubkey, control_block, taproot_payload) = match &output.to_recipient {
             // Script spending condition was passed on directly.
-            ProtoOutputRecipient::custom_script_pubkey(script) => (
-                ScriptBuf::from_bytes(script.to_vec()),
-                NO_CONTROL_BLOCK,
-                NO_TAPROOT_PAYLOAD,
-            ),
+            ProtoOutputRecipient::custom_script_pubkey(script) => {
+                let script_pubkey = ScriptBuf::from_bytes(script.to_vec());
+                check_witness_program(&script_pubkey)?;
+
+                (script_pubkey, NO_CONTROL_BLOCK, NO_TAPROOT_PAYLOAD)
+            },
             // Process builder methods. We construct the Script spending
             // conditions by using the specified parameters.
             ProtoOutputRecipient::builder(builder) => match &builder.variant {
@@ -52,6 +67,14 @@ impl OutputBuilder {
                         NO_TAPROOT_PAYLOAD,
                     )
                 },
+                ProtoOutputBuilder::p2pk(pubkey) => {
+                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
+                    (
+                        ScriptBuf::new_p2pk(&pubkey),
+                        NO_CONTROL_BLOCK,
+                        NO_TAPROOT_PAYLOAD,
+                    )
+                },
                 ProtoOutputBuilder::p2wsh(script_or_hash) => {
                     let wscript_hash = witness_redeem_script_or_hash(script_or_hash)?;
                     (
@@ -69,10 +92,12 @@ impl OutputBuilder {
                     )
                 },
                 ProtoOutputBuilder::p2tr_key_path(pubkey) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(pubkey.as_ref())?;
-                    let xonly = XOnlyPublicKey::from(pubkey.inner);
+                    let pubkey = segwit_public_key(pubkey.as_ref())?;
+                    // The y-parity is dropped, the Taproot tweak is always
+                    // applied to the even-y key (BIP-340).
+                    let (xonly, _parity) = pubkey.inner.x_only_public_key();
                     (
-                        ScriptBuf::new_v1_p2tr(&secp, xonly, None),
+                        ScriptBuf::new_v1_p2tr_tweaked(taproot_output_key(xonly, None)?),
                         NO_CONTROL_BLOCK,
                         NO_TAPROOT_PAYLOAD,
                     )
@@ -81,11 +106,11 @@ impl OutputBuilder {
                     let node_hash = TapNodeHash::from_slice(complex.merkle_root.as_ref())
                         .map_err(|_| Error::from(Proto::Error::Error_invalid_taproot_root))?;
 
-                    let pubkey = bitcoin::PublicKey::from_slice(complex.internal_key.as_ref())?;
+                    let pubkey = segwit_public_key(complex.internal_key.as_ref())?;
                     let xonly = XOnlyPublicKey::from(pubkey.inner);
 
                     (
-                        ScriptBuf::new_v1_p2tr(&secp, xonly, Some(node_hash)),
+                        ScriptBuf::new_v1_p2tr_tweaked(taproot_output_key(xonly, Some(node_hash))?),
                         NO_CONTROL_BLOCK,
                         NO_TAPROOT_PAYLOAD,
                     )
@@ -103,14 +128,37 @@ impl OutputBuilder {
                         NO_TAPROOT_PAYLOAD,
                     )
                 },
+                ProtoOutputBuilder::p2tr_pay_to_contract(p2c) => {
+                    let pubkey = segwit_public_key(p2c.public_key.as_ref())?;
+                    let xonly = XOnlyPublicKey::from(pubkey.inner);
+
+                    let output_key =
+                        pay_to_contract::output_key(xonly, p2c.contract.as_ref(), p2c.tweak_order)?;
+
+                    (
+                        ScriptBuf::new_v1_p2tr_tweaked(output_key),
+                        NO_CONTROL_BLOCK,
+                        NO_TAPROOT_PAYLOAD,
+                    )
+                },
                 ProtoOutputBuilder::ordinal_inscribe(ordinal) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(ordinal.inscribe_to.as_ref())?;
+                    let pubkey = segwit_public_key(ordinal.inscribe_to.as_ref())?;
                     let xonly = XOnlyPublicKey::from(pubkey.inner);
                     let mime_type = ordinal.mime_type.as_ref();
                     let data = ordinal.payload.as_ref();
 
-                    let nft = OrdinalNftInscription::new(mime_type.as_bytes(), data, pubkey)
-                        .expect("badly constructed Ordinal inscription");
+                    let parent = ordinal
+                        .parent
+                        .as_ref()
+                        .map(InscriptionId::from_proto)
+                        .transpose()?;
+
+                    let nft = OrdinalNftInscription::with_parent(
+                        mime_type.as_bytes(),
+                        data,
+                        parent.as_ref(),
+                        pubkey,
+                    )?;
 
                     // Construct the control block.
                     let control_block = nft
@@ -130,13 +178,16 @@ impl OutputBuilder {
                         .expect("badly constructed Taproot merkle root");
 
                     (
-                        ScriptBuf::new_v1_p2tr(&secp, xonly, Some(merkle_root)),
+                        ScriptBuf::new_v1_p2tr_tweaked(taproot_output_key(
+                            xonly,
+                            Some(merkle_root),
+                        )?),
                         Some(control_block.serialize()),
                         Some(nft.inscription().taproot_program().to_vec()),
                     )
                 },
                 ProtoOutputBuilder::brc20_inscribe(brc20) => {
-                    let pubkey = bitcoin::PublicKey::from_slice(brc20.inscribe_to.as_ref())?;
+                    let pubkey = segwit_public_key(brc20.inscribe_to.as_ref())?;
                     let xonly = XOnlyPublicKey::from(pubkey.inner);
 
                     let ticker = Brc20Ticker::new(brc20.ticker.to_string())?;
@@ -162,21 +213,47 @@ impl OutputBuilder {
                         .expect("badly constructed Taproot merkle root");
 
                     (
-                        ScriptBuf::new_v1_p2tr(&secp, xonly, Some(merkle_root)),
+                        ScriptBuf::new_v1_p2tr_tweaked(taproot_output_key(
+                            xonly,
+                            Some(merkle_root),
+                        )?),
                         Some(control_block.serialize()),
                         Some(transfer.inscription().taproot_program().to_vec()),
                     )
                 },
+                ProtoOutputBuilder::cltv_vault(vault) => {
+                    let pubkey = segwit_public_key(vault.public_key.as_ref())?;
+                    let vault = CltvVault::new(vault.expiry, pubkey);
+
+                    (
+                        ScriptBuf::new_v0_p2wsh(&vault.witness_script().wscript_hash()),
+                        NO_CONTROL_BLOCK,
+                        NO_TAPROOT_PAYLOAD,
+                    )
+                },
+                ProtoOutputBuilder::op_return(data) => {
+                    let data = PushBytesBuf::try_from(data.to_vec())
+                        .map_err(|_| Error::from(Proto::Error::Error_non_standard_op_return))?;
+                    // The payload size is checked against the configured
+                    // `SigningInput.data_carrier_size` when signing.
+                    (
+                        ScriptBuf::new_op_return(&data),
+                        NO_CONTROL_BLOCK,
+                        NO_TAPROOT_PAYLOAD,
+                    )
+                },
                 ProtoOutputBuilder::None => {
                     return Err(Error::from(Proto::Error::Error_missing_output_builder))
                 },
             },
             // We derive the transaction type from the address.
             ProtoOutputRecipient::from_address(addr) => {
-                let proto = output_from_address(output.value, addr.as_ref())?;
+                let proto = output_from_address(output.value, addr.as_ref(), network)?;
 
                 // Recursive call, will initiate the appropraite builder.
-                return Self::utxo_from_proto(&proto);
+                let mut utxo = Self::utxo_from_proto_for_network(&proto, network)?;
+                utxo.label = output.label.to_string().into();
+                return Ok(utxo);
             },
             ProtoOutputRecipient::None => {
                 return Err(Error::from(Proto::Error::Error_missing_recipient))
@@ -188,12 +265,37 @@ impl OutputBuilder {
             script_pubkey: script_pubkey.to_vec().into(),
             control_block: control_block.map(|cb| cb.into()).unwrap_or_default(),
             taproot_payload: taproot_payload.map(|cb| cb.into()).unwrap_or_default(),
+            label: output.label.to_string().into(),
         };
 
         Ok(utxo)
     }
 }
 
+// Version 0 witness programs must be either 20 (P2WPKH) or 32 (P2WSH) bytes
+// long, any other length makes the output unspendable. Programs of later
+// versions are not checked, since the lengths of those are not defined yet
+// (such as the 2-byte P2A anchors of version 1).
+fn check_witness_program(script_pubkey: &Script) -> Result<()> {
+    if !script_pubkey.is_witness_program()
+        || script_pubkey.witness_version() != Some(WitnessVersion::V0)
+    {
+        return Ok(());
+    }
+
+    // Skip the version and the push opcode.
+    let program_len = script_pubkey.len() - 2;
+    if program_len != 20 && program_len != 32 {
+        return Err(
+            Error::from(Proto::Error::Error_invalid_witness_program).with_context(format!(
+                "a version 0 witness program of {program_len} bytes is unspendable"
+            )),
+        );
+    }
+
+    Ok(())
+}
+
 // Convenience helper function.
 fn redeem_script_or_hash(
     script_or_hash: &Proto::mod_Output::OutputRedeemScriptOrHash,
@@ -251,7 +353,7 @@ fn witness_pubkey_hash_from_proto(
     let wpubkey_hash = match &pubkey_or_hash.to_address {
         ProtoPubkeyOrHash::hash(hash) => WPubkeyHash::from_slice(hash.as_ref())
             .map_err(|_| Error::from(Proto::Error::Error_invalid_witness_pubkey_hash))?,
-        ProtoPubkeyOrHash::pubkey(pubkey) => bitcoin::PublicKey::from_slice(pubkey.as_ref())?
+        ProtoPubkeyOrHash::pubkey(pubkey) => segwit_public_key(pubkey.as_ref())?
             .wpubkey_hash()
             .ok_or_else(|| Error::from(Proto::Error::Error_invalid_witness_pubkey_hash))?,
         ProtoPubkeyOrHash::None => return Err(Error::from(Proto::Error::Error_missing_recipient)),
@@ -261,13 +363,13 @@ fn witness_pubkey_hash_from_proto(
 }
 
 // Derives the P2* output from the given address.
-fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>> {
+fn output_from_address(value: u64, addr: &str, network: Network) -> Result<Proto::Output<'static>> {
     let string = String::from_utf8(addr.to_vec())
         .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
 
     let addr = Address::from_str(&string)
         .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?
-        .require_network(bitcoin::Network::Bitcoin)
+        .require_network(network)
         .map_err(|_| Error::from(Proto::Error::Error_bad_address_recipient))?;
 
     let proto = match addr.payload {
@@ -279,6 +381,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                     to_address: ProtoPubkeyOrHash::hash(pubkey_hash.to_vec().into()),
                 }),
             }),
+            ..Default::default()
         },
         // Identified a witness program (i.e. Segwit or Taproot).
         Payload::WitnessProgram(progam) => {
@@ -301,6 +404,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                                     }),
                                 },
                             ),
+                            ..Default::default()
                         });
                     }
 
@@ -320,6 +424,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                                     ),
                                 },
                             ),
+                            ..Default::default()
                         });
                     }
 
@@ -341,6 +446,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                                 ),
                             },
                         ),
+                        ..Default::default()
                     }
                 },
                 _ => {
@@ -359,6 +465,7 @@ fn output_from_address(value: u64, addr: &str) -> Result<Proto::Output<'static>>
                     ),
                 }),
             }),
+            ..Default::default()
         },
         _ => {
             return Err(Error::from(
@@ -0,0 +1,109 @@
+use super::TaprootProgram;
+use crate::{Error, Result};
+use bitcoin::script::{Script, ScriptBuf};
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::taproot::{ControlBlock, LeafVersion, TaprootBuilder, TaprootSpendInfo};
+use bitcoin::Witness;
+use tw_proto::BitcoinV2::Proto;
+
+/// A k-of-n Taproot (Tapscript) multisig leaf based on `OP_CHECKSIGADD`
+/// (BIP-342):
+///
+/// `<pubkey_1> OP_CHECKSIG <pubkey_2> OP_CHECKSIGADD ... <pubkey_n> OP_CHECKSIGADD <k> OP_NUMEQUAL`
+pub struct TaprootMultisig {
+    threshold: u32,
+    pubkeys: Vec<XOnlyPublicKey>,
+    program: TaprootProgram,
+}
+
+impl TaprootMultisig {
+    /// Creates the multisig leaf for the given public keys and commits to it
+    /// by the given internal key.
+    pub fn new(
+        threshold: u32,
+        pubkeys: Vec<XOnlyPublicKey>,
+        internal_key: XOnlyPublicKey,
+    ) -> Result<TaprootMultisig> {
+        use bitcoin::opcodes::all::*;
+
+        if threshold == 0 || threshold as usize > pubkeys.len() {
+            return Err(Error::from(Proto::Error::Error_invalid_multisig_threshold));
+        }
+
+        let mut builder = ScriptBuf::builder();
+        for (index, pubkey) in pubkeys.iter().enumerate() {
+            builder = builder.push_x_only_key(pubkey).push_opcode(if index == 0 {
+                OP_CHECKSIG
+            } else {
+                OP_CHECKSIGADD
+            });
+        }
+
+        let script = builder
+            .push_int(threshold as i64)
+            .push_opcode(OP_NUMEQUAL)
+            .into_script();
+
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, script.clone())
+            .expect("Taproot multisig spending info must always build")
+            .finalize(&secp256k1::Secp256k1::new(), internal_key)
+            .expect("Taproot multisig spending info must always build");
+
+        Ok(TaprootMultisig {
+            threshold,
+            pubkeys,
+            program: TaprootProgram { script, spend_info },
+        })
+    }
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+    pub fn pubkeys(&self) -> &[XOnlyPublicKey] {
+        &self.pubkeys
+    }
+    pub fn leaf_script(&self) -> &Script {
+        self.program.script.as_script()
+    }
+    pub fn spend_info(&self) -> &TaprootSpendInfo {
+        &self.program.spend_info
+    }
+    pub fn control_block(&self) -> ControlBlock {
+        self.program
+            .spend_info
+            .control_block(&(self.program.script.clone(), LeafVersion::TapScript))
+            .expect("Taproot multisig leaf must be part of the tree")
+    }
+    /// Creates the script-path witness for the given signatures, one entry per
+    /// public key in the same order. Exactly `threshold` signatures must be
+    /// provided, non-signers are represented by `None`. Since `OP_CHECKSIG` and
+    /// `OP_CHECKSIGADD` consume the signature of the first public key first,
+    /// the signatures are pushed in reverse order, where non-signers are
+    /// represented by empty items (which do not increment the counter).
+    pub fn witness(&self, signatures: &[Option<Vec<u8>>]) -> Result<Witness> {
+        if signatures.len() != self.pubkeys.len() {
+            return Err(Error::from(
+                Proto::Error::Error_unmatched_input_signature_count,
+            ));
+        }
+
+        if signatures.iter().flatten().count() != self.threshold as usize {
+            return Err(Error::from(Proto::Error::Error_invalid_multisig_threshold));
+        }
+
+        let mut witness = Witness::new();
+        for sig in signatures.iter().rev() {
+            match sig {
+                // Schnorr signature, optionally with a sighash type.
+                Some(sig) if sig.len() == 64 || sig.len() == 65 => witness.push(sig),
+                Some(_) => return Err(Error::from(Proto::Error::Error_invalid_schnorr_signature)),
+                None => witness.push(Vec::<u8>::new()),
+            }
+        }
+
+        witness.push(self.program.script.as_bytes());
+        witness.push(self.control_block().serialize());
+
+        Ok(witness)
+    }
+}
@@ -0,0 +1,76 @@
+use super::TaprootProgram;
+use bitcoin::script::{Script, ScriptBuf};
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::taproot::{ControlBlock, LeafVersion, TapNodeHash, TaprootBuilder, TaprootSpendInfo};
+
+/// The provably unspendable "nothing up my sleeve" point `H` of BIP-341,
+/// `lift_x(SHA256(G))`. Nobody knows its discrete logarithm, so an output
+/// committing to it as internal key can not be spent via the key-path.
+pub const NUMS_INTERNAL_KEY: [u8; 32] = [
+    0x50, 0x92, 0x9b, 0x74, 0xc1, 0xa0, 0x49, 0x54, 0xb7, 0x8b, 0x4b, 0x60, 0x35, 0xe9, 0x7a, 0x5e,
+    0x07, 0x8a, 0x5a, 0x0f, 0x28, 0xec, 0x96, 0xd5, 0x47, 0xbf, 0xee, 0x9a, 0xce, 0x80, 0x3a, 0xc0,
+];
+
+/// A script-only Taproot output with a single `<pubkey> OP_CHECKSIG` leaf,
+/// committed to by the NUMS internal key. It can only be spent via the
+/// script-path.
+pub struct TaprootScriptOnly {
+    pubkey: XOnlyPublicKey,
+    program: TaprootProgram,
+}
+
+impl TaprootScriptOnly {
+    pub fn new(pubkey: XOnlyPublicKey) -> TaprootScriptOnly {
+        use bitcoin::opcodes::all::OP_CHECKSIG;
+
+        let script = ScriptBuf::builder()
+            .push_x_only_key(&pubkey)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, script.clone())
+            .expect("Taproot script-only spending info must always build")
+            .finalize(&secp256k1::Secp256k1::new(), Self::internal_key())
+            .expect("Taproot script-only spending info must always build");
+
+        TaprootScriptOnly {
+            pubkey,
+            program: TaprootProgram { script, spend_info },
+        }
+    }
+    pub fn internal_key() -> XOnlyPublicKey {
+        XOnlyPublicKey::from_slice(&NUMS_INTERNAL_KEY).expect("NUMS point must be on the curve")
+    }
+    /// The internal key in the compressed (even) representation, as expected
+    /// by the `p2tr_script_path` output builder.
+    pub fn internal_key_compressed() -> [u8; 33] {
+        let mut key = [0x02; 33];
+        key[1..].copy_from_slice(&NUMS_INTERNAL_KEY);
+        key
+    }
+    pub fn pubkey(&self) -> &XOnlyPublicKey {
+        &self.pubkey
+    }
+    pub fn leaf_script(&self) -> &Script {
+        self.program.script.as_script()
+    }
+    pub fn spend_info(&self) -> &TaprootSpendInfo {
+        &self.program.spend_info
+    }
+    pub fn merkle_root(&self) -> TapNodeHash {
+        self.program
+            .spend_info
+            .merkle_root()
+            .expect("Taproot script-only output must have a merkle root")
+    }
+    pub fn control_block(&self) -> ControlBlock {
+        self.program
+            .spend_info
+            .control_block(&(self.program.script.clone(), LeafVersion::TapScript))
+            .expect("Taproot script-only leaf must be part of the tree")
+    }
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        ScriptBuf::new_v1_p2tr_tweaked(self.program.spend_info.output_key())
+    }
+}
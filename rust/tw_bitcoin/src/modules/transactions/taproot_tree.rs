@@ -0,0 +1,62 @@
+use super::check_tapscript;
+use crate::{Error, Result};
+use bitcoin::script::{Script, ScriptBuf};
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::taproot::{
+    ControlBlock, LeafVersion, TaprootBuilder, TaprootSpendInfo, TAPROOT_CONTROL_MAX_NODE_COUNT,
+};
+use tw_proto::BitcoinV2::Proto;
+
+/// A Taproot script tree of arbitrary shape, committed to by the given
+/// internal key. The leaves are provided in depth-first order, each with its
+/// depth in the tree. The depth of the tree is limited to 128 levels
+/// (BIP-341), a leaf at that depth results in a control block of 4129 bytes.
+/// Each leaf must pass the basic tapscript rules, see [`check_tapscript`].
+pub struct TaprootTree {
+    leaves: Vec<ScriptBuf>,
+    spend_info: TaprootSpendInfo,
+}
+
+impl TaprootTree {
+    pub fn new(leaves: Vec<(u8, ScriptBuf)>, internal_key: XOnlyPublicKey) -> Result<TaprootTree> {
+        let mut builder = TaprootBuilder::new();
+        for (depth, script) in &leaves {
+            if *depth as usize > TAPROOT_CONTROL_MAX_NODE_COUNT {
+                return Err(
+                    Error::from(Proto::Error::Error_invalid_taproot_tree).with_context(format!(
+                        "leaf at depth {depth} exceeds the maximum depth of {TAPROOT_CONTROL_MAX_NODE_COUNT}"
+                    )),
+                );
+            }
+
+            check_tapscript(script)?;
+
+            builder = builder.add_leaf(*depth, script.clone()).map_err(|err| {
+                Error::from(Proto::Error::Error_invalid_taproot_tree).with_context(err.to_string())
+            })?;
+        }
+
+        let spend_info = builder
+            .finalize(&secp256k1::Secp256k1::new(), internal_key)
+            .map_err(|_| {
+                Error::from(Proto::Error::Error_invalid_taproot_tree)
+                    .with_context("the leaves do not form a complete tree")
+            })?;
+
+        Ok(TaprootTree {
+            leaves: leaves.into_iter().map(|(_, script)| script).collect(),
+            spend_info,
+        })
+    }
+    pub fn leaves(&self) -> &[ScriptBuf] {
+        &self.leaves
+    }
+    pub fn spend_info(&self) -> &TaprootSpendInfo {
+        &self.spend_info
+    }
+    /// Returns the control block of the given leaf, if part of the tree.
+    pub fn control_block(&self, leaf: &Script) -> Option<ControlBlock> {
+        self.spend_info
+            .control_block(&(leaf.to_owned(), LeafVersion::TapScript))
+    }
+}
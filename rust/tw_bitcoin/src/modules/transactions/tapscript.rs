@@ -0,0 +1,31 @@
+use crate::{Error, Result};
+use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_CHECKMULTISIGVERIFY};
+use bitcoin::script::{Instruction, Script};
+use tw_proto::BitcoinV2::Proto;
+
+/// Checks the given Taproot leaf script against the basic tapscript rules
+/// (BIP-342): the script must be parsable and must not contain any of the
+/// disabled `OP_CHECKMULTISIG(VERIFY)` opcodes, which make the leaf
+/// unspendable. Multisig leaves use `OP_CHECKSIGADD` instead.
+pub fn check_tapscript(script: &Script) -> Result<()> {
+    for instruction in script.instructions() {
+        let instruction = instruction.map_err(|_| {
+            Error::from(Proto::Error::Error_invalid_tapscript)
+                .with_context("the leaf script is malformed")
+        })?;
+
+        let Instruction::Op(op) = instruction else {
+            continue;
+        };
+
+        if op == OP_CHECKMULTISIG || op == OP_CHECKMULTISIGVERIFY {
+            return Err(
+                Error::from(Proto::Error::Error_invalid_tapscript).with_context(format!(
+                    "{op} is disabled in tapscript, use OP_CHECKSIGADD instead"
+                )),
+            );
+        }
+    }
+
+    Ok(())
+}
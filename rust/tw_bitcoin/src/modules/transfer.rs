@@ -0,0 +1,60 @@
+use crate::aliases::*;
+use crate::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+/// Creates the signing input of a simple transfer to the given (address,
+/// amount) recipients. The inputs are selected automatically from the given
+/// UTXOs and the remainder minus the fee (in sat/vbyte) is returned to the
+/// change address. All addresses must match the given network. The private
+/// key(s) must be set by the caller.
+pub fn build_signing_input<'a>(
+    utxos: Vec<Proto::Input<'a>>,
+    recipients: &[(&'a str, u64)],
+    change_address: &'a str,
+    fee_rate: u64,
+    network: Proto::Network,
+) -> Proto::SigningInput<'a> {
+    let outputs = recipients
+        .iter()
+        .map(|(address, amount)| Proto::Output {
+            value: *amount,
+            to_recipient: ProtoOutputRecipient::from_address((*address).into()),
+            ..Default::default()
+        })
+        .collect();
+
+    Proto::SigningInput {
+        inputs: utxos,
+        outputs,
+        input_selector: UtxoProto::InputSelector::SelectAscending,
+        fee_per_vb: fee_rate,
+        // The value of the change output is calculated automatically.
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: ProtoOutputRecipient::from_address(change_address.into()),
+            ..Default::default()
+        }),
+        disable_change_output: false,
+        network,
+        ..Default::default()
+    }
+}
+
+/// Builds and signs the simple transfer, see [`build_signing_input`]. Errors
+/// are reported via the returned signing output.
+pub fn build_and_sign(
+    utxos: Vec<Proto::Input<'_>>,
+    recipients: &[(&str, u64)],
+    change_address: &str,
+    fee_rate: u64,
+    network: Proto::Network,
+    private_key: &[u8],
+) -> Proto::SigningOutput<'static> {
+    let mut signing = build_signing_input(utxos, recipients, change_address, fee_rate, network);
+    signing.private_key = private_key.into();
+
+    BitcoinEntry.sign(&TestCoinContext::default(), signing)
+}
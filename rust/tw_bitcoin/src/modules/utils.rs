@@ -20,6 +20,9 @@ pub fn hard_clone_proto_input(proto: Proto::Input<'_>) -> Result<Proto::Input<'s
             ProtoInputBuilder::p2pkh(script) => {
                 new_builder(ProtoInputBuilder::p2pkh(script.to_vec().into()))
             },
+            ProtoInputBuilder::p2pk(pubkey) => {
+                new_builder(ProtoInputBuilder::p2pk(pubkey.to_vec().into()))
+            },
             ProtoInputBuilder::p2wsh(script) => {
                 new_builder(ProtoInputBuilder::p2wsh(script.to_vec().into()))
             },
@@ -37,6 +40,7 @@ pub fn hard_clone_proto_input(proto: Proto::Input<'_>) -> Result<Proto::Input<'s
                     one_prevout: script.one_prevout,
                     payload: script.payload.to_vec().into(),
                     control_block: script.control_block.to_vec().into(),
+                    codesep_pos: script.codesep_pos,
                 }),
             ),
             ProtoInputBuilder::brc20_inscribe(brc20) => new_builder(
@@ -53,8 +57,38 @@ pub fn hard_clone_proto_input(proto: Proto::Input<'_>) -> Result<Proto::Input<'s
                     inscribe_to: ord.inscribe_to.to_vec().into(),
                     mime_type: ord.mime_type.to_string().into(),
                     payload: ord.payload.to_vec().into(),
+                    parent: ord.parent.as_ref().map(|parent| Proto::InscriptionId {
+                        txid: parent.txid.to_vec().into(),
+                        index: parent.index,
+                    }),
                 }),
             ),
+            ProtoInputBuilder::cltv_vault(vault) => new_builder(ProtoInputBuilder::cltv_vault(
+                Proto::mod_Input::InputCltvVault {
+                    expiry: vault.expiry,
+                    public_key: vault.public_key.to_vec().into(),
+                },
+            )),
+            ProtoInputBuilder::htlc_refund(refund) => new_builder(ProtoInputBuilder::htlc_refund(
+                Proto::mod_Input::InputHtlcRefund {
+                    payment_hash: refund.payment_hash.to_vec().into(),
+                    recipient_public_key: refund.recipient_public_key.to_vec().into(),
+                    refund_public_key: refund.refund_public_key.to_vec().into(),
+                    expiry: refund.expiry,
+                },
+            )),
+            ProtoInputBuilder::p2tr_pay_to_contract(p2c) => new_builder(
+                ProtoInputBuilder::p2tr_pay_to_contract(hard_clone_pay_to_contract(p2c)),
+            ),
+            ProtoInputBuilder::p2tr_untweaked_key_path(pubkey) => new_builder(
+                ProtoInputBuilder::p2tr_untweaked_key_path(pubkey.to_vec().into()),
+            ),
+            ProtoInputBuilder::auto(auto) => {
+                new_builder(ProtoInputBuilder::auto(Proto::mod_Input::InputAuto {
+                    script_pubkey: auto.script_pubkey.to_vec().into(),
+                    public_key: auto.public_key.to_vec().into(),
+                }))
+            },
             ProtoInputBuilder::None => {
                 return Err(Error::from(Proto::Error::Error_missing_input_builder))
             },
@@ -134,6 +168,9 @@ pub fn hard_clone_proto_output(proto: Proto::Output<'_>) -> Result<Proto::Output
             ProtoOutputBuilder::p2pkh(pubkey_or_hash) => new_builder(ProtoOutputBuilder::p2pkh(
                 new_pubkey_or_hash(pubkey_or_hash)?,
             )),
+            ProtoOutputBuilder::p2pk(pubkey) => {
+                new_builder(ProtoOutputBuilder::p2pk(pubkey.to_vec().into()))
+            },
             ProtoOutputBuilder::p2wsh(script_or_hash) => new_builder(ProtoOutputBuilder::p2wsh(
                 new_script_or_hash(script_or_hash)?,
             )),
@@ -164,8 +201,24 @@ pub fn hard_clone_proto_output(proto: Proto::Output<'_>) -> Result<Proto::Output
                     inscribe_to: ord.inscribe_to.to_vec().into(),
                     mime_type: ord.mime_type.to_string().into(),
                     payload: ord.payload.to_vec().into(),
+                    parent: ord.parent.as_ref().map(|parent| Proto::InscriptionId {
+                        txid: parent.txid.to_vec().into(),
+                        index: parent.index,
+                    }),
                 }),
             ),
+            ProtoOutputBuilder::cltv_vault(vault) => new_builder(ProtoOutputBuilder::cltv_vault(
+                Proto::mod_Output::OutputCltvVault {
+                    expiry: vault.expiry,
+                    public_key: vault.public_key.to_vec().into(),
+                },
+            )),
+            ProtoOutputBuilder::p2tr_pay_to_contract(p2c) => new_builder(
+                ProtoOutputBuilder::p2tr_pay_to_contract(hard_clone_pay_to_contract(p2c)),
+            ),
+            ProtoOutputBuilder::op_return(data) => {
+                new_builder(ProtoOutputBuilder::op_return(data.to_vec().into()))
+            },
             ProtoOutputBuilder::None => {
                 return Err(Error::from(Proto::Error::Error_missing_output_builder))
             },
@@ -184,5 +237,15 @@ pub fn hard_clone_proto_output(proto: Proto::Output<'_>) -> Result<Proto::Output
     Ok(Proto::Output {
         value: proto.value,
         to_recipient,
+        label: proto.label.to_string().into(),
+        dust_threshold: proto.dust_threshold,
     })
 }
+
+fn hard_clone_pay_to_contract(p2c: Proto::PayToContract<'_>) -> Proto::PayToContract<'static> {
+    Proto::PayToContract {
+        public_key: p2c.public_key.to_vec().into(),
+        contract: p2c.contract.to_vec().into(),
+        tweak_order: p2c.tweak_order,
+    }
+}
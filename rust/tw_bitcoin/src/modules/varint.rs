@@ -0,0 +1,40 @@
+/// Encodes the value as LEB128 integer, i.e. little-endian groups of seven
+/// bits, where the high bit of each byte marks a following byte. Used by
+/// runestones, which encode all fields as `u128` values.
+pub fn encode_leb128(mut value: u128) -> Vec<u8> {
+    let mut bytes = vec![];
+
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            bytes.push(byte);
+            return bytes;
+        }
+
+        bytes.push(byte | 0x80);
+    }
+}
+
+/// Decodes the LEB128 integer at the start of the given bytes and returns it
+/// together with the number of bytes read. Returns `None` if the integer is
+/// truncated or does not fit into a `u128`.
+pub fn decode_leb128(bytes: &[u8]) -> Option<(u128, usize)> {
+    let mut value: u128 = 0;
+
+    for (index, byte) in bytes.iter().enumerate() {
+        // A `u128` fits into 19 bytes, the last of which may only use two bits.
+        if index > 18 || (index == 18 && byte & 0b0111_1100 != 0) {
+            return None;
+        }
+
+        value |= u128::from(byte & 0x7f) << (7 * index);
+
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::modules::address::{
+    inspect, multisig_address, p2wpkh_address, script_to_any_address, MultisigWrap, ScriptType,
+};
+use tw_bitcoin::native::{Network, PublicKey, ScriptBuf};
+
+#[test]
+fn address_inspect_p2tr() {
+    let info = inspect("bc1ptmsk7c2yut2xah4pgflpygh2s7fh0cpfkrza9cjj29awapv53mrslgd5cf").unwrap();
+
+    assert_eq!(info.script_type, ScriptType::P2tr);
+    assert_eq!(info.network, Network::Bitcoin);
+    assert_eq!(info.witness_version, Some(1));
+    assert_eq!(info.program.len(), 32);
+}
+
+#[test]
+fn address_inspect_p2pkh() {
+    let info = inspect("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").unwrap();
+
+    assert_eq!(info.script_type, ScriptType::P2pkh);
+    assert_eq!(info.network, Network::Bitcoin);
+    assert_eq!(info.witness_version, None);
+    assert_eq!(info.program.len(), 20);
+}
+
+#[test]
+fn address_inspect_invalid() {
+    assert!(inspect("bc1qinvalid").is_err());
+}
+
+#[test]
+fn address_from_script_p2wpkh() {
+    let pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let pubkey = PublicKey::from_slice(&pubkey).unwrap();
+    let script = ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap());
+
+    let (addr, network) = script_to_any_address(&script).unwrap();
+    assert_eq!(network, Network::Bitcoin);
+    assert!(addr.starts_with("bc1q"));
+    assert_eq!(
+        addr,
+        p2wpkh_address(&pubkey.to_bytes(), Network::Bitcoin).unwrap()
+    );
+
+    // The address decodes back to the same program.
+    let info = inspect(&addr).unwrap();
+    assert_eq!(info.script_type, ScriptType::P2wpkh);
+    assert_eq!(info.program, script.as_bytes()[2..].to_vec());
+}
+
+#[test]
+fn address_from_script_non_standard() {
+    let script = ScriptBuf::from_bytes(vec![0x51]);
+    assert!(script_to_any_address(&script).is_err());
+}
+
+#[test]
+fn multisig_address_2_of_3() {
+    // OP_2 <alice> <bob> <carol> OP_3 OP_CHECKMULTISIG
+    let redeem_script = ScriptBuf::from_bytes(hex("5221028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f21025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f21037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf53ae"));
+
+    let address = |wrap: MultisigWrap, network: Network| {
+        multisig_address(&redeem_script, wrap, network).unwrap()
+    };
+
+    assert_eq!(
+        address(MultisigWrap::P2wsh, Network::Bitcoin),
+        "bc1qt49hzgdngkqsg687xxcqx50c7m26ja35we2hu7et9cptf7uxxrlqxjaf5c"
+    );
+    assert_eq!(
+        address(MultisigWrap::P2wsh, Network::Testnet),
+        "tb1qt49hzgdngkqsg687xxcqx50c7m26ja35we2hu7et9cptf7uxxrlq36txwh"
+    );
+    assert_eq!(
+        address(MultisigWrap::P2sh, Network::Bitcoin),
+        "3Q31vsZhSQMGcwgKVRyuhkPMToYpVxbvdm"
+    );
+    assert_eq!(
+        address(MultisigWrap::P2shP2wsh, Network::Bitcoin),
+        "3FrLmL3tymeFRyXzsk9wfwJSKQQYjmTYSW"
+    );
+
+    let info = inspect(&address(MultisigWrap::P2wsh, Network::Bitcoin)).unwrap();
+    assert_eq!(info.script_type, ScriptType::P2wsh);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::{hex, P2WPKH_TX};
+use tw_bitcoin::modules::anchor::{append_anchor, p2a_script, P2A_DUST_THRESHOLD};
+use tw_bitcoin::modules::standardness::{check_truc, check_truc_child};
+use tw_bitcoin::native::consensus::deserialize;
+use tw_bitcoin::native::Transaction;
+use tw_proto::BitcoinV2::Proto;
+
+#[test]
+fn append_anchor_truc() {
+    let mut tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    tx.version = 3;
+
+    append_anchor(&mut tx).unwrap();
+    assert_eq!(tx.output.len(), 2);
+    assert_eq!(tx.output[1].value, P2A_DUST_THRESHOLD);
+    assert_eq!(
+        tx.output[1].script_pubkey.as_bytes(),
+        hex("51024e73").as_slice()
+    );
+    assert_eq!(tx.output[1].script_pubkey, p2a_script());
+
+    // The transaction passes the static TRUC checks.
+    check_truc(&tx).unwrap();
+    check_truc_child(&tx).unwrap();
+
+    // Only a single anchor output is allowed.
+    let err = append_anchor(&mut tx).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_non_standard_truc
+    );
+
+    let mut with_two_anchors = tx.clone();
+    with_two_anchors.output.push(tx.output[1].clone());
+    assert!(check_truc(&with_two_anchors).is_err());
+}
+
+#[test]
+fn append_anchor_non_truc() {
+    let mut tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    assert_eq!(tx.version, 2);
+
+    let err = append_anchor(&mut tx).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_non_standard_truc
+    );
+    assert!(check_truc(&tx).is_err());
+}
+
+#[test]
+fn truc_child_size_limit() {
+    let mut tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    tx.version = 3;
+
+    // Grow the transaction beyond the child size limit.
+    let output = tx.output[0].clone();
+    tx.output.extend(std::iter::repeat(output).take(40));
+    assert!(tx.vsize() > 1_000);
+
+    check_truc(&tx).unwrap();
+    assert!(check_truc_child(&tx).is_err());
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::anti_fee_sniping::{enable, SEQUENCE_RBF};
+use tw_bitcoin::native::absolute::LockTime;
+use tw_bitcoin::native::consensus::deserialize;
+use tw_bitcoin::native::Transaction;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+fn signing_input() -> Proto::SigningInput<'static> {
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let p2wpkh_utxo = |vout: u32| Proto::Input {
+        txid: txid.clone().into(),
+        vout,
+        value: 50_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 90_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_key: alice_private_key.into(),
+        inputs: vec![p2wpkh_utxo(0), p2wpkh_utxo(1)],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn anti_fee_sniping_with_rbf() {
+    let coin = TestCoinContext::default();
+
+    let mut signing = signing_input();
+    enable(&mut signing, 800_000, 1, SEQUENCE_RBF).unwrap();
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx: Transaction = deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.lock_time, LockTime::from_height(800_000).unwrap());
+
+    // The lock time is enabled by the requested input, which also signals RBF.
+    assert!(!tx.input[0].sequence.enables_absolute_lock_time());
+    assert!(tx.input[1].sequence.enables_absolute_lock_time());
+    assert!(tx.input[1].sequence.is_rbf());
+    assert_eq!(tx.input[1].sequence.0, 0xFFFF_FFFD);
+}
+
+#[test]
+fn anti_fee_sniping_invalid_input() {
+    let mut signing = signing_input();
+
+    // A final sequence would not enable the lock time.
+    let err = enable(&mut signing, 800_000, 0, u32::MAX).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_anti_fee_sniping_input
+    );
+
+    let err = enable(&mut signing, 800_000, 2, SEQUENCE_RBF).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_anti_fee_sniping_input
+    );
+
+    // Nothing was modified.
+    assert_eq!(signing.lock_time, None);
+    assert!(signing.inputs.iter().all(|input| input.sequence == 0));
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use bitcoin::{PublicKey, ScriptBuf};
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn auto_input_p2wpkh() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    // The prevout scriptPubkey of the UTXO.
+    let wpubkey_hash = PublicKey::from_slice(&alice_pubkey)
+        .unwrap()
+        .wpubkey_hash()
+        .unwrap();
+    let prevout_script = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash);
+
+    let signing = |variant: ProtoInputBuilder<'static>| Proto::SigningInput {
+        private_key: alice_private_key.clone().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.clone().into(),
+            vout: 0,
+            value: 10_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value: 9_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.clone().into()),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    // The public key is derived from the private key.
+    let auto = signing(ProtoInputBuilder::auto(Proto::mod_Input::InputAuto {
+        script_pubkey: prevout_script.to_bytes().into(),
+        public_key: Default::default(),
+    }));
+    let signed = BitcoinEntry.sign(&coin, auto);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // Same transaction as with the explicit P2WPKH spend.
+    let explicit = signing(ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()));
+    let expected = BitcoinEntry.sign(&coin, explicit);
+    assert_eq!(expected.error, Proto::Error::OK);
+    assert_eq!(signed.encoded, expected.encoded);
+
+    // The scriptPubkey of a different key can not be spent.
+    let wpubkey_hash = PublicKey::from_slice(&bob_pubkey)
+        .unwrap()
+        .wpubkey_hash()
+        .unwrap();
+    let auto = signing(ProtoInputBuilder::auto(Proto::mod_Input::InputAuto {
+        script_pubkey: ScriptBuf::new_v0_p2wpkh(&wpubkey_hash).to_bytes().into(),
+        public_key: Default::default(),
+    }));
+    let signed = BitcoinEntry.sign(&coin, auto);
+    assert_eq!(signed.error, Proto::Error::Error_unsupported_auto_input);
+}
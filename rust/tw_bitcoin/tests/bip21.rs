@@ -0,0 +1,124 @@
+use tw_bitcoin::modules::bip21::{build, parse, PaymentRequest};
+use tw_proto::BitcoinV2::Proto;
+
+const ADDRESS: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+#[test]
+fn bip21_parse_amount() {
+    let request = parse(&format!("bitcoin:{ADDRESS}?amount=0.001")).unwrap();
+    assert_eq!(
+        request,
+        PaymentRequest {
+            address: ADDRESS.to_string(),
+            amount: Some(100_000),
+            label: None,
+            message: None,
+        }
+    );
+
+    // Amounts are converted exactly, up to eight decimal places.
+    let amount =
+        |amount: &str| parse(&format!("bitcoin:{ADDRESS}?amount={amount}")).map(|r| r.amount);
+    assert_eq!(amount("1").unwrap(), Some(100_000_000));
+    assert_eq!(amount("20.3").unwrap(), Some(2_030_000_000));
+    assert_eq!(amount(".00000001").unwrap(), Some(1));
+    assert_eq!(amount("0.29999999").unwrap(), Some(29_999_999));
+
+    for invalid in [
+        "",
+        ".",
+        "0.000000001",
+        "1e3",
+        "-1",
+        "1,5",
+        "184467440737.09551616",
+    ] {
+        assert_eq!(
+            Proto::Error::from(amount(invalid).unwrap_err()),
+            Proto::Error::Error_invalid_payment_uri
+        );
+    }
+}
+
+#[test]
+fn bip21_parse_label_and_message() {
+    let uri = format!(
+        "BITCOIN:{ADDRESS}?label=Luke-Jr&amount=50&message=Donation%20for%20project%20xyz&foo=bar"
+    );
+    let request = parse(&uri).unwrap();
+    assert_eq!(request.address, ADDRESS);
+    assert_eq!(request.amount, Some(5_000_000_000));
+    assert_eq!(request.label.as_deref(), Some("Luke-Jr"));
+    assert_eq!(request.message.as_deref(), Some("Donation for project xyz"));
+
+    // Without any parameters.
+    let request = parse(&format!("bitcoin:{ADDRESS}")).unwrap();
+    assert_eq!(request.amount, None);
+    assert_eq!(request.label, None);
+}
+
+#[test]
+fn bip21_parse_invalid() {
+    let error = |uri: &str| Proto::Error::from(parse(uri).unwrap_err());
+
+    assert_eq!(
+        error("bitcoin:bc1qinvalid?amount=1"),
+        Proto::Error::Error_bad_address_recipient
+    );
+    assert_eq!(
+        error(&format!("litecoin:{ADDRESS}")),
+        Proto::Error::Error_invalid_payment_uri
+    );
+    assert_eq!(error(ADDRESS), Proto::Error::Error_invalid_payment_uri);
+
+    // Required parameters that are not understood.
+    assert_eq!(
+        error(&format!(
+            "bitcoin:{ADDRESS}?req-somethingyoudontunderstand=50"
+        )),
+        Proto::Error::Error_invalid_payment_uri
+    );
+    assert_eq!(
+        error(&format!("bitcoin:{ADDRESS}?amount=1&amount=2")),
+        Proto::Error::Error_invalid_payment_uri
+    );
+    assert_eq!(
+        error(&format!("bitcoin:{ADDRESS}?label=%zz")),
+        Proto::Error::Error_invalid_payment_uri
+    );
+}
+
+#[test]
+fn bip21_build_round_trip() {
+    let uri = build(ADDRESS, 100_000, Some("Alice & Bob"), None);
+    assert_eq!(
+        uri,
+        format!("bitcoin:{ADDRESS}?amount=0.001&label=Alice%20%26%20Bob")
+    );
+
+    let request = parse(&uri).unwrap();
+    assert_eq!(
+        request,
+        PaymentRequest {
+            address: ADDRESS.to_string(),
+            amount: Some(100_000),
+            label: Some("Alice & Bob".to_string()),
+            message: None,
+        }
+    );
+
+    // Whole and fractional amounts, a non-ASCII message.
+    let uri = build(ADDRESS, 2_030_000_001, None, Some("Zürich?=100%"));
+    assert_eq!(
+        uri,
+        format!("bitcoin:{ADDRESS}?amount=20.30000001&message=Z%C3%BCrich%3F%3D100%25")
+    );
+    let request = parse(&uri).unwrap();
+    assert_eq!(request.amount, Some(2_030_000_001));
+    assert_eq!(request.message.as_deref(), Some("Zürich?=100%"));
+
+    assert_eq!(
+        build(ADDRESS, 100_000_000, None, None),
+        format!("bitcoin:{ADDRESS}?amount=1")
+    );
+}
@@ -42,6 +42,7 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
                 },
             ),
         }),
+        ..Default::default()
     };
 
     // Change/return transaction.
@@ -52,6 +53,7 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -106,6 +108,7 @@ fn coin_entry_sign_brc20_commit_reveal_transfer() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
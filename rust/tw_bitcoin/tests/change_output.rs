@@ -0,0 +1,203 @@
+mod common;
+
+use common::{hex, ONE_BTC};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const SAT_VB: u64 = 10;
+const SEND_AMOUNT: u64 = ONE_BTC;
+
+// Convenience function, creates a P2WPKH -> P2WPKH signing input with a change
+// output (back to Alice).
+fn signing_input(input_value: u64, min_change: u64) -> Proto::SigningInput<'static> {
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: input_value,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: SEND_AMOUNT,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let change_output = Proto::Output {
+        // Will be set by the library.
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_key: alice_private_key.into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: SAT_VB,
+        change_output: Some(change_output),
+        disable_change_output: false,
+        min_change,
+        ..Default::default()
+    }
+}
+
+// Convenience function, returns the fee estimate of the transaction with a
+// change output. Note that the fee estimate is independent of the input value.
+fn fee_estimate() -> u64 {
+    let coin = TestCoinContext::default();
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing_input(2 * ONE_BTC, 0));
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    presigned.fee_estimate
+}
+
+#[test]
+fn sign_with_change_output() {
+    let coin = TestCoinContext::default();
+    let fee = fee_estimate();
+
+    let signed = BitcoinEntry.sign(&coin, signing_input(SEND_AMOUNT + fee + 500, 0));
+    assert_eq!(signed.error, Proto::Error::OK);
+    assert_eq!(signed.donated_change, 0);
+
+    // The change output was created.
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.outputs.len(), 2);
+    assert_eq!(transaction.outputs[0].value, SEND_AMOUNT);
+    assert_eq!(transaction.outputs[1].value, 500);
+}
+
+#[test]
+fn sign_with_change_below_min_change() {
+    let coin = TestCoinContext::default();
+    let fee = fee_estimate();
+
+    // The computed change (500) is below the minimum change (1000).
+    let signing = signing_input(SEND_AMOUNT + fee + 500, 1_000);
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_outputs.len(), 1);
+    assert_eq!(presigned.donated_change, 500);
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    assert_eq!(signed.donated_change, 500);
+
+    // The change output was dropped, only the recipient remains.
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.outputs.len(), 1);
+    assert_eq!(transaction.outputs[0].value, SEND_AMOUNT);
+}
+
+#[test]
+fn sign_with_min_change_amount_selects_another_input() {
+    let coin = TestCoinContext::default();
+    let fee = fee_estimate();
+
+    // A near-total spend: the first input leaves a change of 500 only.
+    let mut signing = signing_input(SEND_AMOUNT + fee + 500, 0);
+    let mut tx2 = signing.inputs[0].clone();
+    tx2.vout = 1;
+    tx2.value = 100_000;
+    signing.inputs.push(tx2);
+    signing.input_selector = UtxoProto::InputSelector::SelectInOrder;
+
+    // Without a minimum, the first input covers the payment.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_inputs.len(), 1);
+    assert_eq!(presigned.utxo_outputs[1].value, 500);
+
+    // The minimum change amount pulls in the second input.
+    signing.min_change_amount = 10_000;
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_inputs.len(), 2);
+
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 2);
+    assert_eq!(transaction.outputs.len(), 2);
+    assert_eq!(transaction.outputs[0].value, SEND_AMOUNT);
+
+    let change = transaction.outputs[1].value;
+    assert!(change >= 10_000);
+    assert_eq!(change, fee + 500 + 100_000 - presigned.fee_estimate);
+
+    // There is no further input to raise the change.
+    signing.min_change_amount = 200_000;
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(
+        presigned.error,
+        Proto::Error::Error_utxo_min_change_not_reached
+    );
+}
+
+#[test]
+fn sign_with_min_change_amount_skips_uneconomic_inputs() {
+    let coin = TestCoinContext::default();
+    let fee = fee_estimate();
+
+    // A near-total spend, followed by an input whose value does not cover the
+    // fee of spending it (68 vbytes at 10 sat/vbyte) and a regular one.
+    let mut signing = signing_input(SEND_AMOUNT + fee + 500, 0);
+    let mut tx2 = signing.inputs[0].clone();
+    tx2.vout = 1;
+    tx2.value = 500;
+    let mut tx3 = signing.inputs[0].clone();
+    tx3.vout = 2;
+    tx3.value = 100_000;
+    signing.inputs.extend([tx2, tx3]);
+    signing.input_selector = UtxoProto::InputSelector::SelectInOrder;
+    signing.min_change_amount = 10_000;
+
+    // The uneconomic input is skipped.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_inputs.len(), 2);
+    assert_eq!(presigned.utxo_inputs[1].vout, 2);
+    assert!(presigned.utxo_outputs[1].value >= 10_000);
+
+    // Without the regular input, the minimum change can not be reached,
+    // although the first input alone covers the payment.
+    signing.inputs.truncate(2);
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(
+        presigned.error,
+        Proto::Error::Error_utxo_min_change_not_reached
+    );
+}
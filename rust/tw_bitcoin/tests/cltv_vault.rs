@@ -0,0 +1,170 @@
+mod common;
+
+use common::{hex, MINER_FEE, ONE_BTC};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::CltvVault;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const EXPIRY: u32 = 800_000;
+
+// Convenience function, creates a signing input that spends Alice's CLTV
+// vault with the given transaction lock time (block height).
+fn vault_spending_input(lock_time: u32) -> Proto::SigningInput<'static> {
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: ONE_BTC,
+        // The lock time is only enforced for non-final sequences.
+        sequence: u32::MAX - 1,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::cltv_vault(Proto::mod_Input::InputCltvVault {
+                expiry: EXPIRY,
+                public_key: alice_pubkey.clone().into(),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_key: alice_private_key.into(),
+        lock_time: Some(UtxoProto::LockTime {
+            variant: UtxoProto::mod_LockTime::OneOfvariant::blocks(lock_time),
+        }),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn cltv_vault_output() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::cltv_vault(Proto::mod_Output::OutputCltvVault {
+                expiry: EXPIRY,
+                public_key: alice_pubkey.clone().into(),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // The vault is locked in a P2WSH output.
+    let pubkey = tw_bitcoin::native::PublicKey::from_slice(&alice_pubkey).unwrap();
+    let vault = CltvVault::new(EXPIRY, pubkey);
+    let expected =
+        tw_bitcoin::native::ScriptBuf::new_v0_p2wsh(&vault.witness_script().wscript_hash());
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(
+        transaction.outputs[0].script_pubkey.as_ref(),
+        expected.as_bytes()
+    );
+}
+
+#[test]
+fn cltv_vault_spend_before_expiry() {
+    let coin = TestCoinContext::default();
+
+    let signed = BitcoinEntry.sign(&coin, vault_spending_input(EXPIRY - 1));
+    assert_eq!(
+        signed.error,
+        Proto::Error::Error_cltv_vault_lock_time_not_reached
+    );
+
+    // The check also applies to external signers.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, vault_spending_input(EXPIRY - 1));
+    assert_eq!(
+        presigned.error,
+        Proto::Error::Error_cltv_vault_lock_time_not_reached
+    );
+}
+
+#[test]
+fn cltv_vault_spend_after_expiry() {
+    let coin = TestCoinContext::default();
+
+    let signed = BitcoinEntry.sign(&coin, vault_spending_input(EXPIRY));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // The witness contains the signature and the witness script.
+    let transaction = signed.transaction.unwrap();
+    let witness = &transaction.inputs[0].witness_items;
+    assert_eq!(witness.len(), 2);
+
+    let pubkey = tw_bitcoin::native::PublicKey::from_slice(&hex(
+        "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f",
+    ))
+    .unwrap();
+    let vault = CltvVault::new(EXPIRY, pubkey);
+    assert_eq!(witness[1].as_ref(), vault.witness_script().as_bytes());
+}
+
+#[test]
+fn cltv_vault_spend_with_final_sequence() {
+    let coin = TestCoinContext::default();
+
+    let mut signing = vault_spending_input(EXPIRY);
+    signing.inputs[0].sequence = u32::MAX;
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::Error_cltv_vault_final_sequence);
+}
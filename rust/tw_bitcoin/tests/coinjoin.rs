@@ -0,0 +1,115 @@
+mod common;
+
+use common::{hex, p2wpkh_utxo};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::coinjoin::{build, check_denomination_outputs, Participant};
+use tw_bitcoin::native::{PublicKey, ScriptBuf};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+const DENOMINATION: u64 = 100_000;
+const FEE_RATE: u64 = 5;
+
+fn p2wpkh_script(pubkey: &[u8]) -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(pubkey).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+// Convenience function, the participants Alice (one UTXO) and Bob (two
+// UTXOs), each mixing into a fresh output.
+fn participants() -> Vec<Participant<'static>> {
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let mixed_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let other_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    vec![
+        Participant {
+            inputs: vec![p2wpkh_utxo(&alice_pubkey, 2, 150_000)],
+            output_script: p2wpkh_script(&mixed_pubkey),
+            change_script: p2wpkh_script(&alice_pubkey),
+        },
+        Participant {
+            inputs: vec![
+                p2wpkh_utxo(&bob_pubkey, 0, 60_000),
+                p2wpkh_utxo(&bob_pubkey, 1, 70_000),
+            ],
+            output_script: p2wpkh_script(&other_pubkey),
+            change_script: p2wpkh_script(&bob_pubkey),
+        },
+    ]
+}
+
+#[test]
+fn coinjoin_two_participants() {
+    let coin = TestCoinContext::default();
+
+    let signing = build(participants(), DENOMINATION, FEE_RATE).unwrap();
+    assert_eq!(signing.inputs.len(), 3);
+    assert_eq!(signing.outputs.len(), 4);
+
+    // Two equal denomination outputs, sorted after the (smaller) change
+    // outputs.
+    assert_eq!(
+        check_denomination_outputs(&signing.outputs, DENOMINATION, 2).unwrap(),
+        vec![2, 3]
+    );
+
+    // Each participant pays for its inputs (41 bytes + 27 vbytes witness
+    // estimate), its two P2WPKH outputs (31 bytes each) and half of the
+    // overhead (11 vbytes), rounded up.
+    let alice_fee = (68 + 2 * 31) * FEE_RATE + 28;
+    let bob_fee = (2 * 68 + 2 * 31) * FEE_RATE + 28;
+    assert_eq!(signing.outputs[0].value, 130_000 - DENOMINATION - bob_fee);
+    assert_eq!(signing.outputs[1].value, 150_000 - DENOMINATION - alice_fee);
+
+    // The skeleton covers the fee of the transaction.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_outputs.len(), 4);
+    assert!(presigned.fee_estimate <= alice_fee + bob_fee);
+}
+
+#[test]
+fn coinjoin_order_does_not_follow_participants() {
+    let alice = participants().remove(0);
+    let bob = participants().remove(1);
+
+    let signing = build(participants(), DENOMINATION, FEE_RATE).unwrap();
+
+    // The inputs are sorted by vout (same txid), Bob's inputs come first.
+    let vouts: Vec<u32> = signing.inputs.iter().map(|input| input.vout).collect();
+    assert_eq!(vouts, vec![0, 1, 2]);
+    assert_eq!(signing.inputs[0].value, bob.inputs[0].value);
+
+    // The outputs are sorted by value and scriptPubkey: Bob's change, Alice's
+    // change, then Bob's denomination output (lower scriptPubkey) before
+    // Alice's.
+    let script = |output: &Proto::Output| match &output.to_recipient {
+        ProtoOutputRecipient::custom_script_pubkey(script) => script.to_vec(),
+        _ => panic!("expected a custom scriptPubkey"),
+    };
+    assert_eq!(script(&signing.outputs[0]), bob.change_script.to_bytes());
+    assert_eq!(script(&signing.outputs[1]), alice.change_script.to_bytes());
+    assert_eq!(script(&signing.outputs[2]), bob.output_script.to_bytes());
+    assert_eq!(script(&signing.outputs[3]), alice.output_script.to_bytes());
+
+    // Swapping the participants results in the same transaction.
+    let swapped = build(vec![bob, alice], DENOMINATION, FEE_RATE).unwrap();
+    assert_eq!(swapped.inputs, signing.inputs);
+    assert_eq!(swapped.outputs, signing.outputs);
+}
+
+#[test]
+fn coinjoin_insufficient_inputs() {
+    let mut participants = participants();
+    participants[1].inputs.pop();
+
+    let err = build(participants, DENOMINATION, FEE_RATE).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::modules::commitment::build_output;
+use tw_bitcoin::modules::transactions::OutputBuilder;
+
+#[test]
+fn commitment_output_script() {
+    // SHA256 of "hello world".
+    let commitment: [u8; 32] =
+        hex("b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9")
+            .try_into()
+            .unwrap();
+
+    let output = build_output(commitment);
+    assert_eq!(output.value, 0);
+
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+
+    // OP_RETURN OP_PUSHBYTES_32 <commitment>
+    let mut expected = vec![0x6a, 0x20];
+    expected.extend_from_slice(&commitment);
+
+    assert_eq!(utxo.script_pubkey.len(), 34);
+    assert_eq!(utxo.script_pubkey.as_ref(), expected.as_slice());
+}
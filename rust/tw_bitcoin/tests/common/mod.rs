@@ -4,9 +4,36 @@
 
 pub mod data;
 
+use tw_bitcoin::aliases::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
 pub const ONE_BTC: u64 = 100_000_000;
 pub const MINER_FEE: u64 = 1_000_000;
 
+// A P2WPKH transaction with one input and one output (see `p2wpkh.rs`).
+pub const P2WPKH_TX: &str = "020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237302483045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a60121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f00000000";
+
 pub fn hex(string: &str) -> Vec<u8> {
     tw_encoding::hex::decode(string).unwrap()
 }
+
+// Convenience function, creates a P2WPKH UTXO of the given public key, spending
+// the given output of a fixed transaction.
+pub fn p2wpkh_utxo(pubkey: &[u8], vout: u32, value: u64) -> Proto::Input<'static> {
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    Proto::Input {
+        txid: txid.into(),
+        vout,
+        value,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(pubkey.to_vec().into()),
+        }),
+        ..Default::default()
+    }
+}
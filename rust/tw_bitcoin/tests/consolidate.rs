@@ -0,0 +1,121 @@
+mod common;
+
+use common::{hex, p2wpkh_utxo};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::consolidate::{
+    analyze, build_signing_input, build_signing_input_excluding,
+};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+// Convenience function, creates the given number of P2WPKH UTXOs with the
+// given value each.
+fn p2wpkh_utxos(count: u32, value: u64) -> Vec<Proto::Input<'static>> {
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    (0..count)
+        .map(|vout| p2wpkh_utxo(&alice_pubkey, vout, value))
+        .collect()
+}
+
+#[test]
+fn consolidate_many_tiny_utxos() {
+    let utxos = p2wpkh_utxos(50, 5_000);
+
+    // Low fee rate now, high fee rate in the future.
+    let report = analyze(&utxos, 2, 50).unwrap();
+    assert_eq!(report.consolidation_fee, 3_442 * 2);
+    assert_eq!(report.future_savings, (3_400 - 68) * 50);
+    assert!(report.net_savings > 0);
+    assert!(report.recommended);
+}
+
+#[test]
+fn consolidate_few_utxos() {
+    let utxos = p2wpkh_utxos(2, 5_000);
+
+    let report = analyze(&utxos, 10, 10).unwrap();
+    assert_eq!(report.future_savings, 68 * 10);
+    assert!(report.net_savings < 0);
+    assert!(!report.recommended);
+
+    // A single UTXO can not be consolidated any further.
+    let report = analyze(&p2wpkh_utxos(1, 5_000), 2, 50).unwrap();
+    assert_eq!(report.future_savings, 0);
+    assert!(!report.recommended);
+}
+
+#[test]
+fn consolidate_at_lower_fee_rate() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let destination = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let sign = |fee_rate: u64| {
+        let mut signing =
+            build_signing_input(p2wpkh_utxos(10, 5_000), destination.clone(), fee_rate);
+        signing.private_key = alice_private_key.as_slice().into();
+
+        let signed = BitcoinEntry.sign(&coin, signing);
+        assert_eq!(signed.error, Proto::Error::OK);
+        signed
+    };
+
+    // Consolidation rate versus the default send rate of the wallet.
+    let consolidated = sign(2);
+    let default_send = sign(20);
+
+    // All UTXOs are consolidated into a single output.
+    let tx = consolidated.transaction.as_ref().unwrap();
+    assert_eq!(tx.inputs.len(), 10);
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].value, 50_000 - consolidated.effective_fee);
+
+    assert!(consolidated.effective_fee * 5 < default_send.effective_fee);
+}
+
+#[test]
+fn consolidate_excluding_inscribed_utxo() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let destination = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // The UTXO at index 2 carries an inscription.
+    let utxos = p2wpkh_utxos(5, 5_000);
+    let mut signing = build_signing_input_excluding(utxos, &[2], destination, 2);
+    signing.private_key = alice_private_key.as_slice().into();
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // The remaining UTXOs are consolidated into a single output.
+    let tx = signed.transaction.as_ref().unwrap();
+    assert_eq!(tx.inputs.len(), 4);
+    assert!(tx.inputs.iter().all(|input| input.vout != 2));
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].value, 20_000 - signed.effective_fee);
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::{ScriptBuf, Transaction, Witness};
+use common::{hex, P2WPKH_TX};
+use tw_bitcoin::modules::decode::{
+    check_ecdsa_signature, decode_transaction, FlaggedInput, SignatureIssue, TaprootInput,
+    TaprootSpend, TaprootSpendPath,
+};
+use tw_proto::BitcoinV2::Proto;
+
+// The `P2WPKH_TX` transaction (low-S signature), but with the S value of the
+// signature replaced by `n - S`, which is equally valid but non-standard.
+const P2WPKH_TX_HIGH_S: &str = "020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237302493046022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd022100f1bd360738f83640f3154b08c61372967ceceaf4112f45ae161646574b601c9b0121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f00000000";
+
+#[test]
+fn decode_strict_low_s() {
+    let decoded = decode_transaction(&hex(P2WPKH_TX), true).unwrap();
+    assert_eq!(decoded.transaction.input.len(), 1);
+    assert!(decoded.flagged_inputs.is_empty());
+}
+
+#[test]
+fn decode_strict_high_s() {
+    let decoded = decode_transaction(&hex(P2WPKH_TX_HIGH_S), true).unwrap();
+    assert_eq!(
+        decoded.flagged_inputs,
+        vec![FlaggedInput {
+            index: 0,
+            issue: SignatureIssue::HighS,
+        }]
+    );
+
+    // Not checked by default.
+    let decoded = decode_transaction(&hex(P2WPKH_TX_HIGH_S), false).unwrap();
+    assert!(decoded.flagged_inputs.is_empty());
+}
+
+#[test]
+fn decode_non_strict_der() {
+    // Low-S signature with an excessively padded R value.
+    let sig =
+        hex("302702220000000000000000000000000000000000000000000000000000000000000000000102010101");
+    assert_eq!(
+        check_ecdsa_signature(&sig),
+        Some(SignatureIssue::NonStrictDer)
+    );
+
+    // The original signature is fine.
+    let sig = hex("3045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a601");
+    assert_eq!(check_ecdsa_signature(&sig), None);
+}
+
+#[test]
+fn decode_malformed() {
+    let mut tx = hex(P2WPKH_TX);
+    tx.truncate(tx.len() - 4);
+
+    let err = decode_transaction(&tx, false).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_malformed_transaction
+    );
+}
+
+#[test]
+fn decode_truncated_reports_offset() {
+    // Truncated within the signature of the witness.
+    let tx = hex(P2WPKH_TX)[..150].to_vec();
+
+    let err = decode_transaction(&tx, false).unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains("unexpected end of data at byte offset 150"),
+        "{message}"
+    );
+
+    // Trailing bytes after the lock time.
+    let mut tx = hex(P2WPKH_TX);
+    tx.extend([0x00, 0x00]);
+
+    let err = decode_transaction(&tx, false).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("2 trailing bytes at byte offset 192"));
+}
+
+#[test]
+fn decode_witness_stack_count_mismatch() {
+    // Spend two inputs, where only the first one provides a witness.
+    let mut tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    let mut second = tx.input[0].clone();
+    second.previous_output.vout = 1;
+    second.witness = Witness::new();
+    tx.input.push(second);
+
+    let encoded = serialize(&tx);
+    assert!(decode_transaction(&encoded, false).is_ok());
+
+    // Drop the empty witness stack of the second input, which is located in
+    // front of the lock time.
+    let mut malformed = encoded.clone();
+    assert_eq!(malformed.remove(encoded.len() - 5), 0x00);
+
+    let err = decode_transaction(&malformed, false).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("SegWit transaction provides 1 witness stacks for 2 inputs"));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_malformed_transaction
+    );
+}
+
+#[test]
+fn decode_unknown_witness_version_round_trip() {
+    // Pay to a witness v3 program (`OP_3 OP_PUSHBYTES_32 <program>`), which
+    // has no meaning yet.
+    let mut tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    let mut script = vec![0x53, 0x20];
+    script.extend_from_slice(&[0xab; 32]);
+    tx.output[0].script_pubkey = ScriptBuf::from_bytes(script.clone());
+
+    let encoded = serialize(&tx);
+
+    let decoded = decode_transaction(&encoded, true).unwrap();
+    let output = &decoded.transaction.output[0];
+    assert_eq!(output.script_pubkey.as_bytes(), script.as_slice());
+    assert_eq!(output.script_pubkey.witness_version().unwrap().to_num(), 3);
+
+    // The re-encoded transaction is identical.
+    assert_eq!(serialize(&decoded.transaction), encoded);
+}
+
+// The x-only public key of the secp256k1 generator point.
+const INTERNAL_KEY: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+// Replaces the witness of the P2WPKH transaction and decodes it.
+fn decode_with_witness(items: Vec<Vec<u8>>) -> Option<TaprootSpend> {
+    let mut tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    tx.input[0].witness = Witness::from_slice(&items);
+
+    let decoded = decode_transaction(&serialize(&tx), false).unwrap();
+    decoded
+        .taproot_inputs
+        .into_iter()
+        .map(|input| input.spend)
+        .next()
+}
+
+#[test]
+fn decode_taproot_key_path() {
+    let spend = decode_with_witness(vec![vec![0xab; 64]]).unwrap();
+    assert_eq!(spend.path, TaprootSpendPath::KeyPath);
+    assert!(!spend.has_annex);
+    assert_eq!(spend.spend_type(), 0x00);
+
+    // With a sighash type and an annex.
+    let spend = decode_with_witness(vec![vec![0xab; 65], vec![0x50, 0x01]]).unwrap();
+    assert_eq!(spend.path, TaprootSpendPath::KeyPath);
+    assert_eq!(spend.spend_type(), 0x01);
+}
+
+#[test]
+fn decode_taproot_script_path() {
+    // Tapscript control block with a single Merkle proof node.
+    let mut control_block = vec![0xc1];
+    control_block.extend(hex(INTERNAL_KEY));
+    control_block.extend([0xcd; 32]);
+
+    let leaf_script = vec![0x51];
+    let spend = decode_with_witness(vec![leaf_script, control_block]).unwrap();
+
+    assert_eq!(
+        spend.path,
+        TaprootSpendPath::ScriptPath {
+            leaf_version: 0xc0,
+            depth: 1,
+        }
+    );
+    assert!(!spend.has_annex);
+    assert_eq!(spend.spend_type(), 0x02);
+    assert_eq!(TaprootSpend::SIGHASH_EPOCH, 0x00);
+}
+
+#[test]
+fn decode_non_taproot_inputs() {
+    // The P2WPKH signature and public key are not mistaken as a script-path
+    // spend with an empty Merkle path.
+    let decoded = decode_transaction(&hex(P2WPKH_TX), false).unwrap();
+    assert!(decoded.taproot_inputs.is_empty());
+
+    // Neither is a truncated control block.
+    let mut control_block = vec![0xc0];
+    control_block.extend(hex(INTERNAL_KEY));
+    control_block.extend([0xcd; 16]);
+    assert_eq!(decode_with_witness(vec![vec![0x51], control_block]), None);
+}
+
+#[test]
+fn decode_taproot_input_index() {
+    let mut tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    let mut taproot = tx.input[0].clone();
+    taproot.witness = Witness::from_slice(&[vec![0xab; 64]]);
+    tx.input.push(taproot);
+
+    let decoded = decode_transaction(&serialize(&tx), false).unwrap();
+    assert_eq!(
+        decoded.taproot_inputs,
+        vec![TaprootInput {
+            index: 1,
+            spend: TaprootSpend {
+                path: TaprootSpendPath::KeyPath,
+                has_annex: false,
+            },
+        }]
+    );
+}
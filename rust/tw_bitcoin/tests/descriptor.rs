@@ -0,0 +1,83 @@
+mod common;
+
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::modules::descriptor::{build_output_to_descriptor, checksum, Descriptor};
+use tw_proto::BitcoinV2::Proto;
+
+// The BIP-84 account key of the "abandon ... about" test mnemonic.
+const XPUB: &str = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V";
+
+#[test]
+fn descriptor_ranged_wpkh_output() {
+    let descriptor = format!("wpkh({XPUB}/0/*)");
+    assert!(Descriptor::from_str(&descriptor).unwrap().is_ranged());
+
+    // bc1qnpzzqjzet8gd5gl8l6gzhuc4s9xv0djt0rlu7a (m/84'/0'/0'/0/5)
+    let output = build_output_to_descriptor(&descriptor, 5, 50_000).unwrap();
+    assert_eq!(output.value, 50_000);
+    assert_eq!(
+        output.to_recipient,
+        ProtoOutputRecipient::custom_script_pubkey(
+            hex("0014984420485959d0da23e7fe902bf315814cc7b64b").into()
+        )
+    );
+
+    // bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu (m/84'/0'/0'/0/0), with the
+    // key origin and checksum.
+    let descriptor = format!("wpkh([73c5da0a/84'/0'/0']{XPUB}/0/*)#wc3n3van");
+    let script_pubkey = Descriptor::from_str(&descriptor)
+        .unwrap()
+        .script_pubkey(0)
+        .unwrap();
+    assert_eq!(
+        script_pubkey.as_bytes(),
+        hex("0014c0cebcd6c3d3ca8c75dc5ec62ebe55330ef910e2")
+    );
+}
+
+#[test]
+fn descriptor_single_key() {
+    // BIP-381 test vectors.
+    let descriptor = "pkh(02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5)";
+    assert_eq!(checksum(descriptor).unwrap(), "8fhd9pwu");
+
+    let parsed = Descriptor::from_str(descriptor).unwrap();
+    assert!(!parsed.is_ranged());
+    assert_eq!(
+        parsed.script_pubkey(0).unwrap().as_bytes(),
+        hex("76a91406afd46bcdfd22ef94ac122aa11f241244a37ecc88ac")
+    );
+
+    let descriptor = "wpkh(02f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9)";
+    assert_eq!(
+        Descriptor::from_str(descriptor)
+            .unwrap()
+            .script_pubkey(0)
+            .unwrap()
+            .as_bytes(),
+        hex("00147dd65592d0ab2fe0d0257d571abf032cd9db93dc")
+    );
+}
+
+#[test]
+fn descriptor_invalid() {
+    let invalid = [
+        // Checksum mismatch.
+        format!("wpkh({XPUB}/0/*)#wc3n3vaq"),
+        // Hardened derivation.
+        format!("wpkh({XPUB}/0'/*)"),
+        format!("wpkh({XPUB}/0/*')"),
+        // Unsupported script type.
+        format!("wsh(pk({XPUB}/0/*))"),
+    ];
+
+    for descriptor in invalid {
+        let err = build_output_to_descriptor(&descriptor, 5, 50_000).unwrap_err();
+        assert_eq!(
+            Proto::Error::from(err),
+            Proto::Error::Error_invalid_descriptor
+        );
+    }
+}
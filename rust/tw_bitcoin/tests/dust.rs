@@ -0,0 +1,152 @@
+mod common;
+
+use bitcoin::ScriptBuf;
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::anchor::{p2a_script, P2A_DUST_THRESHOLD};
+use tw_bitcoin::modules::standardness::{dust_threshold, DEFAULT_DUST_RELAY_FEERATE};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn dust_threshold_per_output() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let output = |value: u64, dust_threshold: u64| Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.clone().into()),
+            }),
+        }),
+        dust_threshold,
+        ..Default::default()
+    };
+
+    let signing = |outputs: Vec<Proto::Output<'static>>| Proto::SigningInput {
+        private_key: alice_private_key.clone().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.clone().into(),
+            vout: 0,
+            value: 10_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+            }),
+            ..Default::default()
+        }],
+        outputs,
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        dust_threshold: 546,
+        ..Default::default()
+    };
+
+    // The custom threshold of the first output accepts 150 sats, the global
+    // threshold applies to the second output.
+    let signed = BitcoinEntry.sign(&coin, signing(vec![output(150, 100), output(1_000, 0)]));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed = BitcoinEntry.sign(&coin, signing(vec![output(150, 100), output(150, 0)]));
+    assert_eq!(signed.error, Proto::Error::Error_dust_output);
+    assert!(signed
+        .error_message
+        .contains("output 1 of 150 sats is below the dust threshold of 546 sats"));
+
+    // The custom threshold can be stricter too.
+    let signed = BitcoinEntry.sign(&coin, signing(vec![output(600, 1_000)]));
+    assert_eq!(signed.error, Proto::Error::Error_dust_output);
+}
+
+#[test]
+fn dust_threshold_from_relay_feerate() {
+    let script = |hex_str: &str| ScriptBuf::from_bytes(hex(hex_str));
+    let threshold = |script: &ScriptBuf| dust_threshold(script, DEFAULT_DUST_RELAY_FEERATE);
+
+    let p2pkh = script("76a91479091972186c449eb1ded22b78e40d009bdf008988ac");
+    let p2sh = script("a91479091972186c449eb1ded22b78e40d009bdf008987");
+    let p2wpkh = script("001479091972186c449eb1ded22b78e40d009bdf0089");
+    let p2wsh = script("00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262");
+    let p2tr = script("51201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262");
+
+    // The well-known thresholds of Bitcoin Core at 3000 sat/kvB.
+    assert_eq!(threshold(&p2pkh), 546);
+    assert_eq!(threshold(&p2sh), 540);
+    assert_eq!(threshold(&p2wpkh), 294);
+    assert_eq!(threshold(&p2wsh), 330);
+    assert_eq!(threshold(&p2tr), 330);
+    assert_eq!(threshold(&p2a_script()), P2A_DUST_THRESHOLD);
+    assert_eq!(threshold(&script("6a0568656c6c6f")), 0);
+
+    // The threshold scales with the relay fee rate.
+    assert_eq!(dust_threshold(&p2wpkh, 1_000), 98);
+    assert_eq!(dust_threshold(&p2wpkh, 0), 0);
+}
+
+#[test]
+fn dust_relay_feerate_rejects_outputs() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let signing = |value: u64, dust_relay_feerate: u64| Proto::SigningInput {
+        private_key: alice_private_key.clone().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.clone().into(),
+            vout: 0,
+            value: 10_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+            }),
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.clone().into()),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        dust_relay_feerate,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing(294, DEFAULT_DUST_RELAY_FEERATE));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed = BitcoinEntry.sign(&coin, signing(293, DEFAULT_DUST_RELAY_FEERATE));
+    assert_eq!(signed.error, Proto::Error::Error_dust_output);
+    assert!(signed
+        .error_message
+        .contains("output 0 of 293 sats is below the dust threshold of 294 sats"));
+
+    // Disabled if unset, for backward compatibility.
+    let signed = BitcoinEntry.sign(&coin, signing(293, 0));
+    assert_eq!(signed.error, Proto::Error::OK);
+    let signed = BitcoinEntry.sign(&coin, signing(1, 0));
+    assert_eq!(signed.error, Proto::Error::OK);
+}
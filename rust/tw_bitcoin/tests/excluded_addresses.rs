@@ -0,0 +1,95 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::address::p2wpkh_address;
+use tw_bitcoin::native::Network;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn excluded_address_is_never_selected() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    // The cold-storage key.
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let p2wpkh_utxo = |vout: u32, value: u64, pubkey: &[u8]| Proto::Input {
+        txid: txid.as_slice().into(),
+        vout,
+        value,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(pubkey.to_vec().into()),
+        }),
+        ..Default::default()
+    };
+
+    let alice_output = |value: u64| Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.clone().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // The smallest UTXO, which would be selected first, belongs to the cold
+    // storage.
+    let mut signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![
+            p2wpkh_utxo(0, 20_000, &bob_pubkey),
+            p2wpkh_utxo(1, 80_000, &alice_pubkey),
+            p2wpkh_utxo(2, 30_000, &bob_pubkey),
+        ],
+        outputs: vec![alice_output(10_000)],
+        input_selector: UtxoProto::InputSelector::SelectAscending,
+        fee_per_vb: 10,
+        change_output: Some(alice_output(0)),
+        disable_change_output: false,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_inputs[0].vout, 0);
+
+    // Exclude the cold-storage address.
+    let cold_address = p2wpkh_address(&bob_pubkey, Network::Bitcoin).unwrap();
+    signing.excluded_addresses = vec![cold_address.into()];
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_inputs.len(), 1);
+    assert_eq!(presigned.utxo_inputs[0].vout, 1);
+
+    // Signing only spends the hot wallet's UTXO.
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx = signed.transaction.unwrap();
+    assert_eq!(tx.inputs.len(), 1);
+    assert_eq!(tx.inputs[0].vout, 1);
+
+    // The cold-storage UTXOs are not spent, even if they would cover the
+    // outputs.
+    signing.inputs.remove(1);
+    signing.outputs = vec![alice_output(40_000)];
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(
+        presigned.error,
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+}
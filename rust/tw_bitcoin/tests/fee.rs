@@ -0,0 +1,258 @@
+mod common;
+
+use common::{hex, P2WPKH_TX};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::fee::{
+    estimate_mixed, meets_min_relay, InputDescriptor, OutputDescriptor,
+};
+use tw_bitcoin::native::consensus::deserialize;
+use tw_bitcoin::native::Transaction;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn fee_estimate_mixed_inputs() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let input = |vout: u32, sighash_type, variant| Proto::Input {
+        txid: txid.as_slice().into(),
+        vout,
+        value: 10_000,
+        sighash_type,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+        ..Default::default()
+    };
+
+    let tx1 = input(
+        0,
+        UtxoProto::SighashType::All,
+        ProtoInputBuilder::p2pkh(alice_pubkey.as_slice().into()),
+    );
+    let tx2 = input(
+        1,
+        UtxoProto::SighashType::All,
+        ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+    );
+    // The default sighash type results in a 64-byte Schnorr signature.
+    let tx3 = input(
+        2,
+        UtxoProto::SighashType::UseDefault,
+        ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+            public_key: alice_pubkey.as_slice().into(),
+            one_prevout: false,
+        }),
+    );
+
+    let out1 = Proto::Output {
+        value: 15_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+    let out2 = Proto::Output {
+        value: 10_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_key_path(bob_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1, tx2, tx3],
+        outputs: vec![out1, out2],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let signed_vsize = (signed.weight + 3) / 4;
+
+    let estimate = estimate_mixed(
+        &[
+            InputDescriptor::P2pkh,
+            InputDescriptor::P2wpkh,
+            InputDescriptor::P2trKeyPath,
+        ],
+        &[OutputDescriptor::P2wpkh, OutputDescriptor::P2tr],
+        10,
+    );
+
+    assert!(estimate.vsize.abs_diff(signed_vsize) <= 1);
+    assert_eq!(estimate.fee, estimate.vsize * 10);
+}
+
+#[test]
+fn fee_estimate_legacy_only() {
+    // No Segwit marker and flag for legacy-only transactions:
+    // 4 + 1 + 148 + 1 + 34 + 4 bytes.
+    let estimate = estimate_mixed(&[InputDescriptor::P2pkh], &[OutputDescriptor::P2pkh], 2);
+    assert_eq!(estimate.vsize, 192);
+    assert_eq!(estimate.fee, 384);
+}
+
+#[test]
+fn fee_meets_min_relay() {
+    let tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    assert_eq!(tx.vsize(), 110);
+
+    // Paying exactly the minimum relay fee.
+    assert!(meets_min_relay(&tx, 110, 1));
+    assert!(meets_min_relay(&tx, 330, 3));
+
+    // Paying one sat less.
+    assert!(!meets_min_relay(&tx, 109, 1));
+    assert!(!meets_min_relay(&tx, 329, 3));
+
+    // The minimum relay fee overflows.
+    assert!(!meets_min_relay(&tx, u64::MAX, u64::MAX));
+}
+
+#[test]
+fn fee_script_sig_placeholder_p2pkh() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657");
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    let txid: Vec<u8> = hex("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let signing_input = |script_sig_size: u32| Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 5_000_000_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2pkh(alice_pubkey.as_slice().into()),
+            }),
+            script_sig_size,
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value: 4_998_999_997,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing_input(107));
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    let signed = BitcoinEntry.sign(&coin, signing_input(107));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // The signed scriptSig: length + signature (71 bytes DER + sighash type),
+    // length + compressed public key.
+    let tx: Transaction = deserialize(&signed.encoded).unwrap();
+    assert_eq!(tx.input[0].script_sig.len(), 107);
+
+    // The estimate matches the signed transaction exactly.
+    assert_eq!(presigned.weight_estimate, tx.weight().to_wu());
+    assert_eq!((presigned.weight_estimate + 3) / 4, tx.vsize() as u64);
+
+    // A smaller placeholder reduces the estimate accordingly.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing_input(100));
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.weight_estimate, tx.weight().to_wu() - 4 * 7);
+}
+
+#[test]
+fn fee_confirmation_bucket() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657");
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    let txid: Vec<u8> = hex("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let bucket =
+        |min_fee_per_vb: u64, blocks: u32, label: &'static str| Proto::ConfirmationBucket {
+            min_fee_per_vb,
+            blocks,
+            label: label.into(),
+        };
+
+    let signing_input = |fee_per_vb: u64| Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 5_000_000_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2pkh(alice_pubkey.as_slice().into()),
+            }),
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value: 4_998_000_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        fee_per_vb,
+        // Not required to be sorted.
+        confirmation_buckets: vec![
+            bucket(5, 6, "~1 hour"),
+            bucket(50, 1, "next block"),
+            bucket(20, 3, "~30 min"),
+        ],
+        ..Default::default()
+    };
+
+    let label = |fee_per_vb: u64| {
+        let presigned = BitcoinEntry.preimage_hashes(&coin, signing_input(fee_per_vb));
+        assert_eq!(presigned.error, Proto::Error::OK);
+        presigned
+            .confirmation_bucket
+            .map(|bucket| bucket.label.to_string())
+    };
+
+    // A high fee rate maps to the fastest bucket, a low one to the slowest.
+    assert_eq!(label(100).as_deref(), Some("next block"));
+    assert_eq!(label(50).as_deref(), Some("next block"));
+    assert_eq!(label(30).as_deref(), Some("~30 min"));
+    assert_eq!(label(6).as_deref(), Some("~1 hour"));
+
+    // Below the slowest bucket.
+    assert_eq!(label(1), None);
+}
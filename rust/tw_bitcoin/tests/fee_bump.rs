@@ -0,0 +1,407 @@
+mod common;
+
+use common::{hex, p2wpkh_utxo, P2WPKH_TX};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::fee_bump::{
+    build_replacement_with_new_inputs, cpfp_child_fee, diff_replacement, effective_value,
+    fee_per_vb_with_floor, min_rbf_feerate, select_bump_inputs, MempoolInfo, OutputChange,
+    ReplacedTransaction, INCREMENTAL_RELAY_FEERATE,
+};
+use tw_bitcoin::native::consensus::{deserialize, serialize};
+use tw_bitcoin::native::{PublicKey, ScriptBuf, Transaction, TxOut, Txid};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn min_rbf_feerate_satisfies_bip125() {
+    let original_tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    let original_vsize = original_tx.vsize() as u64;
+    assert_eq!(original_vsize, 110);
+
+    let original_fee = 1_100;
+    let descendant_fees = 500;
+    let descendant_vsize = 150;
+    let replacement_vsize = 141;
+
+    let rate = min_rbf_feerate(
+        &original_tx,
+        original_fee,
+        descendant_fees,
+        descendant_vsize,
+        replacement_vsize,
+    );
+    let fee = (rate * replacement_vsize as f64).ceil() as u64;
+
+    // The replacement pays at least the fees of all evicted transactions.
+    assert!(fee >= original_fee + descendant_fees);
+
+    // The additional fee pays for the replacement's own bandwidth.
+    let incremental = (INCREMENTAL_RELAY_FEERATE * replacement_vsize as f64) as u64;
+    assert!(fee - (original_fee + descendant_fees) >= incremental);
+
+    // The fee rate is higher than the one of the evicted transactions.
+    let original_rate =
+        (original_fee + descendant_fees) as f64 / (original_vsize + descendant_vsize) as f64;
+    assert!(fee as f64 / replacement_vsize as f64 > original_rate);
+}
+
+#[test]
+fn min_rbf_feerate_as_floor() {
+    let original_tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+
+    // Original fee rate of 10 sat/vbyte, no descendants.
+    let rate = min_rbf_feerate(&original_tx, 1_100, 0, 0, 110);
+    assert_eq!(rate, 11.0);
+
+    // A lower fee rate is raised to the minimum.
+    assert_eq!(fee_per_vb_with_floor(5, rate), 11);
+    // A higher fee rate is kept as is.
+    assert_eq!(fee_per_vb_with_floor(20, rate), 20);
+}
+
+#[test]
+fn select_bump_inputs_prefers_single_largest_utxo() {
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let fee_per_vb = 10;
+    let candidates = vec![
+        p2wpkh_utxo(&alice_pubkey, 0, 3_000),
+        p2wpkh_utxo(&alice_pubkey, 1, 4_000),
+        p2wpkh_utxo(&alice_pubkey, 2, 20_000),
+        p2wpkh_utxo(&alice_pubkey, 3, 2_500),
+    ];
+
+    // The two small UTXOs at index 0 and 1 would cover the fee increase as
+    // well, but the single largest one results in a smaller replacement.
+    let selected = select_bump_inputs(&candidates, 5_000, fee_per_vb).unwrap();
+    assert_eq!(selected, vec![2]);
+
+    // The effective value considers the fee of spending the input.
+    let effective = effective_value(&candidates[2], fee_per_vb).unwrap();
+    assert!(effective < 20_000);
+
+    // Multiple inputs are only combined if no single one is sufficient.
+    let selected = select_bump_inputs(&candidates, 22_000, fee_per_vb).unwrap();
+    assert_eq!(selected, vec![2, 1]);
+
+    // Insufficient candidates.
+    assert!(select_bump_inputs(&candidates, 100_000, fee_per_vb).is_err());
+}
+
+#[test]
+fn replacement_with_new_input_keeps_outputs() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let p2wpkh_output = |pubkey: &[u8], value: u64| Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(pubkey.to_vec().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // The original transaction pays Bob and returns the change to Alice, with
+    // an (implicit) fee of 1_000 sats.
+    let original_outputs = vec![
+        p2wpkh_output(&bob_pubkey, 30_000),
+        p2wpkh_output(&alice_pubkey, 19_000),
+    ];
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![p2wpkh_utxo(&alice_pubkey, 0, 50_000)],
+        outputs: original_outputs.clone(),
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let original = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(original.error, Proto::Error::OK);
+    let original_tx: Transaction = deserialize(&original.encoded).unwrap();
+
+    // The replacement adds a new input that funds the fee increase.
+    let replaced = ReplacedTransaction {
+        tx: &original_tx,
+        fee: 1_000,
+        descendant_fees: 0,
+        descendant_vsize: 0,
+    };
+    let mut signing = build_replacement_with_new_inputs(
+        replaced,
+        vec![p2wpkh_utxo(&alice_pubkey, 0, 50_000)],
+        original_outputs,
+        // The two small candidates would cover the fee increase as well, but
+        // only the largest one is added.
+        vec![
+            p2wpkh_utxo(&alice_pubkey, 1, 3_000),
+            p2wpkh_utxo(&alice_pubkey, 2, 10_000),
+            p2wpkh_utxo(&alice_pubkey, 3, 3_500),
+        ],
+        p2wpkh_output(&alice_pubkey, 0),
+        20,
+    )
+    .unwrap();
+    // The fee rate is above the RBF minimum and kept as is.
+    assert_eq!(signing.fee_per_vb, 20);
+    signing.private_key = alice_private_key.as_slice().into();
+
+    let replacement = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(replacement.error, Proto::Error::OK);
+    let replacement_tx: Transaction = deserialize(&replacement.encoded).unwrap();
+
+    // The original outputs are byte-identical.
+    assert_eq!(replacement_tx.output.len(), 3);
+    for (original, replaced) in original_tx.output.iter().zip(&replacement_tx.output) {
+        assert_eq!(serialize(original), serialize(replaced));
+    }
+
+    // The new input was added, and its excess goes to the fresh change output.
+    assert_eq!(replacement_tx.input.len(), 2);
+    assert_eq!(
+        replacement_tx.input[0].previous_output,
+        original_tx.input[0].previous_output
+    );
+    assert_eq!(replacement_tx.input[1].previous_output.vout, 2);
+    assert_eq!(
+        replacement_tx.output[2].value,
+        10_000 + 1_000 - replacement.effective_fee
+    );
+
+    // The replacement pays a higher fee (rate).
+    assert!(replacement.effective_fee > 1_000);
+    assert!(
+        replacement.effective_fee as f64 / replacement_tx.vsize() as f64
+            > 1_000.0 / original_tx.vsize() as f64
+    );
+}
+
+#[test]
+fn replacement_with_new_input_raises_feerate_to_rbf_minimum() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let p2wpkh_output = |pubkey: &[u8], value: u64| Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(pubkey.to_vec().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // The original transaction pays a fee of 2_000 sats, i.e. more than 10
+    // sat/vbyte.
+    let original_outputs = vec![
+        p2wpkh_output(&bob_pubkey, 30_000),
+        p2wpkh_output(&alice_pubkey, 18_000),
+    ];
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![p2wpkh_utxo(&alice_pubkey, 0, 50_000)],
+        outputs: original_outputs.clone(),
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let original = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(original.error, Proto::Error::OK);
+    let original_tx: Transaction = deserialize(&original.encoded).unwrap();
+
+    let replaced = ReplacedTransaction {
+        tx: &original_tx,
+        fee: 2_000,
+        descendant_fees: 0,
+        descendant_vsize: 0,
+    };
+
+    // A fee rate of 1 sat/vbyte is too low to replace the original.
+    let mut signing = build_replacement_with_new_inputs(
+        replaced,
+        vec![p2wpkh_utxo(&alice_pubkey, 0, 50_000)],
+        original_outputs,
+        vec![p2wpkh_utxo(&alice_pubkey, 1, 10_000)],
+        p2wpkh_output(&alice_pubkey, 0),
+        1,
+    )
+    .unwrap();
+    assert!(signing.fee_per_vb > 1);
+    signing.private_key = alice_private_key.as_slice().into();
+
+    let replacement = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(replacement.error, Proto::Error::OK);
+    let replacement_tx: Transaction = deserialize(&replacement.encoded).unwrap();
+
+    // The raised fee rate satisfies the BIP-125 conditions.
+    let replacement_vsize = replacement_tx.vsize() as u64;
+    assert!(replacement.effective_fee >= 2_000 + replacement_vsize);
+    assert!(
+        replacement.effective_fee as f64 / replacement_vsize as f64
+            > 2_000.0 / original_tx.vsize() as f64
+    );
+}
+
+#[test]
+fn replacement_with_new_input_rejects_fee_overflow() {
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let original_tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+
+    let replaced = ReplacedTransaction {
+        tx: &original_tx,
+        fee: 1_000,
+        descendant_fees: 0,
+        descendant_vsize: 0,
+    };
+    let change_output = Proto::Output {
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.clone().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // The fee of the replacement at this fee rate exceeds the range of
+    // satoshis.
+    let err = build_replacement_with_new_inputs(
+        replaced,
+        vec![],
+        vec![],
+        vec![p2wpkh_utxo(&alice_pubkey, 1, 10_000)],
+        change_output,
+        u64::MAX,
+    )
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_utxo_amount_overflow
+    );
+}
+
+#[test]
+fn diff_replacement_reports_reduced_change_only() {
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let alice_pubkey = PublicKey::from_slice(&alice_pubkey).unwrap();
+    let change_script = ScriptBuf::new_v0_p2wpkh(&alice_pubkey.wpubkey_hash().unwrap());
+
+    // The original transaction pays Bob and returns the change to Alice, with
+    // a fee of 1_000 sats.
+    let mut original: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    original.output[0].value = 30_000;
+    original.output.push(TxOut {
+        value: 19_000,
+        script_pubkey: change_script.clone(),
+    });
+    let bob_output = original.output[0].clone();
+
+    // The replacement spends the same input and funds the fee increase by
+    // reducing the change.
+    let mut replacement = original.clone();
+    replacement.output[1].value = 18_500;
+
+    let diff = diff_replacement(&original, 1_000, &replacement, 1_500);
+    assert!(diff.added_inputs.is_empty());
+    assert!(diff.removed_inputs.is_empty());
+    assert!(diff.added_outputs.is_empty());
+    assert!(diff.removed_outputs.is_empty());
+    assert_eq!(
+        diff.changed_outputs,
+        vec![OutputChange {
+            script_pubkey: change_script.clone(),
+            original_value: 19_000,
+            replacement_value: 18_500,
+        }]
+    );
+    assert_eq!(diff.fee_increase(), Some(500));
+    assert!(diff.preserves_payments(&change_script));
+
+    // Reordering the outputs is not a change.
+    replacement.output.reverse();
+    let reordered = diff_replacement(&original, 1_000, &replacement, 1_500);
+    assert_eq!(reordered, diff);
+
+    // Reducing Bob's output instead does not preserve the payment.
+    let mut replacement = original.clone();
+    replacement.output[0].value = 29_500;
+
+    let diff = diff_replacement(&original, 1_000, &replacement, 1_500);
+    assert_eq!(
+        diff.changed_outputs,
+        vec![OutputChange {
+            script_pubkey: bob_output.script_pubkey,
+            original_value: 30_000,
+            replacement_value: 29_500,
+        }]
+    );
+    assert!(!diff.preserves_payments(&change_script));
+
+    // Diverting part of the change to a foreign output does not preserve the
+    // payments either.
+    let mallory_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+    let mallory_pubkey = PublicKey::from_slice(&mallory_pubkey).unwrap();
+    let mallory_output = TxOut {
+        value: 10_000,
+        script_pubkey: ScriptBuf::new_v0_p2wpkh(&mallory_pubkey.wpubkey_hash().unwrap()),
+    };
+
+    let mut replacement = original.clone();
+    replacement.output[1].value = 8_500;
+    replacement.output.push(mallory_output.clone());
+
+    let diff = diff_replacement(&original, 1_000, &replacement, 1_500);
+    assert_eq!(diff.changed_outputs.len(), 1);
+    assert_eq!(diff.added_outputs, vec![mallory_output]);
+    assert!(!diff.preserves_payments(&change_script));
+
+    // A replacement paying a lower fee.
+    let diff = diff_replacement(&original, 1_000, &original, 900);
+    assert_eq!(diff.fee_increase(), None);
+}
+
+// Returns fixed ancestor data for a single known parent.
+struct MockMempool {
+    parent_txid: Txid,
+}
+
+impl MempoolInfo for MockMempool {
+    fn ancestor_fees_and_size(&self, txid: &Txid) -> (u64, u64) {
+        if txid == &self.parent_txid {
+            // The parent and its ancestors pay 2 sat/vbyte.
+            (1_000, 500)
+        } else {
+            (0, 0)
+        }
+    }
+}
+
+#[test]
+fn cpfp_child_fee_from_mempool_info() {
+    let original_tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+    let mempool = MockMempool {
+        parent_txid: original_tx.txid(),
+    };
+
+    // The package of 500 + 150 vbytes must pay 10 sat/vbyte.
+    let fee = cpfp_child_fee(&mempool, &original_tx.txid(), 150, 10.0);
+    assert_eq!(fee, 6_500 - 1_000);
+    assert_eq!((1_000 + fee) as f64 / 650.0, 10.0);
+
+    // The ancestors already pay more than the target fee rate, so the child
+    // only pays for itself.
+    let fee = cpfp_child_fee(&mempool, &original_tx.txid(), 150, 1.5);
+    assert_eq!(fee, 225);
+}
@@ -51,6 +51,7 @@ fn p2pkh_fee_estimate() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     });
 
     let prehashes = BitcoinEntry.preimage_hashes(&coin, signing.clone());
@@ -106,6 +107,7 @@ fn p2wpkh_fee_estimate() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     });
 
     let prehashes = BitcoinEntry.preimage_hashes(&coin, signing.clone());
@@ -154,6 +156,7 @@ fn p2tr_key_path_fee_estimate() {
         to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
             variant: ProtoOutputBuilder::p2tr_key_path(bob_pubkey.as_slice().into()),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -219,6 +222,7 @@ fn brc20_inscribe_fee_estimate() {
                 },
             ),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -244,3 +248,197 @@ fn brc20_inscribe_fee_estimate() {
     assert_eq!(signed.weight, 571);
     assert_eq!(signed.fee, (571 + 3) / 4 * SAT_VB);
 }
+
+#[test]
+fn p2wpkh_exact_amount_changeless() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    // Send exactly 10_000 sats, the remainder of 500 sats is paid as fee.
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 10_500,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+            }),
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value: 10_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: 1,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let prehashes = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(prehashes.error, Proto::Error::OK);
+    assert_eq!(prehashes.utxo_outputs.len(), 1);
+    assert_eq!(prehashes.effective_fee, 500);
+    assert_eq!(
+        prehashes.effective_fee_rate,
+        500.0 / ((prehashes.weight_estimate + 3) / 4) as f64
+    );
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.outputs.len(), 1);
+    assert_eq!(transaction.outputs[0].value, 10_000);
+    assert_eq!(signed.effective_fee, 500);
+    assert_eq!(
+        signed.effective_fee_rate,
+        500.0 / ((signed.weight + 3) / 4) as f64
+    );
+}
+
+#[test]
+fn p2wpkh_effective_fee_rate_meets_target() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let inputs = (0..3)
+        .map(|vout| Proto::Input {
+            txid: txid.as_slice().into(),
+            vout,
+            value: 20_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+    // Automatic input selection with change at a target of 10 sat/vbyte.
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs,
+        outputs: vec![Proto::Output {
+            value: 30_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::SelectAscending,
+        fee_per_vb: 10,
+        change_output: Some(Proto::Output {
+            value: 0,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 2);
+    assert_eq!(transaction.outputs.len(), 2);
+
+    // The fee is based on the (rounded up) weight estimate, so the achieved
+    // rate never falls below the target.
+    assert!(signed.effective_fee_rate >= 10.0);
+    assert!(signed.effective_fee_rate < 10.5);
+}
+
+#[test]
+fn p2wpkh_totals_are_consistent() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let p2wpkh_output = |value: u64, pubkey: &[u8]| Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(pubkey.to_vec().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let inputs = (0..2)
+        .map(|vout| Proto::Input {
+            txid: txid.as_slice().into(),
+            vout,
+            value: 10_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+    // Two inputs, one recipient and the change output.
+    let signing = Proto::SigningInput {
+        inputs,
+        outputs: vec![p2wpkh_output(15_000, &bob_pubkey)],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: 5,
+        change_output: Some(p2wpkh_output(0, &alice_pubkey)),
+        ..Default::default()
+    };
+
+    let prehashes = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(prehashes.error, Proto::Error::OK);
+    assert_eq!(prehashes.utxo_inputs.len(), 2);
+    assert_eq!(prehashes.utxo_outputs.len(), 2);
+
+    assert_eq!(prehashes.total_input, 20_000);
+    assert_eq!(
+        prehashes.total_output,
+        prehashes
+            .utxo_outputs
+            .iter()
+            .map(|output| output.value)
+            .sum::<u64>()
+    );
+    assert_eq!(
+        prehashes.total_input,
+        prehashes.total_output + prehashes.effective_fee
+    );
+    assert_eq!(prehashes.effective_fee, prehashes.fee_estimate);
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use common::{hex, MINER_FEE, ONE_BTC};
+use std::str::FromStr;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::hd::{
+    bip86_address, derivation_path, derive_input_keys, KeyIndex, Purpose,
+};
+use tw_bitcoin::native::bip32::{DerivationPath, ExtendedPrivKey};
+use tw_bitcoin::native::Network;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+// BIP-32 test vector 1.
+const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+// Convenience function, derives the key pair at the given path.
+fn derive(master: &ExtendedPrivKey, path: &str) -> (Vec<u8>, Vec<u8>) {
+    let secp = tw_bitcoin::secp256k1::Secp256k1::new();
+    let derived = master
+        .derive_priv(&secp, &DerivationPath::from_str(path).unwrap())
+        .unwrap();
+
+    let private_key = derived.private_key.secret_bytes().to_vec();
+    let public_key = derived.to_priv().public_key(&secp).to_bytes();
+    (private_key, public_key)
+}
+
+#[test]
+fn hd_purpose_from_script_type() {
+    let pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let input = |variant: ProtoInputBuilder<'static>| Proto::Input {
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+        ..Default::default()
+    };
+
+    let p2pkh = input(ProtoInputBuilder::p2pkh(pubkey.clone().into()));
+    assert_eq!(Purpose::from_input(&p2pkh).unwrap(), Purpose::Bip44);
+
+    let p2wpkh = input(ProtoInputBuilder::p2wpkh(pubkey.clone().into()));
+    assert_eq!(Purpose::from_input(&p2wpkh).unwrap(), Purpose::Bip84);
+
+    let p2tr = input(ProtoInputBuilder::p2tr_key_path(
+        Proto::mod_Input::InputTaprootKeyPath {
+            one_prevout: false,
+            public_key: pubkey.into(),
+        },
+    ));
+    assert_eq!(Purpose::from_input(&p2tr).unwrap(), Purpose::Bip86);
+
+    let key_index = KeyIndex {
+        account: 0,
+        change: 1,
+        index: 5,
+    };
+
+    let path = derivation_path(Purpose::Bip84, Network::Bitcoin, key_index).unwrap();
+    assert_eq!(path.to_string(), "m/84'/0'/0'/1/5");
+
+    // Test networks use the coin type 1.
+    let path = derivation_path(Purpose::Bip84, Network::Testnet, key_index).unwrap();
+    assert_eq!(path.to_string(), "m/84'/1'/0'/1/5");
+}
+
+#[test]
+fn hd_sign_with_auto_derivation() {
+    let coin = TestCoinContext::default();
+
+    let master = ExtendedPrivKey::new_master(Network::Bitcoin, &hex(SEED)).unwrap();
+    let (p2wpkh_private_key, p2wpkh_pubkey) = derive(&master, "m/84'/0'/0'/0/0");
+    let (p2tr_private_key, p2tr_pubkey) = derive(&master, "m/86'/0'/0'/0/1");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.clone().into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(p2wpkh_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    let tx2 = Proto::Input {
+        txid: txid.into(),
+        vout: 1,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                one_prevout: false,
+                public_key: p2tr_pubkey.into(),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 2 * ONE_BTC - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(p2wpkh_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let mut signing = Proto::SigningInput {
+        inputs: vec![tx1, tx2],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let key_indices = [
+        KeyIndex::default(),
+        KeyIndex {
+            index: 1,
+            ..Default::default()
+        },
+    ];
+    derive_input_keys(&mut signing, &master, &key_indices).unwrap();
+
+    // P2WPKH is derived via `m/84'/..`, P2TR via `m/86'/..`.
+    assert_eq!(
+        signing.inputs[0].private_key.as_ref(),
+        p2wpkh_private_key.as_slice()
+    );
+    assert_eq!(
+        signing.inputs[1].private_key.as_ref(),
+        p2tr_private_key.as_slice()
+    );
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+}
+
+#[test]
+fn hd_derive_testnet_coin_type() {
+    let master = ExtendedPrivKey::new_master(Network::Testnet, &hex(SEED)).unwrap();
+    assert!(master.to_string().starts_with("tprv"));
+
+    let (private_key, pubkey) = derive(&master, "m/84'/1'/0'/0/0");
+    let (mainnet_private_key, _) = derive(&master, "m/84'/0'/0'/0/0");
+
+    let mut signing = Proto::SigningInput {
+        inputs: vec![Proto::Input {
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(pubkey.into()),
+            }),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    derive_input_keys(&mut signing, &master, &[KeyIndex::default()]).unwrap();
+
+    // A testnet master key derives via the coin type 1.
+    assert_eq!(
+        signing.inputs[0].private_key.as_ref(),
+        private_key.as_slice()
+    );
+    assert_ne!(
+        signing.inputs[0].private_key.as_ref(),
+        mainnet_private_key.as_slice()
+    );
+}
+
+#[test]
+fn hd_bip86_address_test_vectors() {
+    // BIP-86 test vectors, account `m/86'/0'/0'` of the mnemonic "abandon
+    // abandon ... about".
+    let xpub = "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ";
+
+    // First receiving address, `m/86'/0'/0'/0/0`.
+    assert_eq!(
+        bip86_address(xpub, 0, 0, Network::Bitcoin).unwrap(),
+        "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+    );
+
+    // Second receiving address, `m/86'/0'/0'/0/1`.
+    assert_eq!(
+        bip86_address(xpub, 0, 1, Network::Bitcoin).unwrap(),
+        "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh"
+    );
+
+    // First change address, `m/86'/0'/0'/1/0`.
+    assert_eq!(
+        bip86_address(xpub, 1, 0, Network::Bitcoin).unwrap(),
+        "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7"
+    );
+
+    // Hardened indices cannot be derived from an extended public key.
+    assert!(bip86_address(xpub, 0, 1 << 31, Network::Bitcoin).is_err());
+}
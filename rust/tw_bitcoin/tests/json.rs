@@ -0,0 +1,290 @@
+mod common;
+
+use common::{hex, ONE_BTC};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::json::signing_input_from_json;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const SIGNING_INPUT_JSON: &str = r#"{
+    "private_key": "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a",
+    "inputs": [{
+        "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+        "vout": 0,
+        "value": 200000000,
+        "sighash_type": "All",
+        "builder": { "p2wpkh": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f" }
+    }],
+    "outputs": [{
+        "value": 100000000,
+        "builder": { "p2wpkh": { "pubkey": "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f" } }
+    }],
+    "input_selector": "UseAll",
+    "fee_per_vb": 20,
+    "disable_change_output": true
+}"#;
+
+#[test]
+fn json_signing_input_p2wpkh() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 2 * ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: 20,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let from_json = signing_input_from_json(SIGNING_INPUT_JSON).unwrap();
+    assert_eq!(from_json, signing);
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed_json = BitcoinEntry.sign(&coin, from_json);
+    assert_eq!(signed_json.error, Proto::Error::OK);
+    assert_eq!(signed_json.encoded, signed.encoded);
+    assert_eq!(signed_json.txid, signed.txid);
+}
+
+#[test]
+fn json_signing_input_missing_required_field() {
+    // The input value is missing.
+    let json = r#"{
+        "inputs": [{
+            "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+            "vout": 0,
+            "builder": { "p2wpkh": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f" }
+        }],
+        "outputs": []
+    }"#;
+
+    let err = signing_input_from_json(json).unwrap_err();
+    assert_eq!(Proto::Error::from(err), Proto::Error::Error_invalid_json);
+}
+
+#[test]
+fn json_signing_input_invalid_txid() {
+    let json = r#"{
+        "inputs": [{
+            "txid": "11b9f62923af73e2",
+            "vout": 0,
+            "value": 200000000,
+            "builder": { "p2wpkh": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f" }
+        }],
+        "outputs": []
+    }"#;
+
+    let err = signing_input_from_json(json).unwrap_err();
+    assert_eq!(err.to_string(), "Error_invalid_json: txid must be 32 bytes");
+}
+
+#[test]
+fn json_signing_input_auto() {
+    let json = r#"{
+        "inputs": [{
+            "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+            "vout": 0,
+            "value": 200000000,
+            "builder": { "auto": { "script_pubkey": "001460cda7b50f14c152d7401c28ae773c698db92373" } }
+        }],
+        "outputs": []
+    }"#;
+
+    let signing = signing_input_from_json(json).unwrap();
+    assert_eq!(
+        signing.inputs[0].to_recipient,
+        ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::auto(Proto::mod_Input::InputAuto {
+                script_pubkey: hex("001460cda7b50f14c152d7401c28ae773c698db92373").into(),
+                public_key: Default::default(),
+            }),
+        })
+    );
+}
+
+#[test]
+fn json_signing_input_pay_to_contract() {
+    let json = r#"{
+        "inputs": [{
+            "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+            "vout": 0,
+            "value": 200000000,
+            "builder": { "p2tr_pay_to_contract": {
+                "public_key": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f",
+                "contract": "deadbeef",
+                "tweak_order": "AfterTaptweak"
+            } }
+        }],
+        "outputs": [{
+            "value": 100000000,
+            "builder": { "p2tr_pay_to_contract": {
+                "public_key": "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f",
+                "contract": "deadbeef"
+            } }
+        }]
+    }"#;
+
+    let signing = signing_input_from_json(json).unwrap();
+    assert_eq!(
+        signing.inputs[0].to_recipient,
+        ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_pay_to_contract(Proto::PayToContract {
+                public_key: hex(
+                    "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f"
+                )
+                .into(),
+                contract: hex("deadbeef").into(),
+                tweak_order: Proto::TweakOrder::AfterTaptweak,
+            }),
+        })
+    );
+
+    // The tweak order defaults to `BeforeTaptweak`.
+    assert_eq!(
+        signing.outputs[0].to_recipient,
+        ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_pay_to_contract(Proto::PayToContract {
+                public_key: hex(
+                    "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f"
+                )
+                .into(),
+                contract: hex("deadbeef").into(),
+                tweak_order: Proto::TweakOrder::BeforeTaptweak,
+            }),
+        })
+    );
+}
+
+#[test]
+fn json_signing_input_htlc_refund() {
+    let json = r#"{
+        "inputs": [{
+            "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+            "vout": 0,
+            "value": 200000000,
+            "builder": { "htlc_refund": {
+                "payment_hash": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+                "recipient_public_key": "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f",
+                "refund_public_key": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f",
+                "expiry": 800000
+            } }
+        }],
+        "outputs": []
+    }"#;
+
+    let signing = signing_input_from_json(json).unwrap();
+    assert_eq!(
+        signing.inputs[0].to_recipient,
+        ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::htlc_refund(Proto::mod_Input::InputHtlcRefund {
+                payment_hash: hex(
+                    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+                )
+                .into(),
+                recipient_public_key: hex(
+                    "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f"
+                )
+                .into(),
+                refund_public_key: hex(
+                    "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f"
+                )
+                .into(),
+                expiry: 800_000,
+            }),
+        })
+    );
+}
+
+#[test]
+fn json_signing_input_p2tr_untweaked_key_path() {
+    let json = r#"{
+        "inputs": [{
+            "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+            "vout": 0,
+            "value": 200000000,
+            "builder": { "p2tr_untweaked_key_path": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f" }
+        }],
+        "outputs": []
+    }"#;
+
+    let signing = signing_input_from_json(json).unwrap();
+    assert_eq!(
+        signing.inputs[0].to_recipient,
+        ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_untweaked_key_path(
+                hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f").into()
+            ),
+        })
+    );
+}
+
+#[test]
+fn json_signing_input_p2pk() {
+    let json = r#"{
+        "inputs": [{
+            "txid": "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+            "vout": 0,
+            "value": 200000000,
+            "builder": { "p2pk": "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f" }
+        }],
+        "outputs": [{
+            "value": 100000000,
+            "builder": { "p2pk": "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f" }
+        }]
+    }"#;
+
+    let signing = signing_input_from_json(json).unwrap();
+    assert_eq!(
+        signing.inputs[0].to_recipient,
+        ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2pk(
+                hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f").into()
+            ),
+        })
+    );
+    assert_eq!(
+        signing.outputs[0].to_recipient,
+        ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2pk(
+                hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f").into()
+            ),
+        })
+    );
+}
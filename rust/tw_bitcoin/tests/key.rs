@@ -0,0 +1,142 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::modules::address::{inspect, ScriptType};
+use tw_bitcoin::modules::key::{compress, decompress, generate, generate_with_rng};
+use tw_bitcoin::modules::network::parse_wif;
+use tw_bitcoin::native::{Address, Network};
+use tw_bitcoin::secp256k1::rand::{Error as RandError, RngCore};
+use tw_bitcoin::secp256k1::Secp256k1;
+use tw_proto::BitcoinV2::Proto;
+
+const COMPRESSED: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const UNCOMPRESSED: &str = "048d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f2c2ef1572b87738c732c66d4a3770d41bb1bda428ce96b0c9829243a0a6a96ae";
+
+#[test]
+fn key_compress_decompress_round_trip() {
+    let uncompressed = decompress(&hex(COMPRESSED)).unwrap();
+    assert_eq!(uncompressed.to_vec(), hex(UNCOMPRESSED));
+
+    let compressed = compress(&uncompressed).unwrap();
+    assert_eq!(compressed.to_vec(), hex(COMPRESSED));
+}
+
+#[test]
+fn key_compress_decompress_invalid() {
+    // Wrong lengths.
+    let err = compress(&hex(COMPRESSED)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+
+    let err = decompress(&hex(UNCOMPRESSED)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+
+    // Not on the curve (modified y-coordinate).
+    let mut off_curve = hex(UNCOMPRESSED);
+    off_curve[64] ^= 0x01;
+    let err = compress(&off_curve).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+
+    // No point with this x-coordinate.
+    let mut off_curve = hex(COMPRESSED);
+    off_curve[32] ^= 0x01;
+    let err = decompress(&off_curve).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_public_key
+    );
+}
+
+// A deterministic RNG (xorshift) for reproducible key generation.
+struct TestRng(u64);
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[test]
+fn key_generate_with_deterministic_rng() {
+    let secp = Secp256k1::new();
+
+    for (network, script_type) in [
+        (Network::Bitcoin, ScriptType::P2pkh),
+        (Network::Bitcoin, ScriptType::P2sh),
+        (Network::Bitcoin, ScriptType::P2wpkh),
+        (Network::Testnet, ScriptType::P2wpkh),
+        (Network::Bitcoin, ScriptType::P2tr),
+    ] {
+        let (wif, addr) = generate_with_rng(&mut TestRng(42), network, script_type).unwrap();
+
+        // The same seed results in the same key.
+        let again = generate_with_rng(&mut TestRng(42), network, script_type).unwrap();
+        assert_eq!(again, (wif.clone(), addr.clone()));
+
+        let private_key = parse_wif(&wif).unwrap();
+        assert_eq!(private_key.network, network);
+        assert!(private_key.compressed);
+
+        // The address is of the requested script type and belongs to the key.
+        let info = inspect(&addr).unwrap();
+        assert_eq!(info.script_type, script_type);
+
+        let pubkey = private_key.public_key(&secp);
+        let expected = match script_type {
+            ScriptType::P2pkh => Address::p2pkh(&pubkey, network),
+            ScriptType::P2sh => Address::p2shwpkh(&pubkey, network).unwrap(),
+            ScriptType::P2wpkh => Address::p2wpkh(&pubkey, network).unwrap(),
+            ScriptType::P2tr => Address::p2tr(&secp, pubkey.inner.into(), None, network),
+            _ => unreachable!(),
+        };
+        assert_eq!(addr, expected.to_string());
+    }
+
+    // A different seed results in a different key.
+    let (wif1, _) =
+        generate_with_rng(&mut TestRng(1), Network::Bitcoin, ScriptType::P2wpkh).unwrap();
+    let (wif2, _) =
+        generate_with_rng(&mut TestRng(2), Network::Bitcoin, ScriptType::P2wpkh).unwrap();
+    assert_ne!(wif1, wif2);
+
+    // The default RNG.
+    let (wif, addr) = generate(Network::Bitcoin, ScriptType::P2tr).unwrap();
+    assert!(parse_wif(&wif).is_ok());
+    assert_eq!(inspect(&addr).unwrap().script_type, ScriptType::P2tr);
+}
+
+#[test]
+fn key_generate_unsupported_script_type() {
+    let err = generate(Network::Bitcoin, ScriptType::P2wsh).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_unsupported_address_recipient
+    );
+}
@@ -5,7 +5,11 @@ mod common;
 use common::{hex, MINER_FEE, ONE_BTC};
 use secp256k1::ffi::CPtr;
 use std::ffi::CString;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::Bitcoin::Proto as LegacyProto;
+use tw_proto::BitcoinV2::Proto;
 use tw_proto::Common::Proto as CommonProto;
 use wallet_core_rs::ffi::bitcoin::legacy as legacy_ffi;
 
@@ -443,3 +447,69 @@ fn ffi_proto_sign_input_p2wpkh_output_brc20() {
     assert_ne!(encoded_hex[164..292], REVEAL_RAW[164..292]);
     assert_eq!(encoded_hex[292..], REVEAL_RAW[292..]);
 }
+
+#[test]
+fn ffi_brc20_reveal_from_legacy_output() {
+    let alice_private_key = hex("e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129");
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+
+    // The legacy inscription output, which carries the Taproot payload in
+    // `spendingScript`.
+    let c_ticker = CString::new("oadf").unwrap();
+    let brc20_output = unsafe {
+        legacy_ffi::tw_bitcoin_legacy_build_brc20_transfer_inscription(
+            c_ticker.as_ptr(),
+            20,
+            7_000,
+            alice_pubkey.as_c_ptr(),
+            alice_pubkey.len(),
+        )
+        .into_vec()
+    };
+
+    // Tagged output.
+    let output = unsafe {
+        legacy_ffi::tw_bitcoin_legacy_build_p2wpkh_script(
+            546,
+            alice_pubkey.as_c_ptr(),
+            alice_pubkey.len(),
+        )
+        .into_vec()
+    };
+
+    // The commit transaction, see `ffi_proto_sign_input_p2wpkh_output_brc20`.
+    let txid: Vec<u8> = hex("797d17d47ae66e598341f9dfdea020b04d4017dcf9cc33f0e51f7a6082171fb1")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let signing = unsafe {
+        legacy_ffi::tw_bitcoin_legacy_build_brc20_reveal_signing_input(
+            brc20_output.as_c_ptr(),
+            brc20_output.len(),
+            txid.as_c_ptr(),
+            txid.len(),
+            0,
+            alice_pubkey.as_c_ptr(),
+            alice_pubkey.len(),
+            output.as_c_ptr(),
+            output.len(),
+            0,
+        )
+        .into_vec()
+    };
+    let mut signing: Proto::SigningInput = tw_proto::deserialize(&signing).unwrap();
+    signing.private_key = alice_private_key.into();
+
+    let signed = BitcoinEntry.sign(&TestCoinContext::default(), signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // Same reveal transaction as built via the legacy signer.
+    const REVEAL_RAW: &str = "02000000000101b11f1782607a1fe5f033ccf9dc17404db020a0dedff94183596ee67ad4177d790000000000ffffffff012202000000000000160014e311b8d6ddff856ce8e9a4e03bc6d4fe5050a83d03406a35548b8fa4620028e021a944c1d3dc6e947243a7bfc901bf63fefae0d2460efa149a6440cab51966aa4f09faef2d1e5efcba23ab4ca6e669da598022dbcfe35b0063036f7264010118746578742f706c61696e3b636861727365743d7574662d3800377b2270223a226272632d3230222c226f70223a227472616e73666572222c227469636b223a226f616466222c22616d74223a223230227d6821c00f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb00000000";
+
+    let encoded_hex = tw_encoding::hex::encode(signed.encoded, false);
+    assert_eq!(encoded_hex[..164], REVEAL_RAW[..164]);
+    // Schnorr signature does not match (non-deterministic).
+    assert_ne!(encoded_hex[164..292], REVEAL_RAW[164..292]);
+    assert_eq!(encoded_hex[292..], REVEAL_RAW[292..]);
+}
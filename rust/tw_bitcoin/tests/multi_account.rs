@@ -0,0 +1,128 @@
+mod common;
+
+use common::{hex, p2wpkh_utxo};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::multi_account::{build, Account, FeeAllocation};
+use tw_bitcoin::native::{PublicKey, ScriptBuf};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+const FEE_RATE: u64 = 5;
+
+const ALICE_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const BOB_PUBKEY: &str = "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f";
+const RECIPIENT_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+
+fn p2wpkh_script(pubkey: &[u8]) -> ScriptBuf {
+    let pubkey = PublicKey::from_slice(pubkey).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap())
+}
+
+fn recipient_output(value: u64) -> Proto::Output<'static> {
+    Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(
+            p2wpkh_script(&hex(RECIPIENT_PUBKEY)).to_bytes().into(),
+        ),
+        ..Default::default()
+    }
+}
+
+// Convenience function, the accounts of Alice (one UTXO) and Bob (two UTXOs),
+// each paying the recipient.
+fn accounts() -> Vec<Account<'static>> {
+    let alice_pubkey = hex(ALICE_PUBKEY);
+    let bob_pubkey = hex(BOB_PUBKEY);
+
+    vec![
+        Account {
+            inputs: vec![p2wpkh_utxo(&alice_pubkey, 0, 150_000)],
+            outputs: vec![recipient_output(40_000)],
+            change_script: p2wpkh_script(&alice_pubkey),
+        },
+        Account {
+            inputs: vec![
+                p2wpkh_utxo(&bob_pubkey, 1, 60_000),
+                p2wpkh_utxo(&bob_pubkey, 2, 70_000),
+            ],
+            outputs: vec![recipient_output(50_000)],
+            change_script: p2wpkh_script(&bob_pubkey),
+        },
+    ]
+}
+
+fn script_of(output: &Proto::Output<'_>) -> ScriptBuf {
+    match &output.to_recipient {
+        ProtoOutputRecipient::custom_script_pubkey(script) => {
+            ScriptBuf::from_bytes(script.to_vec())
+        },
+        _ => panic!("expected a custom scriptPubkey"),
+    }
+}
+
+#[test]
+fn multi_account_change_per_account() {
+    let coin = TestCoinContext::default();
+
+    let signing = build(accounts(), FEE_RATE, FeeAllocation::Proportional).unwrap();
+    assert_eq!(signing.inputs.len(), 3);
+    assert_eq!(signing.outputs.len(), 4);
+
+    // The outputs of the accounts, followed by one change output per account.
+    assert_eq!(signing.outputs[0].value, 40_000);
+    assert_eq!(signing.outputs[1].value, 50_000);
+    assert_eq!(
+        script_of(&signing.outputs[2]),
+        p2wpkh_script(&hex(ALICE_PUBKEY))
+    );
+    assert_eq!(
+        script_of(&signing.outputs[3]),
+        p2wpkh_script(&hex(BOB_PUBKEY))
+    );
+
+    // Each account pays for its inputs (41 bytes + 27 vbytes witness
+    // estimate), its two P2WPKH outputs (31 bytes each) and half of the
+    // overhead (11 vbytes), rounded up.
+    let alice_fee = (68 + 2 * 31) * FEE_RATE + 28;
+    let bob_fee = (2 * 68 + 2 * 31) * FEE_RATE + 28;
+    assert_eq!(signing.outputs[2].value, 150_000 - 40_000 - alice_fee);
+    assert_eq!(signing.outputs[3].value, 130_000 - 50_000 - bob_fee);
+
+    // The skeleton covers the fee of the transaction.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.utxo_outputs.len(), 4);
+    assert!(presigned.fee_estimate <= alice_fee + bob_fee);
+}
+
+#[test]
+fn multi_account_fee_payer() {
+    let signing = build(accounts(), FEE_RATE, FeeAllocation::Payer(0)).unwrap();
+    assert_eq!(signing.outputs.len(), 4);
+
+    // Alice pays the fee of the whole transaction (339 vbytes), Bob receives
+    // the full remainder as change.
+    assert_eq!(signing.outputs[2].value, 150_000 - 40_000 - 339 * FEE_RATE);
+    assert_eq!(signing.outputs[3].value, 130_000 - 50_000);
+
+    let err = build(accounts(), FEE_RATE, FeeAllocation::Payer(2)).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_fee_payer
+    );
+}
+
+#[test]
+fn multi_account_insufficient_inputs() {
+    let mut accounts = accounts();
+    accounts[1].outputs[0].value = 129_900;
+
+    let err = build(accounts, FEE_RATE, FeeAllocation::Proportional).unwrap_err();
+    assert!(err.to_string().contains("account 1 can not cover"));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_utxo_insufficient_inputs
+    );
+}
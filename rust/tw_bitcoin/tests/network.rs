@@ -0,0 +1,113 @@
+mod common;
+
+use bitcoin::{PublicKey, ScriptBuf};
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::address::{inspect, p2wpkh_address, ScriptType};
+use tw_bitcoin::modules::network::{network_from_proto, parse_wif, parse_xprv};
+use tw_bitcoin::native::Network;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn testnet_p2wpkh_address() {
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let mainnet = p2wpkh_address(&bob_pubkey, Network::Bitcoin).unwrap();
+    assert!(mainnet.starts_with("bc1q"));
+
+    let testnet = p2wpkh_address(&bob_pubkey, Network::Testnet).unwrap();
+    assert!(testnet.starts_with("tb1q"));
+
+    // Signet shares the HRP with Testnet.
+    let signet = p2wpkh_address(&bob_pubkey, network_from_proto(Proto::Network::Signet)).unwrap();
+    assert_eq!(signet, testnet);
+
+    let info = inspect(&testnet).unwrap();
+    assert_eq!(info.script_type, ScriptType::P2wpkh);
+    assert_eq!(info.network, Network::Testnet);
+}
+
+#[test]
+fn testnet_wif_and_xprv() {
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+
+    let key = parse_wif("cQX5ePcXjTx7C5p6xV8zkp2NN9unhZx4a8RQVPiHd52WxoApV6yK").unwrap();
+    assert!(key.compressed);
+    assert_eq!(key.network, Network::Testnet);
+    assert_eq!(
+        key.inner.secret_bytes().as_slice(),
+        alice_private_key.as_slice()
+    );
+
+    let key = parse_wif("KzA6BUcgJQFr2eLqa5KsPVXJjvcP37rNW6GwNyFn7xNWi4AxKYsc").unwrap();
+    assert!(key.compressed);
+    assert_eq!(key.network, Network::Bitcoin);
+
+    // Master key of BIP-32 test vector 1.
+    let xprv = parse_xprv("tprv8ZgxMBicQKsPeDgjzdC36fs6bMjGApWDNLR9erAXMs5skhMv36j9MV5ecvfavji5khqjWaWSFhN3YcCUUdiKH6isR4Pwy3U5y5egddBr16m").unwrap();
+    assert_eq!(xprv.network, Network::Testnet);
+    assert_eq!(xprv.depth, 0);
+
+    assert!(parse_wif("invalid").is_err());
+}
+
+#[test]
+fn send_to_testnet_address() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let address = p2wpkh_address(&bob_pubkey, Network::Testnet).unwrap();
+
+    let mut signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 10_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+            }),
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value: 1_000,
+            to_recipient: ProtoOutputRecipient::from_address(address.into()),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    // Testnet addresses are rejected on mainnet (default).
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::Error_bad_address_recipient);
+
+    signing.network = Proto::Network::Testnet;
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let recipient = PublicKey::from_slice(&bob_pubkey).unwrap();
+    let expected = ScriptBuf::new_v0_p2wpkh(&recipient.wpubkey_hash().unwrap());
+
+    let tx = signed.transaction.unwrap();
+    assert_eq!(tx.outputs[0].script_pubkey, expected.as_bytes());
+
+    // Testnet addresses are valid on Signet.
+    signing.network = Proto::Network::Signet;
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+}
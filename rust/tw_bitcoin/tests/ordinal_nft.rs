@@ -1,8 +1,10 @@
 mod common;
 
+use bitcoin::PublicKey;
 use common::hex;
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{OrdinalNftInscription, MAX_INSCRIPTION_PAYLOAD_SIZE};
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
@@ -39,9 +41,11 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
                     inscribe_to: alice_pubkey.as_slice().into(),
                     mime_type: "image/png".into(),
                     payload: hex(common::data::NFT_INSCRIPTION_IMAGE_DATA).into(),
+                    parent: None,
                 },
             ),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -86,6 +90,7 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
                     inscribe_to: alice_pubkey.as_slice().into(),
                     mime_type: "image/png".into(),
                     payload: hex(common::data::NFT_INSCRIPTION_IMAGE_DATA).into(),
+                    parent: None,
                 },
             ),
         }),
@@ -99,6 +104,7 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -128,3 +134,51 @@ fn coin_entry_sign_ordinal_nft_commit_reveal_transfer() {
     assert_eq!(transaction.inputs.len(), 1);
     assert_eq!(transaction.outputs.len(), 1);
 }
+
+#[test]
+fn ordinal_nft_payload_exceeds_single_transaction() {
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+    let pubkey = PublicKey::from_slice(&alice_pubkey).unwrap();
+
+    // Fits into a single reveal transaction.
+    let payload = vec![0xab; MAX_INSCRIPTION_PAYLOAD_SIZE];
+    assert!(OrdinalNftInscription::new(b"image/png", &payload, pubkey).is_ok());
+
+    let payload = vec![0xab; MAX_INSCRIPTION_PAYLOAD_SIZE + 1];
+    let err = OrdinalNftInscription::new(b"image/png", &payload, pubkey)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains(
+        "payload of 390001 bytes exceeds the single transaction limit of 390000 bytes, split it into 2 chunks"
+    ));
+
+    // The error is reported by the signer too.
+    let out1 = Proto::Output {
+        value: 7_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::ordinal_inscribe(
+                Proto::mod_Output::OutputOrdinalInscription {
+                    inscribe_to: alice_pubkey.as_slice().into(),
+                    mime_type: "image/png".into(),
+                    payload: payload.into(),
+                    parent: None,
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let prehashes = BitcoinEntry.preimage_hashes(&TestCoinContext::default(), signing);
+    assert_eq!(
+        prehashes.error,
+        Proto::Error::Error_ordinal_payload_too_large
+    );
+    assert!(prehashes.error_message.contains("390000 bytes"));
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+// Convenience function, creates a labeled P2WPKH output.
+fn p2wpkh_output(value: u64, pubkey: &[u8], label: &str) -> Proto::Output<'static> {
+    Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(pubkey.to_vec().into()),
+            }),
+        }),
+        label: label.to_string().into(),
+    }
+}
+
+#[test]
+fn output_labels_survive_bip69_sorting() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: 100_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![
+            p2wpkh_output(60_000, &bob_pubkey, "invoice-1"),
+            p2wpkh_output(10_000, &bob_pubkey, "invoice-2"),
+            p2wpkh_output(20_000, &bob_pubkey, "invoice-3"),
+        ],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: 10,
+        change_output: Some(p2wpkh_output(0, &alice_pubkey, "change")),
+        bip69_sort_outputs: true,
+        ..Default::default()
+    };
+
+    // The change amount is the smallest, so it becomes the first output.
+    let expected_labels = ["change", "invoice-2", "invoice-3", "invoice-1"];
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    let labels: Vec<&str> = presigned
+        .utxo_outputs
+        .iter()
+        .map(|output| output.label.as_ref())
+        .collect();
+    assert_eq!(labels, expected_labels);
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // The signed transaction matches the outputs the sighashes commit to.
+    assert_eq!(signed.txid, presigned.txid);
+
+    let outputs = &signed.transaction.as_ref().unwrap().outputs;
+    let labels: Vec<&str> = outputs.iter().map(|output| output.label.as_ref()).collect();
+    assert_eq!(labels, expected_labels);
+
+    // Sorted by ascending value.
+    assert!(outputs
+        .windows(2)
+        .all(|pair| pair[0].value <= pair[1].value));
+    assert_eq!(outputs[1].value, 10_000);
+    assert_eq!(outputs[2].value, 20_000);
+    assert_eq!(outputs[3].value, 60_000);
+    assert_eq!(outputs[0].value, 10_000 - signed.effective_fee);
+    assert_eq!(outputs[0].value, presigned.utxo_outputs[0].value);
+}
+
+#[test]
+fn output_labels_without_sorting() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: 100_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![
+            p2wpkh_output(60_000, &bob_pubkey, "invoice-1"),
+            p2wpkh_output(10_000, &bob_pubkey, ""),
+        ],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: 10,
+        change_output: Some(p2wpkh_output(0, &alice_pubkey, "change")),
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // The order is retained, the change output comes last.
+    let labels: Vec<&str> = signed
+        .transaction
+        .as_ref()
+        .unwrap()
+        .outputs
+        .iter()
+        .map(|output| output.label.as_ref())
+        .collect();
+    assert_eq!(labels, ["invoice-1", "", "change"]);
+}
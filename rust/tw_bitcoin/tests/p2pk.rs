@@ -0,0 +1,153 @@
+mod common;
+
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::Hash;
+use bitcoin::script::Instruction;
+use bitcoin::secp256k1::{ecdsa, Message, Secp256k1};
+use bitcoin::sighash::SighashCache;
+use bitcoin::{PublicKey, Script, ScriptBuf, Transaction};
+use common::{hex, MINER_FEE, ONE_BTC};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::OutputBuilder;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "56429688a1a6b00b90ccd22a0de0a376b6569d8684022ae92229a28478bfb657";
+const ALICE_PUBKEY: &str = "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536";
+const BOB_PUBKEY: &str = "037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf";
+
+#[test]
+fn p2pk_output_script() {
+    let output = Proto::Output {
+        value: ONE_BTC,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2pk(hex(ALICE_PUBKEY).into()),
+        }),
+        ..Default::default()
+    };
+
+    // `<pubkey> OP_CHECKSIG`
+    let utxo = OutputBuilder::utxo_from_proto(&output).unwrap();
+    let mut expected = vec![0x21];
+    expected.extend(hex(ALICE_PUBKEY));
+    expected.push(0xac);
+    assert_eq!(utxo.script_pubkey.as_ref(), expected.as_slice());
+}
+
+#[test]
+fn coin_entry_sign_input_p2pk_output_p2pkh() {
+    sign_and_verify_p2pk(&hex(ALICE_PUBKEY));
+}
+
+#[test]
+fn coin_entry_sign_input_p2pk_uncompressed_output_p2pkh() {
+    let alice = PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap();
+    sign_and_verify_p2pk(&alice.inner.serialize_uncompressed());
+}
+
+// Convenience function, signs a P2PK input of the given (compressed or
+// uncompressed) public key and verifies the signature.
+fn sign_and_verify_p2pk(pubkey: &[u8]) {
+    let coin = TestCoinContext::default();
+
+    // The P2PK output, as built by the output builder.
+    let p2pk_output = Proto::Output {
+        value: ONE_BTC * 50,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2pk(pubkey.to_vec().into()),
+        }),
+        ..Default::default()
+    };
+    let p2pk_script = OutputBuilder::utxo_from_proto(&p2pk_output)
+        .unwrap()
+        .script_pubkey
+        .to_vec();
+
+    let txid: Vec<u8> = hex("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: ONE_BTC * 50,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2pk(pubkey.to_vec().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(BOB_PUBKEY).into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    // The input builder spends the output of the output builder.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(
+        presigned.utxo_inputs[0].script_pubkey.as_ref(),
+        p2pk_script.as_slice()
+    );
+
+    // The input commits to the public key itself, not to its hash.
+    let keys = &presigned.signing_keys[0];
+    assert_eq!(keys.public_keys.len(), 1);
+    assert_eq!(keys.public_keys[0].as_ref(), pubkey);
+    assert!(keys.public_key_hash.is_empty());
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+    assert!(tx.input[0].witness.is_empty());
+
+    // The scriptSig consists of the signature only.
+    let instructions: Vec<Instruction> = tx.input[0]
+        .script_sig
+        .instructions()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let [Instruction::PushBytes(sig)] = instructions.as_slice() else {
+        panic!("expected a single signature push");
+    };
+
+    // The signature (followed by `SIGHASH_ALL`) is valid for the legacy
+    // sighash of the P2PK scriptPubkey.
+    let sig = sig.as_bytes();
+    assert_eq!(sig[sig.len() - 1], 0x01);
+    let sig = ecdsa::Signature::from_der(&sig[..sig.len() - 1]).unwrap();
+
+    let sighash = SighashCache::new(&tx)
+        .legacy_signature_hash(0, Script::from_bytes(&p2pk_script), 0x01)
+        .unwrap();
+    let msg = Message::from_slice(sighash.as_byte_array()).unwrap();
+
+    let alice = PublicKey::from_slice(pubkey).unwrap();
+    Secp256k1::new()
+        .verify_ecdsa(&msg, &sig, &alice.inner)
+        .unwrap();
+    assert_eq!(
+        ScriptBuf::from_bytes(p2pk_script),
+        ScriptBuf::new_p2pk(&alice)
+    );
+}
@@ -51,6 +51,7 @@ fn coin_entry_sign_input_p2pkh_output_p2sh() {
                 ),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -93,6 +94,7 @@ fn coin_entry_sign_input_p2pkh_output_p2sh() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let mut signing = Proto::SigningInput {
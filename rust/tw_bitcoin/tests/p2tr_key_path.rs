@@ -1,5 +1,10 @@
 mod common;
 
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::key::TweakedPublicKey;
+use bitcoin::secp256k1::{schnorr, Message, Parity, PublicKey, Scalar, Secp256k1, SecretKey};
+use bitcoin::{ScriptBuf, Transaction};
 use common::{hex, MINER_FEE, ONE_BTC};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
@@ -38,6 +43,7 @@ fn coin_entry_sign_input_p2pkh_output_p2tr_key_path() {
         to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
             variant: ProtoOutputBuilder::p2tr_key_path(bob_pubkey.as_slice().into()),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -78,6 +84,7 @@ fn coin_entry_sign_input_p2pkh_output_p2tr_key_path() {
         to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
             variant: ProtoOutputBuilder::p2tr_key_path(alice_pubkey.as_slice().into()),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -96,3 +103,233 @@ fn coin_entry_sign_input_p2pkh_output_p2tr_key_path() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "02000000000101ac6058397e18c277e98defda1bc38bdf3ab304563d7df7afed0ca5f63220589a0000000000ffffffff01806de72901000000225120a5c027857e359d19f625e52a106b8ac6ca2d6a8728f6cf2107cd7958ee0787c20140ec2d3910d41506b60aaa20520bb72f15e2d2cbd97e3a8e26ee7bad5f4c56b0f2fb0ceaddac33cb2813a33ba017ba6b1d011bab74a0426f12a2bcf47b4ed5bc8600000000");
 }
+
+// Convenience function, builds and signs a P2TR key-path spend with the given
+// sighash type.
+fn sign_p2tr_key_path_with_sighash_type(
+    sighash_type: UtxoProto::SighashType,
+) -> Proto::SigningOutput<'static> {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("0351e003fdc48e7f31c9bc94996c91f6c3273b7ef4208a1686021bedf7673bb058");
+    let bob_private_key = hex("26c2566adcc030a1799213bfd546e615f6ab06f72085ec6806ff1761da48d227");
+    let bob_pubkey = hex("02c0938cf377023dfde55e9c96b3cff4ca8894fb6b5d2009006bd43c0bff69cac9");
+
+    let txid: Vec<u8> = hex("9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: ONE_BTC * 50 - MINER_FEE,
+        sighash_type,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                public_key: bob_pubkey.as_slice().into(),
+                one_prevout: false,
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_key_path(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: bob_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        dangerous_use_fixed_schnorr_rng: true,
+        ..Default::default()
+    };
+
+    BitcoinEntry.sign(&coin, signing)
+}
+
+#[test]
+fn coin_entry_sign_p2tr_key_path_sighash_default_signature_size() {
+    let signed = sign_p2tr_key_path_with_sighash_type(UtxoProto::SighashType::UseDefault);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    let witness = &transaction.inputs[0].witness_items;
+    assert_eq!(witness.len(), 1);
+    // SIGHASH_DEFAULT: no sighash type byte is appended.
+    assert_eq!(witness[0].len(), 64);
+}
+
+#[test]
+fn coin_entry_sign_p2tr_key_path_sighash_all_signature_size() {
+    let signed = sign_p2tr_key_path_with_sighash_type(UtxoProto::SighashType::All);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    let witness = &transaction.inputs[0].witness_items;
+    assert_eq!(witness.len(), 1);
+    // Explicit SIGHASH_ALL: the sighash type byte (0x01) is appended.
+    assert_eq!(witness[0].len(), 65);
+    assert_eq!(witness[0][64], 0x01);
+}
+
+#[test]
+fn coin_entry_sign_p2tr_key_path_missing_prevout() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("0351e003fdc48e7f31c9bc94996c91f6c3273b7ef4208a1686021bedf7673bb058");
+    let bob_private_key = hex("26c2566adcc030a1799213bfd546e615f6ab06f72085ec6806ff1761da48d227");
+    let bob_pubkey = hex("02c0938cf377023dfde55e9c96b3cff4ca8894fb6b5d2009006bd43c0bff69cac9");
+
+    let txid: Vec<u8> = hex("9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: ONE_BTC * 50 - MINER_FEE,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                public_key: bob_pubkey.as_slice().into(),
+                one_prevout: false,
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // The prevout amount of the (non-Taproot) second input is missing.
+    let tx2 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 1,
+        value: 0,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2pkh(bob_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_key_path(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: bob_private_key.as_slice().into(),
+        inputs: vec![tx1, tx2],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::Error_missing_taproot_prevout);
+    // The error names the missing outpoint.
+    assert!(signed
+        .error_message
+        .ends_with("missing 9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac:1"));
+}
+
+#[test]
+fn coin_entry_sign_p2tr_key_path_odd_y_public_key() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    // The compressed public key of this private key has an odd-y prefix.
+    let private_key = SecretKey::from_slice(&[1; 32]).unwrap();
+    let pubkey = private_key.public_key(&secp);
+    assert_eq!(pubkey.serialize()[0], 0x03);
+
+    // Compute the output key independently: the tweak is applied to the
+    // even-y key with the same x-coordinate.
+    let (xonly, parity) = pubkey.x_only_public_key();
+    assert_eq!(parity, Parity::Odd);
+
+    let tag = sha256::Hash::hash(b"TapTweak");
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    engine.input(&xonly.serialize());
+    let tweak = Scalar::from_be_bytes(sha256::Hash::from_engine(engine).to_byte_array()).unwrap();
+
+    let even_pubkey = PublicKey::from_x_only_public_key(xonly, Parity::Even);
+    let output_key = even_pubkey
+        .add_exp_tweak(&secp, &tweak)
+        .unwrap()
+        .x_only_public_key()
+        .0;
+    let script_pubkey =
+        ScriptBuf::new_v1_p2tr_tweaked(TweakedPublicKey::dangerous_assume_tweaked(output_key));
+
+    let txid: Vec<u8> = hex("9a582032f6a50cedaff77d3d5604b33adf8bc31bdaef8de977c2187e395860ac")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let out1 = Proto::Output {
+        value: ONE_BTC - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_key_path(pubkey.serialize().to_vec().into()),
+        }),
+        ..Default::default()
+    };
+
+    // The odd-y key and its even-y counterpart (the negated private key)
+    // result in the same output and both can spend it.
+    for private_key in [private_key, private_key.negate()] {
+        let tx1 = Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: ONE_BTC,
+            sighash_type: UtxoProto::SighashType::UseDefault,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                    public_key: private_key.public_key(&secp).serialize().to_vec().into(),
+                    one_prevout: false,
+                }),
+            }),
+            ..Default::default()
+        };
+
+        let signing = Proto::SigningInput {
+            private_key: private_key.secret_bytes().to_vec().into(),
+            inputs: vec![tx1],
+            outputs: vec![out1.clone()],
+            input_selector: UtxoProto::InputSelector::UseAll,
+            disable_change_output: true,
+            ..Default::default()
+        };
+
+        let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+        assert_eq!(presigned.error, Proto::Error::OK);
+        assert_eq!(
+            presigned.utxo_inputs[0].script_pubkey.as_ref(),
+            script_pubkey.as_bytes()
+        );
+
+        let signed = BitcoinEntry.sign(&coin, signing);
+        assert_eq!(signed.error, Proto::Error::OK);
+
+        let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+        assert_eq!(tx.output[0].script_pubkey, script_pubkey);
+
+        // The signature is valid for the output key.
+        let sig = schnorr::Signature::from_slice(&tx.input[0].witness[0]).unwrap();
+        let msg = Message::from_slice(presigned.sighashes[0].sighash.as_ref()).unwrap();
+        secp.verify_schnorr(&sig, &msg, &output_key).unwrap();
+    }
+}
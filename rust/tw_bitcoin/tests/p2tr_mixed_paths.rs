@@ -0,0 +1,167 @@
+mod common;
+
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::Hash;
+use bitcoin::key::TapTweak;
+use bitcoin::secp256k1::{schnorr, Message, Secp256k1};
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{LeafVersion, TapLeafHash};
+use bitcoin::{ScriptBuf, Transaction, TxOut};
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::TaprootScriptOnly;
+use tw_bitcoin::secp256k1::KeyPair;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn p2tr_key_path_and_script_path_inputs() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let keypair = KeyPair::from_seckey_slice(&secp, &alice_private_key).unwrap();
+    let alice_xonly = keypair.x_only_public_key().0;
+
+    let script_only = TaprootScriptOnly::new(alice_xonly);
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    // Spent via key-path.
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 40_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                public_key: alice_pubkey.as_slice().into(),
+                one_prevout: false,
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // Spent via script-path.
+    let tx2 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 1,
+        value: 30_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_script_path(
+                Proto::mod_Input::InputTaprootScriptPath {
+                    one_prevout: false,
+                    payload: script_only.leaf_script().to_bytes().into(),
+                    control_block: script_only.control_block().serialize().into(),
+                    codesep_pos: None,
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 60_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1, tx2],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.sighashes.len(), 2);
+
+    // Both inputs carry the scriptPubkey of the spent output.
+    let key_path_script = ScriptBuf::new_v1_p2tr(&secp, alice_xonly, None);
+    assert_eq!(
+        presigned.utxo_inputs[0].script_pubkey.as_ref(),
+        key_path_script.as_bytes()
+    );
+    assert_eq!(
+        presigned.utxo_inputs[1].script_pubkey.as_ref(),
+        script_only.script_pubkey().as_bytes()
+    );
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+    assert_eq!(tx.input.len(), 2);
+
+    // Compute the expected sighashes independently. The key-path sighash has
+    // `spend_type` 0, the script-path sighash commits to the leaf hash.
+    let prevouts = [
+        TxOut {
+            value: 40_000,
+            script_pubkey: key_path_script,
+        },
+        TxOut {
+            value: 30_000,
+            script_pubkey: script_only.script_pubkey(),
+        },
+    ];
+    let leaf_hash = TapLeafHash::from_script(script_only.leaf_script(), LeafVersion::TapScript);
+
+    let mut cache = SighashCache::new(&tx);
+    let key_path_sighash = cache
+        .taproot_key_spend_signature_hash(0, &Prevouts::All(&prevouts), TapSighashType::Default)
+        .unwrap();
+    let script_path_sighash = cache
+        .taproot_script_spend_signature_hash(
+            1,
+            &Prevouts::All(&prevouts),
+            leaf_hash,
+            TapSighashType::Default,
+        )
+        .unwrap();
+
+    assert_ne!(key_path_sighash, script_path_sighash);
+    assert_eq!(
+        presigned.sighashes[0].sighash.as_ref(),
+        key_path_sighash.as_byte_array()
+    );
+    assert_eq!(
+        presigned.sighashes[1].sighash.as_ref(),
+        script_path_sighash.as_byte_array()
+    );
+
+    // Witness: signature only, valid for the tweaked output key.
+    let items: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(items.len(), 1);
+
+    let sig = schnorr::Signature::from_slice(items[0]).unwrap();
+    let msg = Message::from_slice(key_path_sighash.as_byte_array()).unwrap();
+    let output_key = alice_xonly.tap_tweak(&secp, None).0.to_inner();
+    secp.verify_schnorr(&sig, &msg, &output_key).unwrap();
+
+    // Witness: signature, leaf script, control block. The signature is valid
+    // for the leaf key.
+    let items: Vec<&[u8]> = tx.input[1].witness.iter().collect();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1], script_only.leaf_script().as_bytes());
+    assert_eq!(items[2], script_only.control_block().serialize().as_slice());
+
+    let sig = schnorr::Signature::from_slice(items[0]).unwrap();
+    let msg = Message::from_slice(script_path_sighash.as_byte_array()).unwrap();
+    secp.verify_schnorr(&sig, &msg, &alice_xonly).unwrap();
+}
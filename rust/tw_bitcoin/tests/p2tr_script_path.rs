@@ -56,6 +56,7 @@ fn coin_entry_custom_script_path() {
                 },
             ),
         }),
+        ..Default::default()
     };
 
     // Change/return transaction.
@@ -66,6 +67,7 @@ fn coin_entry_custom_script_path() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -117,6 +119,7 @@ fn coin_entry_custom_script_path() {
                     one_prevout: false,
                     payload: payload.to_vec().into(),
                     control_block: control_block.serialize().into(),
+                    codesep_pos: None,
                 },
             ),
         }),
@@ -130,6 +133,7 @@ fn coin_entry_custom_script_path() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -155,7 +159,10 @@ fn coin_entry_custom_script_path() {
     let encoded = tw_encoding::hex::encode(signed.encoded, false);
     let transaction = signed.transaction.unwrap();
 
-    assert_eq!(encoded, "02000000000101b11f1782607a1fe5f033ccf9dc17404db020a0dedff94183596ee67ad4177d790000000000ffffffff012202000000000000160014e311b8d6ddff856ce8e9a4e03bc6d4fe5050a83d03406a35548b8fa4620028e021a944c1d3dc6e947243a7bfc901bf63fefae0d2460efa149a6440cab51966aa4f09faef2d1e5efcba23ab4ca6e669da598022dbcfe35b0063036f7264010118746578742f706c61696e3b636861727365743d7574662d3800377b2270223a226272632d3230222c226f70223a227472616e73666572222c227469636b223a226f616466222c22616d74223a223230227d6821c00f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb00000000");
+    // Note that the (unused) signature differs from the broadcasted
+    // transaction, since the leaf does not check any signature and that
+    // transaction was signed with the leaf script as the spent scriptPubkey.
+    assert_eq!(encoded, "02000000000101b11f1782607a1fe5f033ccf9dc17404db020a0dedff94183596ee67ad4177d790000000000ffffffff012202000000000000160014e311b8d6ddff856ce8e9a4e03bc6d4fe5050a83d0340694b812b91c4143c759d717109283fda9a195629d3a742a8132165cad13232fd2ef50c050198f404fbc56ae6923c111c417c8bf53af8fca9f5b076c8e0ec39795b0063036f7264010118746578742f706c61696e3b636861727365743d7574662d3800377b2270223a226272632d3230222c226f70223a227472616e73666572222c227469636b223a226f616466222c22616d74223a223230227d6821c00f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb00000000");
     assert_eq!(transaction.inputs.len(), 1);
     assert_eq!(transaction.outputs.len(), 1);
 }
@@ -3,6 +3,7 @@ mod common;
 use common::{hex, MINER_FEE, ONE_BTC};
 use tw_bitcoin::aliases::*;
 use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::native::hashes::{sha256d, Hash};
 use tw_coin_entry::coin_entry::CoinEntry;
 use tw_coin_entry::test_utils::test_context::TestCoinContext;
 use tw_proto::BitcoinV2::Proto;
@@ -41,6 +42,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wpkh() {
                 to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -83,6 +85,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wpkh() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -100,3 +103,115 @@ fn coin_entry_sign_input_p2pkh_output_p2wpkh() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "020000000001016e1f16dcfafbb3a83697f6c23c624cd71085a7f8a25ce0bd9743a41d0a458e850000000000ffffffff01806de7290100000016001460cda7b50f14c152d7401c28ae773c698db9237302483045022100a9b517de5a5e036d7133df499b5b751db6f9a01576a6c5dc38229ec08b6c45cd02200e42c9f8c707c9bf0ceab4f739ec8d683dc1f1f29e195a8da9bc183584d624a60121025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f00000000");
 }
+
+#[test]
+fn coin_entry_preimage_hashes_signing_keys_p2wpkh() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: ONE_BTC * 50 - MINER_FEE,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(bob_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.signing_keys.len(), 1);
+
+    // The external signer must sign with Bob's public key.
+    let keys = &presigned.signing_keys[0];
+    assert_eq!(keys.public_keys.len(), 1);
+    assert_eq!(keys.public_keys[0].as_ref(), bob_pubkey.as_slice());
+    assert_eq!(
+        keys.public_key_hash.as_ref(),
+        hex("0d0e1cec6c2babe8badde5e9b3dea667da90036d").as_slice()
+    );
+}
+
+#[test]
+fn coin_entry_preimage_hashes_include_preimages_p2wpkh() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: ONE_BTC * 50 - MINER_FEE,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(bob_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC * 50 - MINER_FEE - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let mut signing = Proto::SigningInput {
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    // Preimages are not included by default.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert!(presigned.sighashes[0].preimage.is_empty());
+
+    signing.include_preimages = true;
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(presigned.sighashes.len(), 1);
+
+    // The sighash is the double SHA256 of the (BIP-143) preimage.
+    let sighash = &presigned.sighashes[0];
+    let hashed = sha256d::Hash::hash(sighash.preimage.as_ref());
+    assert_eq!(hashed.as_byte_array().as_slice(), sighash.sighash.as_ref());
+}
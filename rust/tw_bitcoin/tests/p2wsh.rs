@@ -51,6 +51,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wsh() {
                 ),
             }),
         }),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -94,6 +95,7 @@ fn coin_entry_sign_input_p2pkh_output_p2wsh() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let mut signing = Proto::SigningInput {
@@ -140,3 +142,168 @@ fn coin_entry_sign_input_p2pkh_output_p2wsh() {
     assert_eq!(signed.error, Proto::Error::OK);
     assert_eq!(&encoded, "02000000000101c717dda7ac1d846939637c52009d5bab1012efac1a6e019dc8f53235a24c9ddd0000000000ffffffff0100e9c829010000001976a914e4c1ea86373d554b8f4efff2cfb0001ea19124d288ac0347304402201d22810b5580a49a2e73d7c4ea90754b5d70d36adb9a8f0c9cb7393da1d1d28f02207683b2e3d31a5c7e74126681f1f2a7249b7a3a918d5890ef69b94bd3bb4fb9300121037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf1976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000");
 }
+
+#[test]
+fn coin_entry_p2wsh_max_witness_element_size() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    let txid: Vec<u8> = hex("dd9d4ca23532f5c89d016e1aacef1210ab5b9d00527c633969841daca7dd17c7")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let bob_native_pubkey = PublicKey::from_slice(&bob_pubkey).unwrap();
+    let redeem_script = ScriptBuf::new_p2pkh(&bob_native_pubkey.pubkey_hash());
+
+    // Compiles a P2WSH spend with the given stack element in the witness.
+    let compile = |element_size: usize| {
+        let mut witness = Witness::new();
+        witness.push(vec![0xab; element_size]);
+        witness.push(redeem_script.as_bytes());
+
+        let mut encoded = vec![];
+        let _ = witness.consensus_encode(&mut encoded).unwrap();
+
+        let tx1 = Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 50 * ONE_BTC - 3 * MINER_FEE,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wsh(encoded.into()),
+            }),
+            ..Default::default()
+        };
+
+        let out1 = Proto::Output {
+            value: 50 * ONE_BTC - 4 * MINER_FEE,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        };
+
+        let signing = Proto::SigningInput {
+            inputs: vec![tx1],
+            outputs: vec![out1],
+            input_selector: UtxoProto::InputSelector::UseAll,
+            disable_change_output: true,
+            ..Default::default()
+        };
+
+        // The signature is part of the finalized witness already.
+        BitcoinEntry.compile(&coin, signing, vec![vec![]], vec![])
+    };
+
+    let signed = compile(520);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed = compile(521);
+    assert_eq!(signed.error, Proto::Error::Error_script_element_too_large);
+    assert!(signed.error_message.contains("521 bytes"));
+}
+
+#[test]
+fn coin_entry_p2wsh_max_witness_stack_items() {
+    let coin = TestCoinContext::default();
+
+    let alice_pubkey = hex("036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536");
+    let bob_pubkey = hex("037ed9a436e11ec4947ac4b7823787e24ba73180f1edd2857bff19c9f4d62b65bf");
+
+    let txid: Vec<u8> = hex("dd9d4ca23532f5c89d016e1aacef1210ab5b9d00527c633969841daca7dd17c7")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let bob_native_pubkey = PublicKey::from_slice(&bob_pubkey).unwrap();
+    let witness_script = ScriptBuf::new_p2pkh(&bob_native_pubkey.pubkey_hash());
+
+    // Compiles a custom P2WSH spend with the given number of stack items,
+    // followed by the witness script.
+    let compile = |stack_items: usize, max_witness_stack_items: u32| {
+        let witness_items = std::iter::repeat(vec![0xab])
+            .take(stack_items)
+            .chain(std::iter::once(witness_script.to_bytes()))
+            .map(Into::into)
+            .collect();
+
+        let tx1 = Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 50 * ONE_BTC - 3 * MINER_FEE,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::custom_script(
+                Proto::mod_Input::InputScriptWitness {
+                    script_pubkey: ScriptBuf::new_v0_p2wsh(&witness_script.wscript_hash())
+                        .to_bytes()
+                        .into(),
+                    script_sig: Default::default(),
+                    witness_items,
+                    signing_method: UtxoProto::SigningMethod::Segwit,
+                },
+            ),
+            ..Default::default()
+        };
+
+        let out1 = Proto::Output {
+            value: 50 * ONE_BTC - 4 * MINER_FEE,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        };
+
+        let signing = Proto::SigningInput {
+            inputs: vec![tx1],
+            outputs: vec![out1],
+            input_selector: UtxoProto::InputSelector::UseAll,
+            disable_change_output: true,
+            max_witness_stack_items,
+            ..Default::default()
+        };
+
+        // The witness is provided in full already.
+        BitcoinEntry.compile(&coin, signing, vec![vec![]], vec![])
+    };
+
+    // At most 100 stack items by default.
+    let signed = compile(100, 0);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed = compile(101, 0);
+    assert_eq!(
+        signed.error,
+        Proto::Error::Error_too_many_witness_stack_items
+    );
+    assert!(signed
+        .error_message
+        .contains("witness of 101 stack items exceeds the maximum of 100"));
+
+    // The limit is configurable.
+    let signed = compile(101, 200);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed = compile(3, 2);
+    assert_eq!(
+        signed.error,
+        Proto::Error::Error_too_many_witness_stack_items
+    );
+
+    // Up to the consensus limit.
+    let signed = compile(1000, 1000);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let signed = compile(3, 1001);
+    assert_eq!(
+        signed.error,
+        Proto::Error::Error_too_many_witness_stack_items
+    );
+    assert!(signed.error_message.contains("consensus limit of 1000"));
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use bitcoin::consensus::Decodable;
+use bitcoin::key::TweakedPublicKey;
+use bitcoin::secp256k1::{schnorr, Message, Secp256k1};
+use bitcoin::{PublicKey, ScriptBuf, Transaction};
+use common::hex;
+use std::collections::HashMap;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::pay_to_contract::{output_key, signing_keypair};
+use tw_bitcoin::modules::signer::Signer;
+use tw_bitcoin::secp256k1::KeyPair;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a";
+const ALICE_PUBKEY: &str = "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f";
+const CONTRACT: &[u8] = b"invoice #42: 40000 sats";
+
+fn alice_output_key(tweak_order: Proto::TweakOrder) -> TweakedPublicKey {
+    let pubkey = PublicKey::from_slice(&hex(ALICE_PUBKEY)).unwrap();
+    output_key(pubkey.inner.into(), CONTRACT, tweak_order).unwrap()
+}
+
+fn p2c(tweak_order: Proto::TweakOrder) -> Proto::PayToContract<'static> {
+    Proto::PayToContract {
+        public_key: hex(ALICE_PUBKEY).into(),
+        contract: CONTRACT.into(),
+        tweak_order,
+    }
+}
+
+// Convenience function, spends a pay-to-contract output of the given tweak
+// order back to Alice.
+fn spending_input(tweak_order: Proto::TweakOrder) -> Proto::SigningInput<'static> {
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: 40_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_pay_to_contract(p2c(tweak_order)),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 30_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_key: hex(ALICE_PRIVATE_KEY).into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn pay_to_contract_tweak_orders() {
+    let secp = Secp256k1::new();
+    let keypair = KeyPair::from_seckey_slice(&secp, &hex(ALICE_PRIVATE_KEY)).unwrap();
+
+    let before = alice_output_key(Proto::TweakOrder::BeforeTaptweak);
+    let after = alice_output_key(Proto::TweakOrder::AfterTaptweak);
+
+    // Both orders commit to the contract, but result in different keys, none
+    // of which is the regular key-path output key.
+    let regular = ScriptBuf::new_v1_p2tr(&secp, keypair.x_only_public_key().0, None);
+    assert_ne!(before, after);
+    assert_ne!(ScriptBuf::new_v1_p2tr_tweaked(before), regular);
+    assert_ne!(ScriptBuf::new_v1_p2tr_tweaked(after), regular);
+
+    // The signing keys match the respective output keys.
+    let signing = |tweak_order| {
+        signing_keypair(&keypair, CONTRACT, tweak_order)
+            .unwrap()
+            .x_only_public_key()
+            .0
+    };
+    assert_eq!(
+        signing(Proto::TweakOrder::BeforeTaptweak),
+        before.to_inner()
+    );
+    assert_eq!(signing(Proto::TweakOrder::AfterTaptweak), after.to_inner());
+}
+
+#[test]
+fn pay_to_contract_build_and_spend() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    for tweak_order in [
+        Proto::TweakOrder::BeforeTaptweak,
+        Proto::TweakOrder::AfterTaptweak,
+    ] {
+        let output_key = alice_output_key(tweak_order);
+
+        // Create the pay-to-contract output.
+        let tx1 = Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 50_000,
+            sighash_type: UtxoProto::SighashType::All,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+            }),
+            ..Default::default()
+        };
+
+        let out1 = Proto::Output {
+            value: 40_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2tr_pay_to_contract(p2c(tweak_order)),
+            }),
+            ..Default::default()
+        };
+
+        let signing = Proto::SigningInput {
+            private_key: hex(ALICE_PRIVATE_KEY).into(),
+            inputs: vec![tx1],
+            outputs: vec![out1],
+            input_selector: UtxoProto::InputSelector::UseAll,
+            disable_change_output: true,
+            ..Default::default()
+        };
+
+        let signed = BitcoinEntry.sign(&coin, signing);
+        assert_eq!(signed.error, Proto::Error::OK);
+
+        let transaction = signed.transaction.unwrap();
+        assert_eq!(
+            transaction.outputs[0].script_pubkey.as_ref(),
+            ScriptBuf::new_v1_p2tr_tweaked(output_key).as_bytes()
+        );
+
+        // Spend the pay-to-contract output via the key-path.
+        let signing = spending_input(tweak_order);
+
+        let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+        assert_eq!(presigned.error, Proto::Error::OK);
+        assert_eq!(
+            presigned.utxo_inputs[0].script_pubkey.as_ref(),
+            ScriptBuf::new_v1_p2tr_tweaked(output_key).as_bytes()
+        );
+
+        let signed = BitcoinEntry.sign(&coin, signing);
+        assert_eq!(signed.error, Proto::Error::OK);
+
+        let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+        let items: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+        assert_eq!(items.len(), 1);
+
+        // The signature is valid for the committed output key.
+        let sig = schnorr::Signature::from_slice(items[0]).unwrap();
+        let msg = Message::from_slice(presigned.sighashes[0].sighash.as_ref()).unwrap();
+        secp.verify_schnorr(&sig, &msg, &output_key.to_inner())
+            .unwrap();
+    }
+}
+
+#[test]
+fn pay_to_contract_mixed_tweak_orders() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    // The output was built with one order...
+    let output_key = alice_output_key(Proto::TweakOrder::BeforeTaptweak);
+    let presigned =
+        BitcoinEntry.preimage_hashes(&coin, spending_input(Proto::TweakOrder::BeforeTaptweak));
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    // ... but signed with the other.
+    let mut contracts = HashMap::new();
+    contracts.insert(0, (CONTRACT.to_vec(), Proto::TweakOrder::AfterTaptweak));
+
+    let signatures = Signer::signatures_from_proto_with_contracts(
+        &presigned,
+        hex(ALICE_PRIVATE_KEY),
+        Default::default(),
+        contracts,
+        false,
+    )
+    .unwrap();
+
+    let sig = schnorr::Signature::from_slice(&signatures[0]).unwrap();
+    let msg = Message::from_slice(presigned.sighashes[0].sighash.as_ref()).unwrap();
+    assert!(secp
+        .verify_schnorr(&sig, &msg, &output_key.to_inner())
+        .is_err());
+}
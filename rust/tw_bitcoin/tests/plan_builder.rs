@@ -55,6 +55,7 @@ fn transaction_plan_compose_brc20() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let change_output = Proto::Output {
@@ -65,6 +66,7 @@ fn transaction_plan_compose_brc20() {
                 to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let brc20_inscription = Proto::mod_Input::InputBrc20Inscription {
@@ -190,3 +192,74 @@ fn transaction_plan_compose_brc20() {
     //dbg!(&commit_signed);
     //dbg!(&reveal_signed);
 }
+
+#[test]
+fn transaction_plan_compose_brc20_keeps_labels() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129");
+    let alice_pubkey = hex("030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let p2wpkh_output = |value: u64, label: &'static str| Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        label: label.into(),
+        ..Default::default()
+    };
+
+    let compose = Proto::ComposePlan {
+        compose: Proto::mod_ComposePlan::OneOfcompose::brc20(
+            Proto::mod_ComposePlan::ComposeBrc20Plan {
+                private_key: alice_private_key.into(),
+                inputs: vec![tx1],
+                input_selector: UtxoProto::InputSelector::SelectAscending,
+                tagged_output: Some(p2wpkh_output(546, "inscription")),
+                inscription: Some(Proto::mod_Input::InputBrc20Inscription {
+                    one_prevout: false,
+                    inscribe_to: alice_pubkey.as_slice().into(),
+                    ticker: "oadf".into(),
+                    transfer_amount: 20,
+                }),
+                fee_per_vb: 25,
+                change_output: Some(p2wpkh_output(0, "change")),
+                disable_change_output: false,
+            },
+        ),
+    };
+
+    let builder = BitcoinEntry.plan_builder().unwrap();
+    let built = builder.plan(&coin, compose);
+    assert_eq!(built.error, Proto::Error::OK);
+
+    let Proto::mod_TransactionPlan::OneOfplan::brc20(plan) = built.plan else { panic!() };
+
+    // The change output of the COMMIT transaction keeps its label.
+    let commit = plan.commit.unwrap();
+    assert_eq!(commit.outputs.len(), 2);
+    assert_eq!(commit.outputs[1].label, "change");
+
+    // The tagged output of the REVEAL transaction keeps its label.
+    let reveal = plan.reveal.unwrap();
+    assert_eq!(reveal.outputs.len(), 1);
+    assert_eq!(reveal.outputs[0].label, "inscription");
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::provenance::build_child_reveal;
+use tw_bitcoin::modules::transactions::{inscription_parent, InscriptionId};
+use tw_bitcoin::native::hashes::Hash;
+use tw_bitcoin::native::{Script, Txid};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const ALICE_PRIVATE_KEY: &str = "e253373989199da27c48680e3a3fc0f648d50f9a727ef17a7fe6a4dc3b159129";
+const ALICE_PUBKEY: &str = "030f209b6ada5edb42c77fd2bc64ad650ae38314c8f451f3e36d80bc8e26f132cb";
+
+// The transaction of the UTXOs, in reversed order.
+fn utxo_txid() -> Vec<u8> {
+    hex("579590c3227253ad423b1e7e3c5b073b8a280d307c68aecd779df2600daa2f99")
+        .into_iter()
+        .rev()
+        .collect()
+}
+
+// The ID of the parent inscription, `<txid>i0`.
+fn parent_id() -> Proto::InscriptionId<'static> {
+    Proto::InscriptionId {
+        txid: hex("173f8350b722243d44cc8db5584de76b432eb6d0888d9e66e662db51584f44ac")
+            .into_iter()
+            .rev()
+            .collect::<Vec<u8>>()
+            .into(),
+        index: 0,
+    }
+}
+
+fn alice_p2wpkh() -> ProtoOutputRecipient<'static> {
+    ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+        variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+            to_address: ProtoPubkeyOrHash::pubkey(hex(ALICE_PUBKEY).into()),
+        }),
+    })
+}
+
+fn parent_utxo() -> Proto::Input<'static> {
+    Proto::Input {
+        txid: utxo_txid().into(),
+        vout: 1,
+        value: 546,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(hex(ALICE_PUBKEY).into()),
+        }),
+        ..Default::default()
+    }
+}
+
+fn reveal_input(parent: Option<Proto::InscriptionId<'static>>) -> Proto::Input<'static> {
+    Proto::Input {
+        txid: utxo_txid().into(),
+        vout: 0,
+        value: 20_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::ordinal_inscribe(
+                Proto::mod_Input::InputOrdinalInscription {
+                    one_prevout: false,
+                    inscribe_to: hex(ALICE_PUBKEY).into(),
+                    mime_type: "text/plain;charset=utf-8".into(),
+                    payload: b"child".to_vec().into(),
+                    parent,
+                },
+            ),
+        }),
+        ..Default::default()
+    }
+}
+
+fn child_output() -> Proto::Output<'static> {
+    Proto::Output {
+        value: 546,
+        to_recipient: alice_p2wpkh(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn provenance_child_reveal_spends_parent() {
+    let coin = TestCoinContext::default();
+
+    let mut signing = build_child_reveal(
+        parent_utxo(),
+        alice_p2wpkh(),
+        reveal_input(Some(parent_id())),
+        child_output(),
+        10,
+    )
+    .unwrap();
+    signing.private_key = hex(ALICE_PRIVATE_KEY).into();
+    signing.dangerous_use_fixed_schnorr_rng = true;
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 2);
+
+    // The parent inscription UTXO is spent first and returned unchanged.
+    assert_eq!(transaction.inputs[0].txid.as_ref(), utxo_txid().as_slice());
+    assert_eq!(transaction.inputs[0].vout, 1);
+    assert_eq!(transaction.outputs[0].value, 546);
+    assert_eq!(transaction.outputs[1].value, 546);
+
+    // The revealed envelope (signature, script, control block) carries the
+    // parent tag.
+    let witness = &transaction.inputs[1].witness_items;
+    assert_eq!(witness.len(), 3);
+
+    let parent = inscription_parent(Script::from_bytes(&witness[1])).unwrap();
+    assert_eq!(parent, InscriptionId::from_proto(&parent_id()).unwrap());
+    assert_eq!(
+        parent.txid.to_string(),
+        "173f8350b722243d44cc8db5584de76b432eb6d0888d9e66e662db51584f44ac"
+    );
+}
+
+#[test]
+fn provenance_requires_parent() {
+    let err = build_child_reveal(
+        parent_utxo(),
+        alice_p2wpkh(),
+        reveal_input(None),
+        child_output(),
+        10,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_missing_inscription
+    );
+}
+
+#[test]
+fn provenance_inscription_id_tag_value() {
+    let txid = Txid::from_slice(&[0xab; 32]).unwrap();
+
+    // Trailing zero bytes of the index are omitted.
+    for (index, len) in [(0, 32), (1, 33), (0x0100, 34), (u32::MAX, 36)] {
+        let id = InscriptionId { txid, index };
+        let value = id.to_tag_value();
+        assert_eq!(value.len(), len);
+        assert_eq!(InscriptionId::from_tag_value(&value), Some(id));
+    }
+
+    assert_eq!(InscriptionId::from_tag_value(&[0xab; 31]), None);
+    assert_eq!(InscriptionId::from_tag_value(&[0xab; 37]), None);
+}
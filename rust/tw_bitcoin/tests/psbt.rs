@@ -0,0 +1,184 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::modules::psbt::{from_psbt_v2, to_psbt_v2};
+use tw_bitcoin::native::absolute::LockTime;
+use tw_bitcoin::native::consensus::deserialize;
+use tw_bitcoin::native::psbt::PartiallySignedTransaction;
+use tw_bitcoin::native::{OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use tw_proto::BitcoinV2::Proto;
+
+// Convenience function, creates a PSBT of a 1-in/1-out P2WPKH transaction.
+fn psbt() -> PartiallySignedTransaction {
+    let txid = deserialize(&hex(
+        "11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18",
+    ))
+    .unwrap();
+
+    let tx = Transaction {
+        version: 2,
+        lock_time: LockTime::from_consensus(800_000),
+        input: vec![TxIn {
+            previous_output: OutPoint { txid, vout: 1 },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: 90_000,
+            script_pubkey: ScriptBuf::from_bytes(hex(
+                "0014e4b8cc50b1591d437ace6158d22ba4b063fc3257",
+            )),
+        }],
+    };
+
+    let mut psbt = PartiallySignedTransaction::from_unsigned_tx(tx).unwrap();
+    psbt.inputs[0].witness_utxo = Some(TxOut {
+        value: 100_000,
+        script_pubkey: ScriptBuf::from_bytes(hex("00148d7097ba0280643daeebea18f861a83bb27271f9")),
+    });
+
+    psbt
+}
+
+#[test]
+fn psbt_v2_round_trip() {
+    let psbt = psbt();
+
+    let v2 = to_psbt_v2(&psbt).unwrap();
+    assert!(v2.starts_with(b"psbt\xff"));
+
+    // The global map describes the transaction: version 2, the fallback lock
+    // time, one input and one output, followed by the PSBT version 2.
+    assert!(v2.starts_with(&hex("70736274ff\
+         01020402000000\
+         01030400350c00\
+         01040101\
+         01050101\
+         01fb0402000000")));
+
+    // The input carries its outpoint and sequence, the output its amount and
+    // scriptPubkey.
+    let contains = |data: &[u8]| v2.windows(data.len()).any(|window| window == data);
+    assert!(contains(&hex(
+        "010e2011b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18"
+    )));
+    assert!(contains(&hex("010f0401000000")));
+    assert!(contains(&hex("011004fdffffff")));
+    assert!(contains(&hex("010308905f010000000000")));
+    assert!(contains(&hex(
+        "0104160014e4b8cc50b1591d437ace6158d22ba4b063fc3257"
+    )));
+
+    // Converting back yields the original (version 0) PSBT.
+    let v0 = from_psbt_v2(&v2).unwrap();
+    assert_eq!(v0, psbt);
+    assert_eq!(v0.serialize(), psbt.serialize());
+    assert_eq!(v0.inputs[0].witness_utxo.as_ref().unwrap().value, 100_000);
+}
+
+#[test]
+fn psbt_v2_invalid() {
+    let error = |bytes: &[u8]| Proto::Error::from(from_psbt_v2(bytes).unwrap_err());
+
+    // A version 0 PSBT.
+    assert_eq!(error(&psbt().serialize()), Proto::Error::Error_invalid_psbt);
+
+    // Truncated.
+    let v2 = to_psbt_v2(&psbt()).unwrap();
+    assert_eq!(error(&v2[..v2.len() - 1]), Proto::Error::Error_invalid_psbt);
+
+    assert_eq!(error(b"psbt"), Proto::Error::Error_invalid_psbt);
+}
+
+// Convenience function, encodes a version 2 PSBT with one input per given
+// pair of required (height, time) lock times.
+fn psbt_v2_with_required_lock_times(required: &[(Option<u32>, Option<u32>)]) -> Vec<u8> {
+    let pair = |key_type: u8, value: &[u8]| {
+        let mut pair = vec![0x01, key_type, value.len() as u8];
+        pair.extend(value);
+        pair
+    };
+
+    let mut v2 = b"psbt\xff".to_vec();
+    v2.extend(pair(0x02, &2u32.to_le_bytes()));
+    v2.extend(pair(0x03, &800_000u32.to_le_bytes()));
+    v2.extend(pair(0x04, &[required.len() as u8]));
+    v2.extend(pair(0x05, &[1]));
+    v2.extend(pair(0xfb, &2u32.to_le_bytes()));
+    v2.push(0x00);
+
+    let txid = hex("11b9f62923af73e297abb69f749e7a1aa2735fbdfd32ac5f6aa89e5c96841c18");
+    for (vout, (height, time)) in required.iter().enumerate() {
+        v2.extend(pair(0x0e, &txid));
+        v2.extend(pair(0x0f, &(vout as u32).to_le_bytes()));
+        v2.extend(pair(
+            0x10,
+            &Sequence::ENABLE_RBF_NO_LOCKTIME.0.to_le_bytes(),
+        ));
+        if let Some(time) = time {
+            v2.extend(pair(0x11, &time.to_le_bytes()));
+        }
+        if let Some(height) = height {
+            v2.extend(pair(0x12, &height.to_le_bytes()));
+        }
+        v2.push(0x00);
+    }
+
+    v2.extend(pair(0x03, &90_000u64.to_le_bytes()));
+    v2.extend(pair(
+        0x04,
+        &hex("0014e4b8cc50b1591d437ace6158d22ba4b063fc3257"),
+    ));
+    v2.push(0x00);
+
+    v2
+}
+
+#[test]
+fn psbt_v2_required_lock_times() {
+    let lock_time = |required: &[(Option<u32>, Option<u32>)]| {
+        let v0 = from_psbt_v2(&psbt_v2_with_required_lock_times(required)).unwrap();
+        // The required lock times are replaced by the unsigned transaction.
+        assert!(v0.inputs.iter().all(|input| input.unknown.is_empty()));
+        v0.unsigned_tx.lock_time.to_consensus_u32()
+    };
+
+    // No input requires a lock time, the fallback lock time is used.
+    assert_eq!(lock_time(&[(None, None), (None, None)]), 800_000);
+
+    // The maximum of the required height lock times.
+    assert_eq!(
+        lock_time(&[(Some(800_100), None), (Some(800_200), None)]),
+        800_200
+    );
+
+    // Inputs without a required lock time are ignored.
+    assert_eq!(lock_time(&[(None, None), (Some(800_100), None)]), 800_100);
+
+    // Heights are preferred if all inputs support them.
+    assert_eq!(
+        lock_time(&[(Some(800_100), Some(1_700_000_000)), (Some(800_200), None)]),
+        800_200
+    );
+
+    // Otherwise the maximum of the required time lock times.
+    assert_eq!(
+        lock_time(&[
+            (Some(800_100), Some(1_700_000_000)),
+            (None, Some(1_700_000_100))
+        ]),
+        1_700_000_100
+    );
+}
+
+#[test]
+fn psbt_v2_mixed_required_lock_times() {
+    // One input requires a height, the other a time based lock time.
+    let v2 =
+        psbt_v2_with_required_lock_times(&[(Some(800_100), None), (None, Some(1_700_000_000))]);
+    assert_eq!(
+        Proto::Error::from(from_psbt_v2(&v2).unwrap_err()),
+        Proto::Error::Error_invalid_psbt
+    );
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn expected_recipient_total() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 100_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let p2wpkh_output = |pubkey: &[u8], value: u64| Proto::Output {
+        value,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(pubkey.to_vec().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let mut signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![
+            p2wpkh_output(&bob_pubkey, 30_000),
+            p2wpkh_output(&bob_pubkey, 10_000),
+        ],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb: 10,
+        change_output: Some(p2wpkh_output(&alice_pubkey, 0)),
+        disable_change_output: false,
+        // The change output is not a recipient.
+        expected_recipient_total: 40_000,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::OK);
+    assert_eq!(signed.transaction.unwrap().outputs.len(), 3);
+
+    // One of the output values was modified.
+    signing.outputs[1].value = 15_000;
+
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::Error_recipient_total_mismatch);
+    assert!(signed
+        .error_message
+        .contains("outputs total 45000 sats, but 40000 sats are expected"));
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing);
+    assert_eq!(
+        presigned.error,
+        Proto::Error::Error_recipient_total_mismatch
+    );
+}
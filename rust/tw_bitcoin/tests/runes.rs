@@ -0,0 +1,156 @@
+mod common;
+
+use common::{hex, MINER_FEE, ONE_BTC};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::runes::{decode_runestone, CenotaphWarning, RuneId};
+use tw_bitcoin::native::opcodes::all::*;
+use tw_bitcoin::native::script::PushBytesBuf;
+use tw_bitcoin::native::ScriptBuf;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+// Convenience function, creates the runestone output script with the given
+// (LEB128 encoded) payload.
+fn runestone(payload: &[u8]) -> ScriptBuf {
+    ScriptBuf::builder()
+        .push_opcode(OP_RETURN)
+        .push_opcode(OP_PUSHNUM_13)
+        .push_slice(PushBytesBuf::try_from(payload.to_vec()).unwrap())
+        .into_script()
+}
+
+#[test]
+fn runestone_decode_edict() {
+    // Body, followed by an edict transferring 1000 of rune 840000:3 to output 1.
+    let script = runestone(&hex("00c0a23303e80701"));
+
+    let decoded = decode_runestone(&script, 2).unwrap();
+    assert!(decoded.fields.is_empty());
+    assert_eq!(decoded.edicts.len(), 1);
+    assert_eq!(
+        decoded.edicts[0].id,
+        RuneId {
+            block: 840_000,
+            tx: 3
+        }
+    );
+    assert_eq!(decoded.edicts[0].amount, 1000);
+    assert_eq!(decoded.edicts[0].output, 1);
+}
+
+#[test]
+fn runestone_cenotaph() {
+    // Unrecognized flag (bit 3).
+    let script = runestone(&hex("0208"));
+    assert_eq!(
+        decode_runestone(&script, 1).unwrap_err(),
+        CenotaphWarning::UnrecognizedFlag
+    );
+
+    // Truncated varint.
+    let script = runestone(&hex("0280"));
+    assert_eq!(
+        decode_runestone(&script, 1).unwrap_err(),
+        CenotaphWarning::Varint
+    );
+
+    // Unrecognized even tag.
+    let script = runestone(&hex("1e01"));
+    assert_eq!(
+        decode_runestone(&script, 1).unwrap_err(),
+        CenotaphWarning::UnrecognizedEvenTag
+    );
+
+    // Tag without a value.
+    let script = runestone(&hex("04"));
+    assert_eq!(
+        decode_runestone(&script, 1).unwrap_err(),
+        CenotaphWarning::TruncatedField
+    );
+
+    // Incomplete edict.
+    let script = runestone(&hex("00c0a23303"));
+    assert_eq!(
+        decode_runestone(&script, 1).unwrap_err(),
+        CenotaphWarning::TrailingIntegers
+    );
+
+    // Edict referring to a non-existing output.
+    let script = runestone(&hex("00c0a23303e80705"));
+    assert_eq!(
+        decode_runestone(&script, 2).unwrap_err(),
+        CenotaphWarning::EdictOutput
+    );
+
+    // Non-push opcode.
+    let mut script = runestone(&hex("00"));
+    script.push_opcode(OP_CHECKSIG);
+    assert_eq!(
+        decode_runestone(&script, 1).unwrap_err(),
+        CenotaphWarning::Opcode
+    );
+}
+
+#[test]
+fn runestone_cenotaph_builder() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let sign = |runestone: ScriptBuf| {
+        let signing = Proto::SigningInput {
+            private_key: alice_private_key.as_slice().into(),
+            inputs: vec![Proto::Input {
+                txid: txid.as_slice().into(),
+                vout: 0,
+                value: ONE_BTC,
+                sighash_type: UtxoProto::SighashType::All,
+                to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                    variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+                }),
+                ..Default::default()
+            }],
+            outputs: vec![
+                Proto::Output {
+                    value: ONE_BTC - MINER_FEE,
+                    to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                        variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                            to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+                        }),
+                    }),
+                    ..Default::default()
+                },
+                Proto::Output {
+                    value: 0,
+                    to_recipient: ProtoOutputRecipient::custom_script_pubkey(
+                        runestone.to_bytes().into(),
+                    ),
+                    ..Default::default()
+                },
+            ],
+            input_selector: UtxoProto::InputSelector::UseAll,
+            disable_change_output: true,
+            ..Default::default()
+        };
+
+        BitcoinEntry.sign(&coin, signing)
+    };
+
+    // Transfers to the first output.
+    let signed = sign(runestone(&hex("00c0a23303e80700")));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // A malformed runestone would burn the runes.
+    let signed = sign(runestone(&hex("0208")));
+    assert_eq!(signed.error, Proto::Error::Error_runestone_cenotaph);
+    assert!(signed.error_message.contains("UnrecognizedFlag"));
+}
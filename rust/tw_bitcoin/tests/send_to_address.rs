@@ -44,6 +44,7 @@ fn send_to_p2sh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address.to_string().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -104,6 +105,7 @@ fn send_to_p2pkh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address_string.as_str().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -165,6 +167,7 @@ fn send_to_p2wsh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address.to_string().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -225,6 +228,7 @@ fn send_to_p2wpkh_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address_string.as_str().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -288,6 +292,7 @@ fn send_to_p2tr_key_path_address() {
     let out1 = Proto::Output {
         value: 1_000,
         to_recipient: ProtoOutputRecipient::from_address(address_string.as_str().into()),
+        ..Default::default()
     };
 
     let signing = Proto::SigningInput {
@@ -0,0 +1,328 @@
+mod common;
+
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::Hash;
+use bitcoin::opcodes::all::{OP_CHECKSIG, OP_CODESEPARATOR, OP_NOP};
+use bitcoin::secp256k1::{schnorr, Message, Secp256k1};
+use bitcoin::sighash::{Annex, Prevouts, SighashCache, TapSighashType};
+use bitcoin::taproot::{LeafVersion, TapLeafHash, TaprootBuilder};
+use bitcoin::{ScriptBuf, Transaction, TxOut};
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::sighash::{taproot_components, TaprootSighashInput};
+use tw_bitcoin::modules::transactions::TaprootScriptOnly;
+use tw_bitcoin::secp256k1::KeyPair;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn sighash_taproot_components() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let keypair = KeyPair::from_seckey_slice(&secp, &alice_private_key).unwrap();
+    let alice_xonly = keypair.x_only_public_key().0;
+
+    let script_only = TaprootScriptOnly::new(alice_xonly);
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    // Spent via key-path.
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 40_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                public_key: alice_pubkey.as_slice().into(),
+                one_prevout: false,
+            }),
+        }),
+        ..Default::default()
+    };
+
+    // Spent via script-path.
+    let tx2 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 1,
+        value: 30_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_script_path(
+                Proto::mod_Input::InputTaprootScriptPath {
+                    one_prevout: false,
+                    payload: script_only.leaf_script().to_bytes().into(),
+                    control_block: script_only.control_block().serialize().into(),
+                    codesep_pos: None,
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 60_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1, tx2],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+
+    let prevouts = [
+        TxOut {
+            value: 40_000,
+            script_pubkey: ScriptBuf::new_v1_p2tr(&secp, alice_xonly, None),
+        },
+        TxOut {
+            value: 30_000,
+            script_pubkey: script_only.script_pubkey(),
+        },
+    ];
+    let leaf_hash = TapLeafHash::from_script(script_only.leaf_script(), LeafVersion::TapScript);
+
+    // Key-path: reassembling the components reproduces the sighash.
+    let key_path =
+        taproot_components(&tx, 0, &prevouts, TapSighashType::Default, None, None, None).unwrap();
+    assert_eq!(key_path.epoch, 0);
+    assert_eq!(key_path.hash_type, 0);
+    assert_eq!(key_path.version, 2);
+    assert_eq!(key_path.spend_type, 0);
+    assert_eq!(key_path.input, TaprootSighashInput::Index(0));
+    assert!(key_path.sha_outputs.is_some());
+    assert_eq!(
+        key_path.sighash().as_byte_array(),
+        presigned.sighashes[0].sighash.as_ref()
+    );
+
+    // Script-path: commits to the leaf hash.
+    let script_path = taproot_components(
+        &tx,
+        1,
+        &prevouts,
+        TapSighashType::Default,
+        Some(leaf_hash),
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(script_path.spend_type, 2);
+    assert_eq!(script_path.tapleaf_hash, Some(leaf_hash));
+    assert_eq!(
+        script_path.sighash().as_byte_array(),
+        presigned.sighashes[1].sighash.as_ref()
+    );
+
+    // Other sighash types and an annex, compared to the sighashes of the
+    // underlying library.
+    let annex = [0x50, 0x01, 0x02];
+    let mut cache = SighashCache::new(&tx);
+    for sighash_type in [
+        TapSighashType::None,
+        TapSighashType::Single,
+        TapSighashType::AllPlusAnyoneCanPay,
+        TapSighashType::SinglePlusAnyoneCanPay,
+    ] {
+        let components =
+            taproot_components(&tx, 0, &prevouts, sighash_type, None, None, Some(&annex)).unwrap();
+        assert_eq!(components.spend_type, 1);
+
+        let expected = cache
+            .taproot_signature_hash(
+                0,
+                &Prevouts::All(&prevouts),
+                Some(Annex::new(&annex).unwrap()),
+                None,
+                sighash_type,
+            )
+            .unwrap();
+        assert_eq!(components.sighash(), expected);
+    }
+
+    // `ANYONECANPAY` only commits to the signed input.
+    let components = taproot_components(
+        &tx,
+        1,
+        &prevouts,
+        TapSighashType::AllPlusAnyoneCanPay,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(components.sha_prevouts, None);
+    assert_eq!(
+        components.input,
+        TaprootSighashInput::Data {
+            outpoint: tx.input[1].previous_output,
+            amount: 30_000,
+            script_pubkey: script_only.script_pubkey(),
+            sequence: u32::MAX,
+        }
+    );
+
+    // No output at the index of the input for `SINGLE`.
+    let err = taproot_components(&tx, 1, &prevouts, TapSighashType::Single, None, None, None)
+        .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_utxo_sighash_single_missing_output
+    );
+}
+
+#[test]
+fn sighash_taproot_codesep_pos() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let keypair = KeyPair::from_seckey_slice(&secp, &alice_private_key).unwrap();
+    let alice_xonly = keypair.x_only_public_key().0;
+
+    // The `OP_CODESEPARATOR` is the second opcode, at position 1.
+    let leaf_script = ScriptBuf::builder()
+        .push_opcode(OP_NOP)
+        .push_opcode(OP_CODESEPARATOR)
+        .push_x_only_key(&alice_xonly)
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+    let spend_info = TaprootBuilder::new()
+        .add_leaf(0, leaf_script.clone())
+        .unwrap()
+        .finalize(&secp, TaprootScriptOnly::internal_key())
+        .unwrap();
+    let control_block = spend_info
+        .control_block(&(leaf_script.clone(), LeafVersion::TapScript))
+        .unwrap();
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let signing = |codesep_pos: Option<u32>| Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![Proto::Input {
+            txid: txid.as_slice().into(),
+            vout: 0,
+            value: 30_000,
+            sighash_type: UtxoProto::SighashType::UseDefault,
+            to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                variant: ProtoInputBuilder::p2tr_script_path(
+                    Proto::mod_Input::InputTaprootScriptPath {
+                        one_prevout: false,
+                        payload: leaf_script.to_bytes().into(),
+                        control_block: control_block.serialize().into(),
+                        codesep_pos: codesep_pos
+                            .map(|position| UtxoProto::CodeSeparatorPosition { position }),
+                    },
+                ),
+            }),
+            ..Default::default()
+        }],
+        outputs: vec![Proto::Output {
+            value: 20_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing(Some(1)));
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    let signed = BitcoinEntry.sign(&coin, signing(Some(1)));
+    assert_eq!(signed.error, Proto::Error::OK);
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+
+    let prevouts = [TxOut {
+        value: 30_000,
+        script_pubkey: ScriptBuf::new_v1_p2tr(
+            &secp,
+            TaprootScriptOnly::internal_key(),
+            spend_info.merkle_root(),
+        ),
+    }];
+    let leaf_hash = TapLeafHash::from_script(&leaf_script, LeafVersion::TapScript);
+
+    let reference = |codesep_pos: u32| {
+        SighashCache::new(&tx)
+            .taproot_signature_hash(
+                0,
+                &Prevouts::All(&prevouts),
+                None,
+                Some((leaf_hash, codesep_pos)),
+                TapSighashType::Default,
+            )
+            .unwrap()
+    };
+
+    // The sighash commits to the code separator position.
+    let expected = reference(1);
+    assert_eq!(
+        presigned.sighashes[0].sighash.as_ref(),
+        expected.as_byte_array()
+    );
+
+    // The signature is valid for that sighash.
+    let signature = schnorr::Signature::from_slice(&tx.input[0].witness[0]).unwrap();
+    let message = Message::from_slice(expected.as_byte_array()).unwrap();
+    secp.verify_schnorr(&signature, &message, &alice_xonly)
+        .unwrap();
+
+    // The components commit to the code separator position as well.
+    let components = taproot_components(
+        &tx,
+        0,
+        &prevouts,
+        TapSighashType::Default,
+        Some(leaf_hash),
+        Some(1),
+        None,
+    )
+    .unwrap();
+    assert_eq!(components.codesep_pos, Some(1));
+    assert_eq!(components.sighash(), expected);
+
+    // None (0xFFFFFFFF) by default.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing(None));
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(
+        presigned.sighashes[0].sighash.as_ref(),
+        reference(u32::MAX).as_byte_array()
+    );
+    assert_ne!(reference(u32::MAX), expected);
+}
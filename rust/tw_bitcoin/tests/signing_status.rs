@@ -0,0 +1,138 @@
+mod common;
+
+use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_2};
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+use bitcoin::{PublicKey, ScriptBuf};
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::signing_status::{
+    input_statuses, is_complete, unsigned_inputs, InputSigningStatus,
+};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn signing_status_2_of_2_multisig() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    // The two cosigners of the 2-of-2 P2WSH multisig.
+    let cosigner1 = SecretKey::from_slice(&[1; 32]).unwrap();
+    let cosigner2 = SecretKey::from_slice(&[2; 32]).unwrap();
+
+    let witness_script = ScriptBuf::builder()
+        .push_opcode(OP_PUSHNUM_2)
+        .push_key(&PublicKey::new(cosigner1.public_key(&secp)))
+        .push_key(&PublicKey::new(cosigner2.public_key(&secp)))
+        .push_opcode(OP_PUSHNUM_2)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    // Signed by Alice.
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 50_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    // Signed by the cosigners, missing signatures are empty items.
+    let multisig_input = |signatures: [Vec<u8>; 2]| Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 1,
+        value: 50_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::custom_script(Proto::mod_Input::InputScriptWitness {
+            script_pubkey: witness_script.to_bytes().into(),
+            script_sig: Default::default(),
+            // The dummy element, the signatures and the witness script.
+            witness_items: [vec![], signatures[0].clone(), signatures[1].clone()]
+                .into_iter()
+                .chain(std::iter::once(witness_script.to_bytes()))
+                .map(Into::into)
+                .collect(),
+            signing_method: UtxoProto::SigningMethod::Segwit,
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 90_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing_input = |signatures: [Vec<u8>; 2]| Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1.clone(), multisig_input(signatures)],
+        outputs: vec![out1.clone()],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    // The cosigners sign the sighash of the multisig input.
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing_input([vec![], vec![]]));
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    let sighash = Message::from_slice(presigned.sighashes[1].sighash.as_ref()).unwrap();
+    let sign = |key: &SecretKey| {
+        let mut sig = secp.sign_ecdsa(&sighash, key).serialize_der().to_vec();
+        // SIGHASH_ALL
+        sig.push(0x01);
+        sig
+    };
+
+    // Only the first cosigner signed.
+    let signed = BitcoinEntry.sign(&coin, signing_input([sign(&cosigner1), vec![]]));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    assert!(!is_complete(&signed));
+    assert_eq!(unsigned_inputs(&signed), vec![1]);
+    assert_eq!(
+        input_statuses(&signed),
+        vec![
+            InputSigningStatus {
+                signatures: 1,
+                required: 1,
+            },
+            InputSigningStatus {
+                signatures: 1,
+                required: 2,
+            },
+        ]
+    );
+
+    // Both cosigners signed.
+    let signed = BitcoinEntry.sign(&coin, signing_input([sign(&cosigner1), sign(&cosigner2)]));
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    assert!(is_complete(&signed));
+    assert!(unsigned_inputs(&signed).is_empty());
+    assert_eq!(
+        input_statuses(&signed)[1],
+        InputSigningStatus {
+            signatures: 2,
+            required: 2,
+        }
+    );
+}
@@ -0,0 +1,368 @@
+mod common;
+
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::key::TweakedPublicKey;
+use bitcoin::secp256k1::{schnorr, Message};
+use bitcoin::{OutPoint, ScriptBuf, Transaction, Txid};
+use common::hex;
+use std::str::FromStr;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::silent_payments::{
+    derive_outputs, input_hash, receiver_tweak, spending_private_key, SilentPaymentInput,
+    SilentPaymentRecipient,
+};
+use tw_bitcoin::secp256k1::{Parity, PublicKey, Scalar, Secp256k1, SecretKey, XOnlyPublicKey};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+// Convenience function, the BIP-340 tagged hash.
+fn tagged_hash(tag: &str, msg: &[u8]) -> [u8; 32] {
+    let tag = sha256::Hash::hash(tag.as_bytes());
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    engine.input(msg);
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+// Convenience function, scans for the k-th output of the given recipient keys,
+// as done by the receiving wallet: `P_k = B_spend + t_k * G` with
+// `t_k = hash(input_hash * b_scan * A || k)`.
+fn receiver_output(
+    scan_key: &SecretKey,
+    spend_pubkey: &PublicKey,
+    input_pubkey: &PublicKey,
+    input_hash: &Scalar,
+    k: u32,
+) -> XOnlyPublicKey {
+    let secp = Secp256k1::new();
+
+    let ecdh_key = scan_key.mul_tweak(input_hash).unwrap();
+    let shared_secret = input_pubkey
+        .mul_tweak(&secp, &Scalar::from(ecdh_key))
+        .unwrap();
+
+    let mut msg = shared_secret.serialize().to_vec();
+    msg.extend_from_slice(&k.to_be_bytes());
+    let tweak = Scalar::from_be_bytes(tagged_hash("BIP0352/SharedSecret", &msg)).unwrap();
+
+    spend_pubkey
+        .add_exp_tweak(&secp, &tweak)
+        .unwrap()
+        .x_only_public_key()
+        .0
+}
+
+#[test]
+fn silent_payments_multiple_recipients() {
+    let secp = Secp256k1::new();
+    let secret = |byte: u8| SecretKey::from_slice(&[byte; 32]).unwrap();
+
+    // The sender spends a P2WPKH and a P2TR key-path input.
+    let alice_private_key = SecretKey::from_slice(&hex(
+        "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a",
+    ))
+    .unwrap();
+    let inputs = [
+        SilentPaymentInput {
+            private_key: alice_private_key,
+            is_taproot: false,
+        },
+        SilentPaymentInput {
+            private_key: secret(7),
+            is_taproot: true,
+        },
+    ];
+
+    let txid =
+        Txid::from_str("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911").unwrap();
+    let outpoints = [OutPoint::new(txid, 1), OutPoint::new(txid, 0)];
+
+    // Two distinct recipients.
+    let (bob_scan, bob_spend) = (secret(1), secret(2));
+    let (carol_scan, carol_spend) = (secret(3), secret(4));
+
+    let recipient = |scan: &SecretKey, spend: &SecretKey| SilentPaymentRecipient {
+        scan_pubkey: scan.public_key(&secp),
+        spend_pubkey: spend.public_key(&secp),
+    };
+    let recipients = [
+        recipient(&bob_scan, &bob_spend),
+        recipient(&carol_scan, &carol_spend),
+    ];
+
+    let outputs = derive_outputs(&outpoints, &inputs, &recipients).unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_ne!(outputs[0], outputs[1]);
+
+    // The public key `A` of the input key sum, with the Taproot key being
+    // negated if required.
+    let taproot_xonly = secret(7).public_key(&secp).x_only_public_key().0;
+    let taproot_pubkey = PublicKey::from_x_only_public_key(taproot_xonly, Parity::Even);
+    let input_pubkey = alice_private_key
+        .public_key(&secp)
+        .combine(&taproot_pubkey)
+        .unwrap();
+    let input_hash = input_hash(&outpoints, &input_pubkey).unwrap();
+
+    // Each recipient finds its output with its own scan key.
+    let bob_output = receiver_output(
+        &bob_scan,
+        &bob_spend.public_key(&secp),
+        &input_pubkey,
+        &input_hash,
+        0,
+    );
+    let carol_output = receiver_output(
+        &carol_scan,
+        &carol_spend.public_key(&secp),
+        &input_pubkey,
+        &input_hash,
+        0,
+    );
+    assert_eq!(outputs[0], bob_output);
+    assert_eq!(outputs[1], carol_output);
+
+    // The outputs do not depend on the order of the outpoints.
+    let reversed = [outpoints[1], outpoints[0]];
+    assert_eq!(
+        derive_outputs(&reversed, &inputs, &recipients).unwrap(),
+        outputs
+    );
+
+    // Paying Bob twice results in two distinct outputs, numbered by `k`.
+    let recipients = [recipients[0], recipients[1], recipients[0]];
+    let outputs = derive_outputs(&outpoints, &inputs, &recipients).unwrap();
+    assert_eq!(outputs[0], bob_output);
+    assert_eq!(outputs[1], carol_output);
+    assert_eq!(
+        outputs[2],
+        receiver_output(
+            &bob_scan,
+            &bob_spend.public_key(&secp),
+            &input_pubkey,
+            &input_hash,
+            1,
+        )
+    );
+    assert_ne!(outputs[0], outputs[2]);
+}
+
+#[test]
+fn silent_payments_bip352_test_vectors() {
+    let secp = Secp256k1::new();
+    let secret = |key: &str| SecretKey::from_slice(&hex(key)).unwrap();
+    let xonly = |key: &str| XOnlyPublicKey::from_slice(&hex(key)).unwrap();
+
+    // The sender spends two non-Taproot inputs (BIP-352 test vectors).
+    let inputs = [
+        SilentPaymentInput {
+            private_key: secret("eadc78165ff1f8ea94ad7cfdc54990738a4c53f6e0507b42154201b8e5dff3b1"),
+            is_taproot: false,
+        },
+        SilentPaymentInput {
+            private_key: secret("93f5ed907ad5b2bdbbdcb5d9116ebc0a4e1f92f910d5260237fa45a9408aad16"),
+            is_taproot: false,
+        },
+    ];
+
+    // The recipient, given by its scan and spend keys.
+    let recipient = SilentPaymentRecipient {
+        scan_pubkey: secret("0f694e068028a717f8af6b9411f9a133dd3565258714cc226594b34db90c1f2c")
+            .public_key(&secp),
+        spend_pubkey: secret("9d6ad855ce3417ef84e836892e5a56392bfba05fa5d97ccea30e266f540e08b3")
+            .public_key(&secp),
+    };
+
+    // "Simple send: two inputs".
+    let txid = |txid: &str| Txid::from_str(txid).unwrap();
+    let outpoints = [
+        OutPoint::new(
+            txid("f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16"),
+            0,
+        ),
+        OutPoint::new(
+            txid("a1075db55d416d3ca199f55b6084e2115b9345e16c5cf302fc80e9d5fbf5d48d"),
+            0,
+        ),
+    ];
+    let outputs = derive_outputs(&outpoints, &inputs, &[recipient]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![xonly(
+            "3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1"
+        )]
+    );
+
+    // Paying the same recipient a second time adds the output with `k = 1`.
+    let outputs = derive_outputs(&outpoints, &inputs, &[recipient, recipient]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![
+            xonly("3e9fce73d4e77a4809908e3c3a2e54ee147b9312dc5044a193d1fc85de46e3c1"),
+            xonly("0ffe0b3d72d66b785e1a7ad416edcc22b951293b1507aa04850e890b002c60f1"),
+        ]
+    );
+
+    // "Simple send: two inputs from the same transaction".
+    let same_txid = txid("f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16");
+    let outpoints = [OutPoint::new(same_txid, 3), OutPoint::new(same_txid, 7)];
+    let outputs = derive_outputs(&outpoints, &inputs, &[recipient]).unwrap();
+    assert_eq!(
+        outputs,
+        vec![xonly(
+            "79e71baa2ba3fc66396de3a04f168c7bf24d6870ec88ca877754790c1db357b6"
+        )]
+    );
+}
+
+#[test]
+fn silent_payments_spend_received_output() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let alice_private_key = SecretKey::from_slice(&hex(
+        "57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a",
+    ))
+    .unwrap();
+    let alice_pubkey = alice_private_key.public_key(&secp);
+
+    let bob_scan = SecretKey::from_slice(&[1; 32]).unwrap();
+    let bob_spend = SecretKey::from_slice(&[2; 32]).unwrap();
+    let bob = SilentPaymentRecipient {
+        scan_pubkey: bob_scan.public_key(&secp),
+        spend_pubkey: bob_spend.public_key(&secp),
+    };
+
+    // Bob's regular P2WPKH key.
+    let bob_wpkh_key = SecretKey::from_slice(&[5; 32]).unwrap();
+    let bob_wpkh_pubkey = bob_wpkh_key.public_key(&secp);
+
+    let txid =
+        Txid::from_str("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911").unwrap();
+    let outpoint = OutPoint::new(txid, 0);
+
+    // Alice sends a silent payment to Bob, spending a P2WPKH input.
+    let alice_input = SilentPaymentInput {
+        private_key: alice_private_key,
+        is_taproot: false,
+    };
+    let output_key = derive_outputs(&[outpoint], &[alice_input], &[bob]).unwrap()[0];
+    let output_script =
+        ScriptBuf::new_v1_p2tr_tweaked(TweakedPublicKey::dangerous_assume_tweaked(output_key));
+
+    let p2wpkh_input = |txid: &[u8], vout: u32, value: u64, pubkey: &PublicKey| Proto::Input {
+        txid: txid.to_vec().into(),
+        vout,
+        value,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(pubkey.serialize().to_vec().into()),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.secret_bytes().to_vec().into(),
+        inputs: vec![p2wpkh_input(
+            &txid.to_byte_array(),
+            0,
+            100_000,
+            &alice_pubkey,
+        )],
+        outputs: vec![Proto::Output {
+            value: 90_000,
+            to_recipient: ProtoOutputRecipient::custom_script_pubkey(
+                output_script.to_bytes().into(),
+            ),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    let payment = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+    assert_eq!(payment.output[0].script_pubkey, output_script);
+
+    // Bob finds the output with the public data of the transaction and
+    // derives the private key that spends it.
+    let input_hash = input_hash(&[outpoint], &alice_pubkey).unwrap();
+    let tweak = receiver_tweak(&bob_scan, &alice_pubkey, &input_hash, 0).unwrap();
+    let spending_key = spending_private_key(&bob_spend, &tweak).unwrap();
+    assert_eq!(
+        spending_key.public_key(&secp).x_only_public_key().0,
+        output_key
+    );
+
+    // Bob spends the received output together with a regular P2WPKH input.
+    let payment_txid = payment.txid().to_byte_array();
+    let sp_input = Proto::Input {
+        txid: payment_txid.to_vec().into(),
+        vout: 0,
+        value: 90_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        private_key: spending_key.secret_bytes().to_vec().into(),
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_untweaked_key_path(
+                spending_key.public_key(&secp).serialize().to_vec().into(),
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: bob_wpkh_key.secret_bytes().to_vec().into(),
+        inputs: vec![
+            sp_input,
+            p2wpkh_input(&txid.to_byte_array(), 1, 20_000, &bob_wpkh_pubkey),
+        ],
+        outputs: vec![Proto::Output {
+            value: 100_000,
+            to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+                variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                    to_address: ProtoPubkeyOrHash::pubkey(
+                        bob_wpkh_pubkey.serialize().to_vec().into(),
+                    ),
+                }),
+            }),
+            ..Default::default()
+        }],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+    assert_eq!(
+        presigned.utxo_inputs[0].script_pubkey.as_ref(),
+        output_script.as_bytes()
+    );
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+    assert_eq!(tx.input.len(), 2);
+    assert_eq!(tx.input[0].previous_output.txid, payment.txid());
+
+    // Witness: signature only, valid for the (untweaked) output key.
+    let items: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(items.len(), 1);
+
+    let sig = schnorr::Signature::from_slice(items[0]).unwrap();
+    let msg = Message::from_slice(presigned.sighashes[0].sighash.as_ref()).unwrap();
+    secp.verify_schnorr(&sig, &msg, &output_key).unwrap();
+
+    // Witness: signature, public key.
+    let items: Vec<&[u8]> = tx.input[1].witness.iter().collect();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[1], bob_wpkh_pubkey.serialize().as_slice());
+}
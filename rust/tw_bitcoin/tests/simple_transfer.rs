@@ -0,0 +1,93 @@
+mod common;
+
+use bitcoin::{Address, PublicKey};
+use common::hex;
+use secp256k1::ffi::CPtr;
+use std::ffi::CString;
+use tw_bitcoin::aliases::*;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+use wallet_core_rs::ffi::bitcoin::transfer as transfer_ffi;
+
+#[test]
+fn ffi_simple_transfer_with_change() {
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let utxos = Proto::SimpleTransferUtxos {
+        utxos: (0..2)
+            .map(|vout| Proto::Input {
+                txid: txid.as_slice().into(),
+                vout,
+                value: 20_000,
+                sighash_type: UtxoProto::SighashType::All,
+                to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+                    variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+                }),
+                ..Default::default()
+            })
+            .collect(),
+    };
+
+    let alice_address = Address::p2wpkh(
+        &PublicKey::from_slice(&alice_pubkey).unwrap(),
+        bitcoin::Network::Bitcoin,
+    )
+    .unwrap()
+    .to_string();
+    let bob_address = Address::p2wpkh(
+        &PublicKey::from_slice(&bob_pubkey).unwrap(),
+        bitcoin::Network::Bitcoin,
+    )
+    .unwrap();
+
+    let recipients = Proto::SimpleTransferRecipients {
+        recipients: vec![Proto::mod_SimpleTransferRecipients::Recipient {
+            address: bob_address.to_string().into(),
+            amount: 15_000,
+        }],
+    };
+
+    let utxos = tw_proto::serialize(&utxos).unwrap();
+    let recipients = tw_proto::serialize(&recipients).unwrap();
+    let change_address = CString::new(alice_address.as_str()).unwrap();
+
+    let signed = unsafe {
+        transfer_ffi::tw_bitcoin_build_simple_transfer(
+            alice_private_key.as_c_ptr(),
+            alice_private_key.len(),
+            utxos.as_c_ptr(),
+            utxos.len(),
+            recipients.as_c_ptr(),
+            recipients.len(),
+            change_address.as_ptr(),
+            10,
+            Proto::Network::Bitcoin as i32,
+        )
+        .into_vec()
+    };
+    let signed: Proto::SigningOutput = tw_proto::deserialize(&signed).unwrap();
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // A single input covers the recipient, the rest is returned as change.
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.inputs.len(), 1);
+    assert_eq!(transaction.outputs.len(), 2);
+
+    assert_eq!(transaction.outputs[0].value, 15_000);
+    assert_eq!(
+        transaction.outputs[0].script_pubkey.as_ref(),
+        bob_address.script_pubkey().as_bytes()
+    );
+    assert_eq!(
+        transaction.outputs[1].value,
+        20_000 - 15_000 - signed.effective_fee
+    );
+    assert!(signed.effective_fee_rate >= 10.0);
+}
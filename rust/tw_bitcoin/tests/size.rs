@@ -0,0 +1,134 @@
+mod common;
+
+use common::{hex, P2WPKH_TX};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::size::{tx_size, weight_breakdown, witness_size};
+use tw_bitcoin::native::consensus::deserialize;
+use tw_bitcoin::native::Transaction;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+// A P2PKH transaction with one input and one output (see `p2pkh.rs`).
+const P2PKH_TX: &str = "02000000017be4e642bb278018ab12277de9427773ad1c5f5b1d164a157e0d99aa48dc1c1e000000006a473044022078eda020d4b86fcb3af78ef919912e6d79b81164dbbb0b0b96da6ac58a2de4b102201a5fd8d48734d5a02371c4b5ee551a69dca3842edbf577d863cf8ae9fdbbd4590121036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536ffffffff01c0aff629010000001976a9145eaaa4f458f9158f86afcba08dd7448d27045e3d88ac00000000";
+
+#[test]
+fn witness_size_p2wpkh() {
+    let tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+
+    let size = tx_size(&tx);
+    assert_eq!(size.full, hex(P2WPKH_TX).len() as u64);
+    assert!(size.stripped < size.full);
+    assert_ne!(witness_size(&tx), 0);
+}
+
+#[test]
+fn witness_size_legacy() {
+    let tx: Transaction = deserialize(&hex(P2PKH_TX)).unwrap();
+
+    let size = tx_size(&tx);
+    assert_eq!(size.full, hex(P2PKH_TX).len() as u64);
+    assert_eq!(size.stripped, size.full);
+    assert_eq!(witness_size(&tx), 0);
+}
+
+#[test]
+fn weight_breakdown_p2wpkh() {
+    let tx: Transaction = deserialize(&hex(P2WPKH_TX)).unwrap();
+
+    let breakdown = weight_breakdown(&tx);
+    assert_eq!(breakdown.base_bytes, tx.strippedsize() as u64);
+    assert_eq!(
+        breakdown.base_bytes + breakdown.witness_bytes,
+        hex(P2WPKH_TX).len() as u64
+    );
+
+    // The witness bytes are counted at a quarter of the weight of the base
+    // bytes.
+    assert_eq!(
+        breakdown.weight,
+        breakdown.base_bytes * 4 + breakdown.witness_bytes
+    );
+    assert_eq!(breakdown.weight, tx.weight().to_wu());
+    assert_eq!(breakdown.vsize, tx.vsize() as u64);
+    assert!(breakdown.vsize < breakdown.base_bytes + breakdown.witness_bytes);
+
+    // No witness discount for legacy transactions.
+    let tx: Transaction = deserialize(&hex(P2PKH_TX)).unwrap();
+
+    let breakdown = weight_breakdown(&tx);
+    assert_eq!(breakdown.witness_bytes, 0);
+    assert_eq!(breakdown.vsize, hex(P2PKH_TX).len() as u64);
+}
+
+#[test]
+fn input_sizes_of_signed_transaction() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 50_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+                one_prevout: false,
+                public_key: alice_pubkey.as_slice().into(),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let tx2 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 1,
+        value: 50_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2pkh(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 90_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1, tx2],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+    assert_eq!(signed.input_sizes.len(), 2);
+
+    // P2TR key-path: item count, length and Schnorr signature.
+    let p2tr = &signed.input_sizes[0];
+    assert_eq!(p2tr.script_sig, 0);
+    assert!((64..=66).contains(&p2tr.witness));
+
+    // P2PKH: length, ECDSA signature, length and public key.
+    let p2pkh = &signed.input_sizes[1];
+    assert!((105..=108).contains(&p2pkh.script_sig));
+    assert_eq!(p2pkh.witness, 0);
+}
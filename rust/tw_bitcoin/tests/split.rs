@@ -0,0 +1,82 @@
+mod common;
+
+use common::{hex, p2wpkh_utxo};
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::split::build;
+use tw_bitcoin::native::{PublicKey, ScriptBuf};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+
+fn destination_script() -> ScriptBuf {
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let bob_pubkey = PublicKey::from_slice(&bob_pubkey).unwrap();
+    ScriptBuf::new_v0_p2wpkh(&bob_pubkey.wpubkey_hash().unwrap())
+}
+
+#[test]
+fn split_into_equal_outputs() {
+    let coin = TestCoinContext::default();
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let mut signing = build(
+        p2wpkh_utxo(&alice_pubkey, 0, 100_000),
+        10,
+        &destination_script(),
+        3,
+    )
+    .unwrap();
+    assert_eq!(signing.outputs.len(), 10);
+
+    // The outputs differ by one satoshi at most.
+    let values: Vec<u64> = signing.outputs.iter().map(|output| output.value).collect();
+    assert!(values.iter().max().unwrap() - values.iter().min().unwrap() <= 1);
+
+    signing.private_key = alice_private_key.as_slice().into();
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx = signed.transaction.as_ref().unwrap();
+    assert_eq!(tx.outputs.len(), 10);
+
+    // The fee is based on the estimated weight of 1_554 (389 vbytes) at 3
+    // sat/vbyte, which covers the actual size of the signed transaction.
+    assert_eq!(signed.effective_fee, 1_167);
+    assert!(signed.effective_fee >= (signed.weight + 3) / 4 * 3);
+
+    // The remaining 98_833 sats are divided into 9_883 sats per output, with
+    // the remainder of 3 sats going to the first three outputs.
+    let paid: Vec<u64> = tx.outputs.iter().map(|output| output.value).collect();
+    assert_eq!(paid, values);
+    assert_eq!(&paid[..3], &[9_884; 3]);
+    assert_eq!(&paid[3..], &[9_883; 7]);
+    assert_eq!(paid.iter().sum::<u64>(), 100_000 - 1_167);
+}
+
+#[test]
+fn split_rejects_dust_outputs() {
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let err = build(
+        p2wpkh_utxo(&alice_pubkey, 0, 100_000),
+        1_000,
+        &destination_script(),
+        3,
+    )
+    .unwrap_err();
+    assert_eq!(Proto::Error::from(err), Proto::Error::Error_dust_output);
+
+    let err = build(
+        p2wpkh_utxo(&alice_pubkey, 0, 100_000),
+        0,
+        &destination_script(),
+        3,
+    )
+    .unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_missing_recipient
+    );
+}
@@ -0,0 +1,366 @@
+mod common;
+
+use common::{hex, MINER_FEE, ONE_BTC};
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::standardness::{
+    check_output_script, check_output_script_with_data_carrier_size, check_sigops, count_sigops,
+    DEFAULT_DATA_CARRIER_SIZE, MAX_STANDARD_TX_SIGOPS_COST,
+};
+use tw_bitcoin::native::absolute::LockTime;
+use tw_bitcoin::native::opcodes::all::*;
+use tw_bitcoin::native::script::PushBytesBuf;
+use tw_bitcoin::native::{
+    OutPoint, PublicKey, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+};
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn op_return_push_only() {
+    let script = ScriptBuf::builder()
+        .push_opcode(OP_RETURN)
+        .push_slice(b"hello")
+        .push_opcode(OP_PUSHNUM_1)
+        .into_script();
+
+    assert!(check_output_script(&script).is_ok());
+}
+
+#[test]
+fn op_return_non_push_opcode() {
+    let script = ScriptBuf::builder()
+        .push_opcode(OP_RETURN)
+        .push_slice(b"hello")
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+
+    let err = check_output_script(&script).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_non_standard_op_return
+    );
+}
+
+fn op_return_output(data: &[u8]) -> Proto::Output<'static> {
+    Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::op_return(data.to_vec().into()),
+        }),
+        ..Default::default()
+    }
+}
+
+// Convenience function, signs a P2WPKH transaction with an additional
+// OP_RETURN output carrying the given data.
+fn sign_with_op_return(data: &[u8]) -> Proto::SigningOutput<'static> {
+    sign_with_extra_output(Some(op_return_output(data)), 0)
+}
+
+// Convenience function, signs a P2WPKH transaction with the given additional
+// output (if any) at the given fee rate.
+fn sign_with_extra_output(
+    extra: Option<Proto::Output<'static>>,
+    fee_per_vb: u64,
+) -> Proto::SigningOutput<'static> {
+    let coin = TestCoinContext::default();
+    BitcoinEntry.sign(&coin, signing_with_extra_output(extra, fee_per_vb))
+}
+
+fn signing_with_extra_output(
+    extra: Option<Proto::Output<'static>>,
+    fee_per_vb: u64,
+) -> Proto::SigningInput<'static> {
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.into(),
+        vout: 0,
+        value: ONE_BTC,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: ONE_BTC - MINER_FEE,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    Proto::SigningInput {
+        private_key: alice_private_key.into(),
+        inputs: vec![tx1],
+        outputs: std::iter::once(out1).chain(extra).collect(),
+        input_selector: UtxoProto::InputSelector::UseAll,
+        fee_per_vb,
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn op_return_builder() {
+    let signed = sign_with_op_return(&[0xab; 80]);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    let script_pubkey = &transaction.outputs[1].script_pubkey;
+    // OP_RETURN OP_PUSHDATA1 <80 bytes>
+    assert_eq!(script_pubkey.len(), 83);
+    assert_eq!(script_pubkey[0], OP_RETURN.to_u8());
+}
+
+#[test]
+fn op_return_builder_too_large() {
+    let signed = sign_with_op_return(&[0xab; 81]);
+    assert_eq!(signed.error, Proto::Error::Error_non_standard_op_return);
+}
+
+#[test]
+fn op_return_data_carrier_size() {
+    let script = |size: usize| {
+        let data = PushBytesBuf::try_from(vec![0xab; size]).unwrap();
+        ScriptBuf::new_op_return(&data)
+    };
+    let check = |size: usize, data_carrier_size: usize| {
+        check_output_script_with_data_carrier_size(&script(size), data_carrier_size)
+            .map_err(Proto::Error::from)
+    };
+
+    // Disabled, any OP_RETURN is rejected.
+    assert_eq!(check(0, 0), Err(Proto::Error::Error_non_standard_op_return));
+    assert_eq!(check(1, 0), Err(Proto::Error::Error_non_standard_op_return));
+
+    // The default of 80 bytes.
+    assert!(check_output_script(&script(DEFAULT_DATA_CARRIER_SIZE)).is_ok());
+    assert_eq!(check(80, 80), Ok(()));
+    assert_eq!(
+        check(81, 80),
+        Err(Proto::Error::Error_non_standard_op_return)
+    );
+
+    // Relaxed policy.
+    assert_eq!(check(200, 200), Ok(()));
+    assert_eq!(
+        check(201, 200),
+        Err(Proto::Error::Error_non_standard_op_return)
+    );
+}
+
+#[test]
+fn op_return_builder_data_carrier_size() {
+    let coin = TestCoinContext::default();
+    let signing = |data_carrier_size: Option<u32>| Proto::SigningInput {
+        data_carrier_size: data_carrier_size.map(|value| Proto::DataCarrierSize { value }),
+        ..signing_with_extra_output(Some(op_return_output(&[0xab; 200])), 0)
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing(None));
+    assert_eq!(signed.error, Proto::Error::Error_non_standard_op_return);
+
+    let signed = BitcoinEntry.sign(&coin, signing(Some(200)));
+    assert_eq!(signed.error, Proto::Error::OK);
+    // OP_RETURN OP_PUSHDATA1 <200 bytes>
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(transaction.outputs[1].script_pubkey.len(), 203);
+
+    let signed = BitcoinEntry.sign(&coin, signing(Some(0)));
+    assert_eq!(signed.error, Proto::Error::Error_non_standard_op_return);
+}
+
+#[test]
+fn zero_value_outputs() {
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+
+    // Only data carrier outputs may have a zero value.
+    let zero_p2wpkh = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signed = sign_with_extra_output(Some(zero_p2wpkh), 10);
+    assert_eq!(signed.error, Proto::Error::Error_utxo_zero_value_output);
+
+    let zero_op_return = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::op_return(b"hello".to_vec().into()),
+        }),
+        ..Default::default()
+    };
+
+    let with_op_return = sign_with_extra_output(Some(zero_op_return), 10);
+    assert_eq!(with_op_return.error, Proto::Error::OK);
+
+    let without_op_return = sign_with_extra_output(None, 10);
+    assert_eq!(without_op_return.error, Proto::Error::OK);
+
+    // The OP_RETURN output does not contribute to the value balance, but its
+    // size is counted in the fee.
+    let outputs = &with_op_return.transaction.as_ref().unwrap().outputs;
+    assert_eq!(outputs[1].value, 0);
+    assert_eq!(
+        with_op_return.effective_fee,
+        without_op_return.effective_fee
+    );
+    assert!(with_op_return.weight > without_op_return.weight);
+    assert!(with_op_return.fee > without_op_return.fee);
+}
+
+// Convenience function, creates a 2-of-3 `OP_CHECKMULTISIG` script.
+fn multisig_script() -> ScriptBuf {
+    let pubkey = |pubkey: &str| PublicKey::from_slice(&hex(pubkey)).unwrap();
+
+    ScriptBuf::builder()
+        .push_opcode(OP_PUSHNUM_2)
+        .push_key(&pubkey(
+            "028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f",
+        ))
+        .push_key(&pubkey(
+            "025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f",
+        ))
+        .push_key(&pubkey(
+            "036666dd712e05a487916384bfcd5973eb53e8038eccbbf97f7eed775b87389536",
+        ))
+        .push_opcode(OP_PUSHNUM_3)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script()
+}
+
+// Convenience function, creates an input with the given scriptSig and witness
+// along with the spent output paying to the given scriptPubkey.
+fn sigops_input(
+    script_sig: ScriptBuf,
+    witness: Vec<Vec<u8>>,
+    script_pubkey: ScriptBuf,
+) -> (TxIn, TxOut) {
+    let txin = TxIn {
+        previous_output: OutPoint::null(),
+        script_sig,
+        sequence: Sequence::MAX,
+        witness: Witness::from_slice(&witness),
+    };
+    let prevout = TxOut {
+        value: ONE_BTC,
+        script_pubkey,
+    };
+
+    (txin, prevout)
+}
+
+#[test]
+fn sigops_multisig_inputs() {
+    let multisig = multisig_script();
+    let signature = vec![1; 72];
+    let push = |data: &[u8]| PushBytesBuf::try_from(data.to_vec()).unwrap();
+
+    // P2WSH 2-of-3: the witness script is counted accurately (3).
+    let p2wsh = || {
+        sigops_input(
+            ScriptBuf::new(),
+            vec![
+                vec![],
+                signature.clone(),
+                signature.clone(),
+                multisig.to_bytes(),
+            ],
+            ScriptBuf::new_v0_p2wsh(&multisig.wscript_hash()),
+        )
+    };
+
+    // P2SH 2-of-3: the redeem script is counted accurately, times four (12).
+    let script_sig = ScriptBuf::builder()
+        .push_opcode(OP_PUSHBYTES_0)
+        .push_slice(push(&signature))
+        .push_slice(push(&signature))
+        .push_slice(push(multisig.as_bytes()))
+        .into_script();
+    let p2sh = sigops_input(
+        script_sig,
+        vec![],
+        ScriptBuf::new_p2sh(&multisig.script_hash()),
+    );
+
+    // P2SH-P2WSH 2-of-3 (3).
+    let redeem_script = ScriptBuf::new_v0_p2wsh(&multisig.wscript_hash());
+    let p2sh_p2wsh = sigops_input(
+        ScriptBuf::builder()
+            .push_slice(push(redeem_script.as_bytes()))
+            .into_script(),
+        vec![
+            vec![],
+            signature.clone(),
+            signature.clone(),
+            multisig.to_bytes(),
+        ],
+        ScriptBuf::new_p2sh(&redeem_script.script_hash()),
+    );
+
+    let (inputs, prevouts): (Vec<TxIn>, Vec<TxOut>) = std::iter::repeat_with(p2wsh)
+        .take(10)
+        .chain([p2sh, p2sh_p2wsh])
+        .unzip();
+
+    // A bare multisig output: the number of public keys is not evaluated, so
+    // it counts as 20, times four (80).
+    let bare_multisig = TxOut {
+        value: ONE_BTC,
+        script_pubkey: multisig.clone(),
+    };
+
+    let mut tx = Transaction {
+        version: 2,
+        lock_time: LockTime::ZERO,
+        input: inputs,
+        output: vec![bare_multisig.clone()],
+    };
+
+    assert_eq!(count_sigops(&tx, &prevouts).unwrap(), 10 * 3 + 12 + 3 + 80);
+    assert!(check_sigops(&tx, &prevouts).is_ok());
+
+    // Many bare multisig outputs: 200 are exactly at the limit, the sigops of
+    // any input exceed it.
+    let (p2wsh_input, p2wsh_prevout) = p2wsh();
+
+    tx.input = vec![];
+    tx.output = vec![bare_multisig; 200];
+    assert_eq!(count_sigops(&tx, &[]).unwrap(), MAX_STANDARD_TX_SIGOPS_COST);
+    assert!(check_sigops(&tx, &[]).is_ok());
+
+    tx.input = vec![p2wsh_input];
+    let prevouts = [p2wsh_prevout];
+    assert_eq!(
+        count_sigops(&tx, &prevouts).unwrap(),
+        MAX_STANDARD_TX_SIGOPS_COST + 3
+    );
+
+    let err = check_sigops(&tx, &prevouts).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_non_standard_sigops
+    );
+
+    // A prevout is required for every input.
+    assert!(count_sigops(&tx, &[]).is_err());
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use bitcoin::absolute::LockTime;
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::{ecdsa, Message, Secp256k1};
+use bitcoin::{PublicKey, Transaction};
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::swap::{refund_tx, HtlcUtxo};
+use tw_bitcoin::modules::transactions::Htlc;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+const EXPIRY: u32 = 800_000;
+
+#[test]
+fn swap_refund_via_timeout_path() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    // Alice is the sender of the HTLC, Bob the recipient.
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+
+    let payment_hash = sha256::Hash::hash(b"swap secret");
+    let htlc = Htlc::new(
+        payment_hash,
+        PublicKey::from_slice(&bob_pubkey).unwrap(),
+        PublicKey::from_slice(&alice_pubkey).unwrap(),
+        EXPIRY,
+    );
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let htlc_utxo = HtlcUtxo {
+        txid,
+        vout: 0,
+        value: 100_000,
+        payment_hash: payment_hash.to_byte_array().to_vec(),
+        recipient_pubkey: bob_pubkey,
+    };
+
+    let destination = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.clone().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let mut signing = refund_tx(htlc_utxo, &alice_pubkey, EXPIRY, destination, 10);
+    signing.private_key = alice_private_key.into();
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+
+    // The lock time satisfies the CLTV and is enabled by the input sequence.
+    assert_eq!(tx.lock_time, LockTime::from_height(EXPIRY).unwrap());
+    assert!(tx.input[0].sequence.enables_absolute_lock_time());
+
+    // The destination receives the HTLC value minus the fee.
+    assert_eq!(tx.output.len(), 1);
+    assert_eq!(tx.output[0].value, 100_000 - signed.effective_fee);
+
+    // Witness: signature, empty item (`OP_ELSE` branch), witness script.
+    let items: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+    assert_eq!(items.len(), 3);
+    assert!(items[1].is_empty());
+    assert_eq!(items[2], htlc.witness_script().as_bytes());
+    assert_eq!(
+        presigned.utxo_inputs[0].script_pubkey.as_ref(),
+        htlc.witness_script().as_bytes()
+    );
+
+    // The (refund) signature is valid for Alice's key.
+    let sig = ecdsa::Signature::from_der(&items[0][..items[0].len() - 1]).unwrap();
+    let msg = Message::from_slice(presigned.sighashes[0].sighash.as_ref()).unwrap();
+    let alice = PublicKey::from_slice(&alice_pubkey).unwrap();
+    secp.verify_ecdsa(&msg, &sig, &alice.inner).unwrap();
+
+    // The HTLC can not be refunded before the expiry.
+    signing.lock_time = Some(UtxoProto::LockTime {
+        variant: UtxoProto::mod_LockTime::OneOfvariant::blocks(EXPIRY - 1),
+    });
+    let signed = BitcoinEntry.sign(&coin, signing.clone());
+    assert_eq!(signed.error, Proto::Error::Error_htlc_not_expired);
+
+    // A final sequence disables the expiry.
+    signing.lock_time = Some(UtxoProto::LockTime {
+        variant: UtxoProto::mod_LockTime::OneOfvariant::blocks(EXPIRY),
+    });
+    signing.inputs[0].sequence = u32::MAX;
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::Error_htlc_final_sequence);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::sweep::build_signing_input;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn sweep_except_inscriptions() {
+    let coin = TestCoinContext::default();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let bob_pubkey = hex("025a0af1510f0f24d40dd00d7c0e51605ca504bbc177c3e19b065f373a1efdd22f");
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    let utxo = |vout: u32, value: u64| Proto::Input {
+        txid: txid.clone().into(),
+        vout,
+        value,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.clone().into()),
+        }),
+        ..Default::default()
+    };
+
+    // The second and fourth UTXO carry an inscription and runes, respectively.
+    let utxos = vec![
+        utxo(0, 20_000),
+        utxo(1, 546),
+        utxo(2, 30_000),
+        utxo(3, 10_000),
+    ];
+
+    let destination = Proto::Output {
+        value: 0,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(bob_pubkey.into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let mut signing = build_signing_input(utxos, &[1, 3], destination, 5);
+    assert_eq!(signing.inputs.len(), 2);
+    assert_eq!(signing.inputs[0].vout, 0);
+    assert_eq!(signing.inputs[1].vout, 2);
+
+    signing.private_key = alice_private_key.as_slice().into();
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    // Only the spendable balance is swept.
+    let tx = signed.transaction.as_ref().unwrap();
+    assert_eq!(tx.inputs.len(), 2);
+    assert!(tx
+        .inputs
+        .iter()
+        .all(|input| input.vout != 1 && input.vout != 3));
+    assert_eq!(tx.outputs.len(), 1);
+    assert_eq!(tx.outputs[0].value, 50_000 - signed.effective_fee);
+    assert!(signed.effective_fee > 0);
+}
@@ -0,0 +1,96 @@
+use tw_bitcoin::modules::transactions::TaprootMultisig;
+use tw_bitcoin::native::opcodes::all::*;
+use tw_bitcoin::native::script::Instruction;
+use tw_bitcoin::native::ScriptBuf;
+use tw_bitcoin::secp256k1::{KeyPair, Secp256k1, XOnlyPublicKey};
+use tw_proto::BitcoinV2::Proto;
+
+// Convenience function, derives the x-only public key of the given secret.
+fn xonly(secret: u8) -> XOnlyPublicKey {
+    let secp = Secp256k1::new();
+    let keypair = KeyPair::from_seckey_slice(&secp, &[secret; 32]).unwrap();
+    keypair.x_only_public_key().0
+}
+
+#[test]
+fn taproot_multisig_2_of_4_leaf() {
+    let pubkeys = vec![xonly(1), xonly(2), xonly(3), xonly(4)];
+    let multisig = TaprootMultisig::new(2, pubkeys.clone(), xonly(5)).unwrap();
+
+    let opcodes: Vec<_> = multisig
+        .leaf_script()
+        .instructions()
+        .map(|ins| ins.unwrap())
+        .filter_map(|ins| match ins {
+            Instruction::Op(op) => Some(op),
+            Instruction::PushBytes(_) => None,
+        })
+        .collect();
+
+    assert_eq!(
+        opcodes,
+        vec![
+            OP_CHECKSIG,
+            OP_CHECKSIGADD,
+            OP_CHECKSIGADD,
+            OP_CHECKSIGADD,
+            OP_PUSHNUM_2,
+            OP_NUMEQUAL
+        ]
+    );
+
+    // The control block commits to the leaf.
+    let secp = Secp256k1::new();
+    let output_key = multisig.spend_info().output_key();
+    assert!(multisig.control_block().verify_taproot_commitment(
+        &secp,
+        output_key.to_inner(),
+        multisig.leaf_script()
+    ));
+}
+
+#[test]
+fn taproot_multisig_2_of_4_witness() {
+    let pubkeys = vec![xonly(1), xonly(2), xonly(3), xonly(4)];
+    let multisig = TaprootMultisig::new(2, pubkeys, xonly(5)).unwrap();
+
+    // Keys one and three sign.
+    let sig1 = vec![1; 64];
+    let sig3 = vec![3; 64];
+    let signatures = vec![Some(sig1.clone()), None, Some(sig3.clone()), None];
+
+    let witness = multisig.witness(&signatures).unwrap();
+    let items: Vec<&[u8]> = witness.iter().collect();
+
+    // The signature of the first key is on top of the stack, followed by the
+    // leaf script and the control block.
+    assert_eq!(items.len(), 6);
+    assert!(items[0].is_empty());
+    assert_eq!(items[1], sig3.as_slice());
+    assert!(items[2].is_empty());
+    assert_eq!(items[3], sig1.as_slice());
+    assert_eq!(
+        ScriptBuf::from_bytes(items[4].to_vec()).as_script(),
+        multisig.leaf_script()
+    );
+    assert_eq!(items[5], multisig.control_block().serialize().as_slice());
+
+    // Exactly the threshold number of signers is required.
+    let signatures = vec![Some(sig1.clone()), None, None, None];
+    let err = multisig.witness(&signatures).unwrap_err();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_multisig_threshold
+    );
+
+    let signatures = vec![Some(sig1.clone()), Some(sig1), Some(sig3), None];
+    assert!(multisig.witness(&signatures).is_err());
+}
+
+#[test]
+fn taproot_multisig_invalid_threshold() {
+    let pubkeys = vec![xonly(1), xonly(2)];
+
+    assert!(TaprootMultisig::new(0, pubkeys.clone(), xonly(5)).is_err());
+    assert!(TaprootMultisig::new(3, pubkeys, xonly(5)).is_err());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::modules::transactions::{taproot_keypair, taproot_output_key, tweak_output_key};
+use tw_bitcoin::native::key::TapTweak;
+use tw_bitcoin::secp256k1::{KeyPair, Scalar, Secp256k1, XOnlyPublicKey};
+use tw_proto::BitcoinV2::Proto;
+
+// The x-only public key of the secp256k1 generator point `G`, which has an
+// even y-coordinate.
+const GENERATOR: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+// The curve order minus one, i.e. the secret key of `-G`.
+const ORDER_MINUS_ONE: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140";
+
+#[test]
+fn taproot_output_key_matches_tap_tweak() {
+    let internal_key = XOnlyPublicKey::from_slice(&hex(GENERATOR)).unwrap();
+
+    let output_key = taproot_output_key(internal_key, None).unwrap();
+    let expected = internal_key.tap_tweak(&Secp256k1::new(), None).0;
+    assert_eq!(output_key, expected);
+}
+
+#[test]
+fn taproot_keypair_matches_output_key() {
+    let secp = Secp256k1::new();
+
+    // The public key `-G` has an odd y-coordinate, so the secret key is
+    // negated before tweaking.
+    let keypair = KeyPair::from_seckey_slice(&secp, &hex(ORDER_MINUS_ONE)).unwrap();
+    let (internal_key, _) = keypair.x_only_public_key();
+    assert_eq!(
+        internal_key,
+        XOnlyPublicKey::from_slice(&hex(GENERATOR)).unwrap()
+    );
+
+    let tweaked = taproot_keypair(&keypair, None).unwrap();
+    let output_key = taproot_output_key(internal_key, None).unwrap();
+    assert_eq!(tweaked.x_only_public_key().0, output_key.to_inner());
+    assert_eq!(tweaked, KeyPair::from(keypair.tap_tweak(&secp, None)));
+}
+
+#[test]
+fn taproot_output_key_invalid_tweak() {
+    let internal_key = XOnlyPublicKey::from_slice(&hex(GENERATOR)).unwrap();
+
+    // `G + (n - 1)G` is the point at infinity, which has no x-only encoding.
+    let tweak = Scalar::from_be_bytes(hex(ORDER_MINUS_ONE).try_into().unwrap()).unwrap();
+    let err = tweak_output_key(internal_key, tweak).unwrap_err();
+
+    assert!(err.to_string().contains("invalid output key"));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_taproot_tweaked_pubkey
+    );
+
+    // Any other tweak results in a valid output key.
+    let tweak = Scalar::from_be_bytes([1; 32]).unwrap();
+    assert!(tweak_output_key(internal_key, tweak).is_ok());
+}
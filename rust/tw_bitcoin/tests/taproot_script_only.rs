@@ -0,0 +1,173 @@
+mod common;
+
+use bitcoin::consensus::Decodable;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::secp256k1::{schnorr, Message, PublicKey, Secp256k1, SecretKey};
+use bitcoin::{ScriptBuf, Transaction};
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::entry::BitcoinEntry;
+use tw_bitcoin::modules::transactions::{TaprootScriptOnly, NUMS_INTERNAL_KEY};
+use tw_bitcoin::secp256k1::KeyPair;
+use tw_coin_entry::coin_entry::CoinEntry;
+use tw_coin_entry::test_utils::test_context::TestCoinContext;
+use tw_proto::BitcoinV2::Proto;
+use tw_proto::Utxo::Proto as UtxoProto;
+
+#[test]
+fn taproot_script_only_nums_internal_key() {
+    let secp = Secp256k1::new();
+
+    // The NUMS point is `lift_x(SHA256(G))`, as specified by BIP-341.
+    let mut one = [0; 32];
+    one[31] = 1;
+    let generator = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&one).unwrap());
+    let hashed = sha256::Hash::hash(&generator.serialize_uncompressed());
+    assert_eq!(hashed.as_byte_array(), &NUMS_INTERNAL_KEY);
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let keypair = KeyPair::from_seckey_slice(&secp, &alice_private_key).unwrap();
+    let alice_xonly = keypair.x_only_public_key().0;
+
+    let script_only = TaprootScriptOnly::new(alice_xonly);
+    let spend_info = script_only.spend_info();
+
+    // The output commits to the NUMS point, not to the leaf key. Hence there is
+    // no key-path spend available to the owner of the leaf key.
+    assert_eq!(spend_info.internal_key(), TaprootScriptOnly::internal_key());
+    assert_ne!(
+        script_only.script_pubkey(),
+        ScriptBuf::new_v1_p2tr(&secp, alice_xonly, None)
+    );
+    assert_ne!(
+        script_only.script_pubkey(),
+        ScriptBuf::new_v1_p2tr(&secp, alice_xonly, Some(script_only.merkle_root()))
+    );
+
+    // The control block commits to the single leaf.
+    assert!(script_only.control_block().verify_taproot_commitment(
+        &secp,
+        spend_info.output_key().to_inner(),
+        script_only.leaf_script()
+    ));
+}
+
+#[test]
+fn taproot_script_only_spend_via_script_path() {
+    let coin = TestCoinContext::default();
+    let secp = Secp256k1::new();
+
+    let alice_private_key = hex("57a64865bce5d4855e99b1cce13327c46171434f2d72eeaf9da53ee075e7f90a");
+    let alice_pubkey = hex("028d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f");
+    let keypair = KeyPair::from_seckey_slice(&secp, &alice_private_key).unwrap();
+    let alice_xonly = keypair.x_only_public_key().0;
+
+    let script_only = TaprootScriptOnly::new(alice_xonly);
+
+    let txid: Vec<u8> = hex("181c84965c9ea86a5fac32fdbd5f73a21a7a9e749fb6ab97e273af2329f6b911")
+        .into_iter()
+        .rev()
+        .collect();
+
+    // Create the script-only output.
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 50_000,
+        sighash_type: UtxoProto::SighashType::All,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2wpkh(alice_pubkey.as_slice().into()),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 40_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2tr_script_path(
+                Proto::mod_Output::OutputTaprootScriptPath {
+                    internal_key: TaprootScriptOnly::internal_key_compressed().to_vec().into(),
+                    merkle_root: script_only.merkle_root().to_byte_array().to_vec().into(),
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let transaction = signed.transaction.unwrap();
+    assert_eq!(
+        transaction.outputs[0].script_pubkey.as_ref(),
+        script_only.script_pubkey().as_bytes()
+    );
+
+    // Spend the script-only output via the script-path.
+    let txid: Vec<u8> = signed.txid.into_iter().rev().collect();
+
+    let tx1 = Proto::Input {
+        txid: txid.as_slice().into(),
+        vout: 0,
+        value: 40_000,
+        sighash_type: UtxoProto::SighashType::UseDefault,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder {
+            variant: ProtoInputBuilder::p2tr_script_path(
+                Proto::mod_Input::InputTaprootScriptPath {
+                    one_prevout: false,
+                    payload: script_only.leaf_script().to_bytes().into(),
+                    control_block: script_only.control_block().serialize().into(),
+                    codesep_pos: None,
+                },
+            ),
+        }),
+        ..Default::default()
+    };
+
+    let out1 = Proto::Output {
+        value: 30_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
+            variant: ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+                to_address: ProtoPubkeyOrHash::pubkey(alice_pubkey.as_slice().into()),
+            }),
+        }),
+        ..Default::default()
+    };
+
+    let signing = Proto::SigningInput {
+        private_key: alice_private_key.as_slice().into(),
+        inputs: vec![tx1],
+        outputs: vec![out1],
+        input_selector: UtxoProto::InputSelector::UseAll,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let presigned = BitcoinEntry.preimage_hashes(&coin, signing.clone());
+    assert_eq!(presigned.error, Proto::Error::OK);
+
+    let signed = BitcoinEntry.sign(&coin, signing);
+    assert_eq!(signed.error, Proto::Error::OK);
+
+    let tx = Transaction::consensus_decode(&mut signed.encoded.as_ref()).unwrap();
+    let items: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+
+    // Witness: signature, leaf script, control block.
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1], script_only.leaf_script().as_bytes());
+    assert_eq!(items[2], script_only.control_block().serialize().as_slice());
+
+    // The signature satisfies `<pubkey> OP_CHECKSIG` of the leaf.
+    let sig = schnorr::Signature::from_slice(items[0]).unwrap();
+    let msg = Message::from_slice(presigned.sighashes[0].sighash.as_ref()).unwrap();
+    secp.verify_schnorr(&sig, &msg, &alice_xonly).unwrap();
+}
@@ -0,0 +1,104 @@
+use tw_bitcoin::modules::transactions::{check_tapscript, TaprootTree};
+use tw_bitcoin::native::opcodes::all::*;
+use tw_bitcoin::native::ScriptBuf;
+use tw_bitcoin::secp256k1::{KeyPair, Secp256k1, XOnlyPublicKey};
+use tw_proto::BitcoinV2::Proto;
+
+// Convenience function, derives the x-only public key of the given secret.
+fn xonly(secret: u8) -> XOnlyPublicKey {
+    let secp = Secp256k1::new();
+    let keypair = KeyPair::from_seckey_slice(&secp, &[secret; 32]).unwrap();
+    keypair.x_only_public_key().0
+}
+
+// Convenience function, creates the leaves of a maximally unbalanced tree
+// with the given depth, in depth-first order. The two leaves at the bottom are
+// at the given depth.
+fn unbalanced_leaves(depth: u8) -> Vec<(u8, ScriptBuf)> {
+    let leaf = |index: u32| ScriptBuf::builder().push_int(index as i64).into_script();
+
+    let mut leaves = vec![(depth, leaf(0))];
+    leaves.extend((1..=depth).rev().map(|d| (d, leaf(d as u32))));
+    leaves
+}
+
+#[test]
+fn taproot_tree_max_depth() {
+    let leaves = unbalanced_leaves(128);
+    let deepest = leaves[0].1.clone();
+    let tree = TaprootTree::new(leaves, xonly(1)).unwrap();
+    assert_eq!(tree.leaves().len(), 129);
+
+    // The control block consists of the header, the internal key and one
+    // node for each level.
+    let control_block = tree.control_block(&deepest).unwrap();
+    assert_eq!(control_block.serialize().len(), 33 + 32 * 128);
+
+    let secp = Secp256k1::new();
+    let output_key = tree.spend_info().output_key();
+    assert!(control_block.verify_taproot_commitment(&secp, output_key.to_inner(), &deepest));
+}
+
+#[test]
+fn taproot_tree_too_deep() {
+    let err = TaprootTree::new(unbalanced_leaves(129), xonly(1))
+        .err()
+        .unwrap();
+    assert!(err
+        .to_string()
+        .contains("leaf at depth 129 exceeds the maximum depth of 128"));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_taproot_tree
+    );
+}
+
+#[test]
+fn taproot_tree_incomplete() {
+    let leaves = vec![(1, ScriptBuf::builder().push_int(1).into_script())];
+    let err = TaprootTree::new(leaves, xonly(1)).err().unwrap();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_taproot_tree
+    );
+}
+
+#[test]
+fn taproot_tree_disabled_opcode() {
+    // A 2-of-2 multisig leaf, as in legacy scripts.
+    let checkmultisig = ScriptBuf::builder()
+        .push_opcode(OP_PUSHNUM_2)
+        .push_x_only_key(&xonly(1))
+        .push_x_only_key(&xonly(2))
+        .push_opcode(OP_PUSHNUM_2)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+
+    // The same 2-of-2 multisig leaf in tapscript.
+    let checksigadd = ScriptBuf::builder()
+        .push_x_only_key(&xonly(1))
+        .push_opcode(OP_CHECKSIG)
+        .push_x_only_key(&xonly(2))
+        .push_opcode(OP_CHECKSIGADD)
+        .push_opcode(OP_PUSHNUM_2)
+        .push_opcode(OP_NUMEQUAL)
+        .into_script();
+
+    let err = check_tapscript(&checkmultisig).unwrap_err();
+    assert!(err.to_string().contains("use OP_CHECKSIGADD instead"));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_tapscript
+    );
+    check_tapscript(&checksigadd).unwrap();
+
+    // The tree is checked before building.
+    let err = TaprootTree::new(vec![(0, checkmultisig)], xonly(3))
+        .err()
+        .unwrap();
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_tapscript
+    );
+    assert!(TaprootTree::new(vec![(0, checksigadd)], xonly(3)).is_ok());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::modules::transactions::{InputBuilder, OutputBuilder};
+use tw_proto::BitcoinV2::Proto;
+
+// The uncompressed form of Alice's public key `028d7dce...ba28f`.
+const UNCOMPRESSED: &str = "048d7dce6d72fb8f7af9566616c6436349c67ad379f2404dd66fe7085fe0fba28f2c2ef1572b87738c732c66d4a3770d41bb1bda428ce96b0c9829243a0a6a96ae";
+
+fn output(variant: ProtoOutputBuilder<'static>) -> Proto::Output<'static> {
+    Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder { variant }),
+        ..Default::default()
+    }
+}
+
+fn input(variant: ProtoInputBuilder<'static>) -> Proto::Input<'static> {
+    Proto::Input {
+        txid: vec![1; 32].into(),
+        value: 1_000,
+        to_recipient: ProtoInputRecipient::builder(Proto::mod_Input::InputBuilder { variant }),
+        ..Default::default()
+    }
+}
+
+// Convenience function, asserts the descriptive error of an uncompressed key.
+fn assert_uncompressed_error(result: tw_bitcoin::Result<impl Sized>) {
+    let Err(err) = result else {
+        panic!("expected the uncompressed public key to be rejected");
+    };
+
+    assert!(err
+        .to_string()
+        .contains("require a compressed (33-byte) public key"));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_uncompressed_public_key
+    );
+}
+
+#[test]
+fn uncompressed_key_rejected_by_segwit_output_builders() {
+    let pubkey = hex(UNCOMPRESSED);
+
+    let outputs = [
+        ProtoOutputBuilder::p2wpkh(Proto::ToPublicKeyOrHash {
+            to_address: ProtoPubkeyOrHash::pubkey(pubkey.clone().into()),
+        }),
+        ProtoOutputBuilder::p2tr_key_path(pubkey.clone().into()),
+        ProtoOutputBuilder::p2tr_script_path(Proto::mod_Output::OutputTaprootScriptPath {
+            internal_key: pubkey.clone().into(),
+            merkle_root: vec![2; 32].into(),
+        }),
+        ProtoOutputBuilder::p2tr_pay_to_contract(Proto::PayToContract {
+            public_key: pubkey.clone().into(),
+            contract: b"contract".to_vec().into(),
+            ..Default::default()
+        }),
+        ProtoOutputBuilder::cltv_vault(Proto::mod_Output::OutputCltvVault {
+            expiry: 800_000,
+            public_key: pubkey.clone().into(),
+        }),
+    ];
+
+    for variant in outputs {
+        assert_uncompressed_error(OutputBuilder::utxo_from_proto(&output(variant)));
+    }
+
+    // Legacy P2PKH outputs still accept uncompressed public keys.
+    let p2pkh = output(ProtoOutputBuilder::p2pkh(Proto::ToPublicKeyOrHash {
+        to_address: ProtoPubkeyOrHash::pubkey(pubkey.into()),
+    }));
+    assert!(OutputBuilder::utxo_from_proto(&p2pkh).is_ok());
+}
+
+#[test]
+fn uncompressed_key_rejected_by_segwit_input_builders() {
+    let pubkey = hex(UNCOMPRESSED);
+
+    let inputs = [
+        ProtoInputBuilder::p2wpkh(pubkey.clone().into()),
+        ProtoInputBuilder::p2tr_key_path(Proto::mod_Input::InputTaprootKeyPath {
+            public_key: pubkey.clone().into(),
+            one_prevout: false,
+        }),
+        ProtoInputBuilder::p2tr_untweaked_key_path(pubkey.clone().into()),
+        ProtoInputBuilder::p2tr_pay_to_contract(Proto::PayToContract {
+            public_key: pubkey.clone().into(),
+            contract: b"contract".to_vec().into(),
+            ..Default::default()
+        }),
+        ProtoInputBuilder::cltv_vault(Proto::mod_Input::InputCltvVault {
+            expiry: 800_000,
+            public_key: pubkey.clone().into(),
+        }),
+    ];
+
+    for variant in inputs {
+        assert_uncompressed_error(InputBuilder::utxo_from_proto(&input(variant)));
+    }
+
+    // Legacy P2PKH inputs still accept uncompressed public keys.
+    let p2pkh = input(ProtoInputBuilder::p2pkh(pubkey.into()));
+    assert!(InputBuilder::utxo_from_proto(&p2pkh).is_ok());
+}
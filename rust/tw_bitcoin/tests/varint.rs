@@ -0,0 +1,42 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::modules::varint::{decode_leb128, encode_leb128};
+
+#[test]
+fn varint_leb128_round_trip() {
+    let vectors = [
+        (0, "00"),
+        (127, "7f"),
+        (128, "8001"),
+        (840_000, "c0a233"),
+        (u128::MAX, "ffffffffffffffffffffffffffffffffffff03"),
+    ];
+
+    for (value, encoded) in vectors {
+        let encoded = hex(encoded);
+        assert_eq!(encode_leb128(value), encoded);
+        assert_eq!(decode_leb128(&encoded), Some((value, encoded.len())));
+    }
+
+    // Only the first integer is decoded.
+    assert_eq!(decode_leb128(&hex("8001e807")), Some((128, 2)));
+}
+
+#[test]
+fn varint_leb128_invalid() {
+    // Truncated.
+    assert_eq!(decode_leb128(&[]), None);
+    assert_eq!(decode_leb128(&hex("80")), None);
+
+    // Overflows a `u128`, either by the bits of the last byte or by the number
+    // of bytes.
+    assert_eq!(
+        decode_leb128(&hex("ffffffffffffffffffffffffffffffffffff04")),
+        None
+    );
+    assert_eq!(
+        decode_leb128(&hex("ffffffffffffffffffffffffffffffffffff8300")),
+        None
+    );
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::hex;
+use tw_bitcoin::aliases::*;
+use tw_bitcoin::modules::transactions::OutputBuilder;
+use tw_proto::BitcoinV2::Proto;
+
+fn custom_output(script_pubkey: &str) -> Proto::Output<'static> {
+    Proto::Output {
+        value: 1_000,
+        to_recipient: ProtoOutputRecipient::custom_script_pubkey(hex(script_pubkey).into()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn witness_program_v0_invalid_length() {
+    // OP_0 <21 bytes>
+    let output = custom_output("0015e4b8cc50b1591d437ace6158d22ba4b063fc325700");
+
+    let err = OutputBuilder::utxo_from_proto(&output).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("a version 0 witness program of 21 bytes is unspendable"));
+    assert_eq!(
+        Proto::Error::from(err),
+        Proto::Error::Error_invalid_witness_program
+    );
+}
+
+#[test]
+fn witness_program_valid() {
+    for script_pubkey in [
+        // P2WPKH
+        "0014e4b8cc50b1591d437ace6158d22ba4b063fc3257",
+        // P2WSH
+        "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+        // P2TR
+        "51201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262",
+        // P2A, a version 1 program of 2 bytes.
+        "51024e73",
+        // Not a witness program, P2PKH.
+        "76a914e4b8cc50b1591d437ace6158d22ba4b063fc325788ac",
+    ] {
+        let utxo = OutputBuilder::utxo_from_proto(&custom_output(script_pubkey)).unwrap();
+        assert_eq!(utxo.script_pubkey.as_ref(), hex(script_pubkey).as_slice());
+    }
+}
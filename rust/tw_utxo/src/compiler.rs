@@ -60,8 +60,9 @@ impl Compiler<StandardBitcoinContext> {
         // TODO: Check for duplicate Txid (user error).
 
         // Calculate total outputs amount, based on it we can determine how many inputs to select.
-        let total_input: u64 = proto.inputs.iter().map(|input| input.value).sum();
-        let total_output: u64 = proto.outputs.iter().map(|output| output.value).sum();
+        // Note that a (maliciously) huge value must not wrap the sum.
+        let total_input = checked_total(proto.inputs.iter().map(|input| input.value))?;
+        let total_output = checked_total(proto.outputs.iter().map(|output| output.value))?;
 
         // Do some easy checks first.
 
@@ -70,6 +71,15 @@ impl Compiler<StandardBitcoinContext> {
             return Err(Error::from(Proto::Error::Error_insufficient_inputs));
         }
 
+        // Only data carrier (OP_RETURN) outputs may have a zero value. Note
+        // that those do not contribute to the value balance, but their size is
+        // counted in the fee.
+        if proto.outputs.iter().any(|output| {
+            output.value == 0 && !Script::from_bytes(output.script_pubkey.as_ref()).is_op_return()
+        }) {
+            return Err(Error::from(Proto::Error::Error_zero_value_output));
+        }
+
         // Change scriptPubkey must be set if change output is enabled.
         if !proto.disable_change_output && proto.change_script_pubkey.is_empty() {
             return Err(Error::from(
@@ -77,6 +87,50 @@ impl Compiler<StandardBitcoinContext> {
             ));
         }
 
+        // If the input selector is InputSelector::SelectChangeless, we search
+        // for a subset of inputs that does not require a change output. If
+        // there is none, we fall back to InputSelector::SelectAscending.
+        let mut changeless = false;
+        if let Proto::InputSelector::SelectChangeless = proto.input_selector {
+            if let Some(indices) = select_changeless(&proto, total_output)? {
+                proto.inputs = indices
+                    .into_iter()
+                    .map(|index| proto.inputs[index].clone())
+                    .collect();
+                proto.input_selector = Proto::InputSelector::UseAll;
+                changeless = true;
+            } else {
+                proto.input_selector = Proto::InputSelector::SelectAscending;
+            }
+        }
+
+        // If the input selector is InputSelector::SelectTargetChange, we search
+        // for a subset of inputs whose change amount is closest to the target.
+        // If there is none, we fall back to InputSelector::SelectAscending.
+        if let Proto::InputSelector::SelectTargetChange = proto.input_selector {
+            // Without a target, the search would aim for the smallest change,
+            // possibly dust.
+            if proto.target_change == 0 {
+                return Err(Error::from(Proto::Error::Error_missing_target_change));
+            }
+
+            let indices = if proto.disable_change_output {
+                None
+            } else {
+                select_target_change(&proto, total_output)?
+            };
+
+            if let Some(indices) = indices {
+                proto.inputs = indices
+                    .into_iter()
+                    .map(|index| proto.inputs[index].clone())
+                    .collect();
+                proto.input_selector = Proto::InputSelector::UseAll;
+            } else {
+                proto.input_selector = Proto::InputSelector::SelectAscending;
+            }
+        }
+
         // If the input selector is InputSelector::SelectAscending, we sort the
         // input first.
         if let Proto::InputSelector::SelectAscending = proto.input_selector {
@@ -86,101 +140,162 @@ impl Compiler<StandardBitcoinContext> {
         // Unless InputSelector::UseAll is provided, we only use the necessariy
         // amount of inputs to cover `total_output`. Any other input gets
         // dropped.
-        let selected = if let Proto::InputSelector::SelectInOrder
-        | Proto::InputSelector::SelectAscending = proto.input_selector
-        {
-            let mut total_input = total_input;
+        let is_selecting = matches!(
+            proto.input_selector,
+            Proto::InputSelector::SelectInOrder | Proto::InputSelector::SelectAscending
+        );
+
+        let mut available: Vec<Proto::TxIn> = proto
+            .inputs
+            .into_iter()
+            .map(|input| Proto::TxIn {
+                txid: input.txid.to_vec().into(),
+                script_pubkey: input.script_pubkey.to_vec().into(),
+                leaf_hash: input.leaf_hash.to_vec().into(),
+                ..input
+            })
+            .collect();
+
+        let mut selected_count = if is_selecting {
             let mut remaining = total_output;
 
-            let selected: Vec<Proto::TxIn> = proto
-                .inputs
-                .into_iter()
+            available
+                .iter()
                 .take_while(|input| {
                     if remaining == 0 {
                         return false;
                     }
 
-                    total_input += input.value;
                     remaining = remaining.saturating_sub(input.value);
 
                     true
                 })
-                .map(|input| Proto::TxIn {
-                    txid: input.txid.to_vec().into(),
-                    script_pubkey: input.script_pubkey.to_vec().into(),
-                    leaf_hash: input.leaf_hash.to_vec().into(),
-                    ..input
-                })
-                .collect();
-
-            selected
+                .count()
         } else {
-            // TODO: Write a function for this
-            proto
-                .inputs
-                .into_iter()
-                .map(|input| Proto::TxIn {
-                    txid: input.txid.to_vec().into(),
-                    script_pubkey: input.script_pubkey.to_vec().into(),
-                    leaf_hash: input.leaf_hash.to_vec().into(),
-                    ..input
-                })
-                .collect()
+            available.len()
         };
 
-        // Update protobuf structure with selected inputs.
-        proto.inputs = selected.clone();
+        // Whether the change amount must reach `min_change_amount`, so that
+        // the sender is left with a spendable change output.
+        let requires_min_change =
+            !proto.disable_change_output && !changeless && proto.min_change_amount != 0;
+        let initial_count = selected_count;
+
+        let (selected, weight_estimate, fee_estimate, change_amount_before_fee) = loop {
+            let selected = available[..selected_count].to_vec();
+
+            // Update protobuf structure with selected inputs.
+            proto.inputs = selected.clone();
+
+            // Update the `total_input` amount based on the selected inputs.
+            let total_input: u64 = proto.inputs.iter().map(|input| input.value).sum();
+
+            // Calculate the total input weight projection.
+            let input_weight: u64 = proto.inputs.iter().map(|input| input.weight_estimate).sum();
+
+            // Convert Protobuf structure to `bitcoin` crate native transaction,
+            // used for weight/fee calculation.
+            let tx = convert_proto_to_tx(&proto)?;
+
+            // Estimate of the change output weight.
+            let output_weight = if proto.disable_change_output || changeless {
+                0
+            } else {
+                // VarInt + script_pubkey size, rough estimate.
+                1 + proto.change_script_pubkey.len() as u64
+            };
+
+            // Calculate the full weight projection (base weight + input & output weight).
+            let weight_estimate = tx.weight().to_wu() + input_weight + output_weight;
+            let fee_estimate = (weight_estimate + 3) / 4 * proto.weight_base;
+
+            // Check if the fee projection would make the change amount negative
+            // (implying insufficient input amount).
+            let change_amount_before_fee = total_input - total_output;
+            if change_amount_before_fee < fee_estimate {
+                // The initial selection was sufficient, but the inputs added
+                // to raise the change do not cover their own fee.
+                if selected_count > initial_count {
+                    return Err(Error::from(Proto::Error::Error_min_change_not_reached));
+                }
+
+                return Err(Error::from(Proto::Error::Error_insufficient_inputs));
+            }
 
-        // Update the `total_input` amount based on the selected inputs.
-        let total_input: u64 = proto.inputs.iter().map(|input| input.value).sum();
+            // If the change amount falls below `min_change_amount`, we add
+            // the next available input (if any) to raise it. Inputs whose
+            // value does not cover the fee of spending them would lower the
+            // change instead, so those are skipped.
+            if requires_min_change
+                && change_amount_before_fee - fee_estimate < proto.min_change_amount
+            {
+                let economic = available[selected_count..].iter().position(|input| {
+                    let input_weight = TXIN_BASE_WEIGHT + input.weight_estimate;
+                    input.value > (input_weight + 3) / 4 * proto.weight_base
+                });
 
-        // Calculate the total input weight projection.
-        let input_weight: u64 = proto.inputs.iter().map(|input| input.weight_estimate).sum();
+                if let Some(offset) = economic {
+                    // Only skipped (uneconomic) inputs are moved back.
+                    available.swap(selected_count, selected_count + offset);
+                    selected_count += 1;
+                    continue;
+                }
 
-        // Convert Protobuf structure to `bitcoin` crate native transaction,
-        // used for weight/fee calculation.
-        let tx = convert_proto_to_tx(&proto)?;
+                return Err(Error::from(Proto::Error::Error_min_change_not_reached));
+            }
 
-        // Estimate of the change output weight.
-        let output_weight = if proto.disable_change_output {
-            0
-        } else {
-            // VarInt + script_pubkey size, rough estimate.
-            1 + proto.change_script_pubkey.len() as u64
+            break (
+                selected,
+                weight_estimate,
+                fee_estimate,
+                change_amount_before_fee,
+            );
         };
 
-        // Calculate the full weight projection (base weight + input & output weight).
-        let weight_estimate = tx.weight().to_wu() + input_weight + output_weight;
-        let fee_estimate = (weight_estimate + 3) / 4 * proto.weight_base;
-
-        // Check if the fee projection would make the change amount negative
-        // (implying insufficient input amount).
-        let change_amount_before_fee = total_input - total_output;
-        if change_amount_before_fee < fee_estimate {
-            return Err(Error::from(Proto::Error::Error_insufficient_inputs));
-        }
+        // The change amount that is donated to the miner fee, if it falls
+        // below the configured minimum or the selection is changeless.
+        let mut donated_change = 0;
+        let mut change_output_amount = 0;
 
         if !proto.disable_change_output {
             // The amount to be returned (if enabled).
             let change_amount = change_amount_before_fee - fee_estimate;
 
-            // Update the passed on protobuf structure by adding a change output
-            // (return to sender)
-            if change_amount != 0 {
+            if changeless {
+                // The selected inputs do not require a change output, any
+                // excess is donated to the miner fee.
+                donated_change = change_amount;
+            } else if change_amount != 0 && change_amount < proto.min_change {
+                // The change amount is below the configured minimum, so it's
+                // not worth creating a change output. The amount is donated to
+                // the miner fee instead.
+                donated_change = change_amount;
+            } else if change_amount != 0 {
+                // Update the passed on protobuf structure by adding a change output
+                // (return to sender)
                 proto.outputs.push(Proto::TxOut {
                     value: change_amount,
                     script_pubkey: proto.change_script_pubkey.clone(),
                 });
+                change_output_amount = change_amount;
             }
         }
 
+        // Sort the outputs, including the change output, according to BIP-69.
+        // The sort is stable, so outputs that are identical keep their order.
+        if proto.bip69_sort_outputs {
+            proto.outputs.sort_by(|a, b| {
+                (a.value, a.script_pubkey.as_ref()).cmp(&(b.value, b.script_pubkey.as_ref()))
+            });
+        }
+
         // Convert *updated* Protobuf structure to `bitcoin` crate native
         // transaction.
         let tx = convert_proto_to_tx(&proto)?;
 
         let mut cache = SighashCache::new(&tx);
 
-        let mut sighashes: Vec<(Vec<u8>, ProtoSigningMethod, Proto::SighashType)> = vec![];
+        let mut sighashes: Vec<(Vec<u8>, ProtoSigningMethod, Proto::SighashType, Vec<u8>)> = vec![];
 
         for (index, input) in proto.inputs.iter().enumerate() {
             match input.signing_method {
@@ -192,13 +307,35 @@ impl Compiler<StandardBitcoinContext> {
                     } else {
                         EcdsaSighashType::from_consensus(input.sighash_type as u32)
                     };
+                    // Note that for `SIGHASH_SINGLE` with an input index
+                    // greater or equal than the number of outputs, the
+                    // resulting sighash is `0x00..01` ("`SIGHASH_SINGLE` bug"),
+                    // which is reproduced as-is for legacy compatibility.
                     let sighash =
                         cache.legacy_signature_hash(index, script_pubkey, sighash_type.to_u32())?;
 
+                    // The unhashed preimage, if requested. Note that there is
+                    // no preimage for the `SIGHASH_SINGLE` bug.
+                    let mut preimage = vec![];
+                    if proto.include_preimages
+                        && cache
+                            .legacy_encode_signing_data_to(
+                                &mut preimage,
+                                index,
+                                script_pubkey,
+                                sighash_type.to_u32(),
+                            )
+                            .is_sighash_single_bug()
+                            .map_err(|_| Error::from(Proto::Error::Error_sighash_failed))?
+                    {
+                        preimage.clear();
+                    }
+
                     sighashes.push((
                         sighash.as_byte_array().to_vec(),
                         ProtoSigningMethod::Legacy,
                         input.sighash_type,
+                        preimage,
                     ));
                 },
                 // Use the Segwit hashing mechanism (e.g. P2WSH, P2WPKH).
@@ -217,10 +354,25 @@ impl Compiler<StandardBitcoinContext> {
                         sighash_type,
                     )?;
 
+                    // The unhashed (BIP-143) preimage, if requested.
+                    let mut preimage = vec![];
+                    if proto.include_preimages {
+                        cache
+                            .segwit_encode_signing_data_to(
+                                &mut preimage,
+                                index,
+                                script_pubkey.as_script(),
+                                input.value,
+                                sighash_type,
+                            )
+                            .map_err(|_| Error::from(Proto::Error::Error_sighash_failed))?;
+                    }
+
                     sighashes.push((
                         sighash.as_byte_array().to_vec(),
                         ProtoSigningMethod::Segwit,
                         input.sighash_type,
+                        preimage,
                     ));
                 },
                 // Use the Taproot hashing mechanism (e.g. P2TR key-path/script-path)
@@ -231,14 +383,14 @@ impl Compiler<StandardBitcoinContext> {
                         Some((
                             TapLeafHash::from_slice(input.leaf_hash.as_ref())
                                 .map_err(|_| Error::from(Proto::Error::Error_invalid_leaf_hash))?,
-                            // TODO: We might want to make this configurable?.
-                            0xFFFFFFFF,
+                            codesep_pos(input),
                         ))
                     };
 
                     // Note that `input.sighash_type = 0` is handled by the underlying library.
                     let sighash_type = TapSighashType::from_consensus_u8(input.sighash_type as u8)
                         .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash_type))?;
+                    check_taproot_sighash_single(index, sighash_type, proto.outputs.len())?;
 
                     let prevouts = proto
                         .inputs
@@ -257,10 +409,26 @@ impl Compiler<StandardBitcoinContext> {
                         sighash_type,
                     )?;
 
+                    // The unhashed (BIP-341) preimage, if requested.
+                    let mut preimage = vec![];
+                    if proto.include_preimages {
+                        cache
+                            .taproot_encode_signing_data_to(
+                                &mut preimage,
+                                index,
+                                &Prevouts::All(&prevouts),
+                                None,
+                                leaf_hash,
+                                sighash_type,
+                            )
+                            .map_err(|_| Error::from(Proto::Error::Error_sighash_failed))?;
+                    }
+
                     sighashes.push((
                         sighash.as_byte_array().to_vec(),
                         ProtoSigningMethod::TaprootAll,
                         input.sighash_type,
+                        preimage,
                     ));
                 },
                 ProtoSigningMethod::TaprootOnePrevout => {
@@ -270,14 +438,14 @@ impl Compiler<StandardBitcoinContext> {
                         Some((
                             TapLeafHash::from_slice(input.leaf_hash.as_ref())
                                 .map_err(|_| Error::from(Proto::Error::Error_invalid_leaf_hash))?,
-                            // TODO: We might want to make this configurable?.
-                            0xFFFFFFFF,
+                            codesep_pos(input),
                         ))
                     };
 
                     // Note that `input.sighash_type = 0` is handled by the underlying library.
                     let sighash_type = TapSighashType::from_consensus_u8(input.sighash_type as u8)
                         .map_err(|_| Error::from(Proto::Error::Error_invalid_sighash_type))?;
+                    check_taproot_sighash_single(index, sighash_type, proto.outputs.len())?;
 
                     let prevouts = Prevouts::One(
                         index,
@@ -295,10 +463,26 @@ impl Compiler<StandardBitcoinContext> {
                         sighash_type,
                     )?;
 
+                    // The unhashed (BIP-341) preimage, if requested.
+                    let mut preimage = vec![];
+                    if proto.include_preimages {
+                        cache
+                            .taproot_encode_signing_data_to(
+                                &mut preimage,
+                                index,
+                                &prevouts,
+                                None,
+                                leaf_hash,
+                                sighash_type,
+                            )
+                            .map_err(|_| Error::from(Proto::Error::Error_sighash_failed))?;
+                    }
+
                     sighashes.push((
                         sighash.as_byte_array().to_vec(),
                         ProtoSigningMethod::TaprootOnePrevout,
                         input.sighash_type,
+                        preimage,
                     ));
                 },
             }
@@ -314,10 +498,11 @@ impl Compiler<StandardBitcoinContext> {
             txid: txid.into(),
             sighashes: sighashes
                 .into_iter()
-                .map(|(sighash, method, sighash_type)| Proto::Sighash {
+                .map(|(sighash, method, sighash_type, preimage)| Proto::Sighash {
                     sighash: sighash.into(),
                     signing_method: method,
                     sighash_type,
+                    preimage: preimage.into(),
                 })
                 .collect(),
             inputs: selected,
@@ -331,6 +516,8 @@ impl Compiler<StandardBitcoinContext> {
                 .collect(),
             weight_estimate,
             fee_estimate,
+            donated_change,
+            change_amount: change_output_amount,
         })
     }
 
@@ -373,14 +560,19 @@ impl Compiler<StandardBitcoinContext> {
             });
         }
 
-        // Encode the transaction.
+        // Encode the transaction. Note that the Segwit marker and flag are
+        // only included if at least one input carries witness data, so a
+        // transaction consisting of Segwit-capable inputs with empty
+        // witnesses only is encoded in the legacy format.
         let mut buffer = vec![];
         tx.consensus_encode(&mut buffer)
             .map_err(|_| Error::from(Proto::Error::Error_failed_encoding))?;
 
-        // The transaction identifier, which we represent in
-        // non-reversed/non-network order.
+        // The transaction identifiers, which we represent in
+        // non-reversed/non-network order. Without any witness data, the wtxid
+        // equals the txid.
         let txid: Vec<u8> = tx.txid().as_byte_array().iter().copied().rev().collect();
+        let wtxid: Vec<u8> = tx.wtxid().as_byte_array().iter().copied().rev().collect();
 
         Ok(Proto::SerializedTransaction {
             error: Proto::Error::OK,
@@ -388,6 +580,7 @@ impl Compiler<StandardBitcoinContext> {
             txid: txid.into(),
             weight: tx.weight().to_wu(),
             fee: tx.weight().to_vbytes_ceil() * proto.weight_base,
+            wtxid: wtxid.into(),
         })
     }
 }
@@ -424,6 +617,303 @@ fn convert_proto_to_tx<'a>(proto: &'a Proto::SigningInput<'a>) -> Result<Transac
     Ok(tx)
 }
 
+// Sums up the given amounts, failing on overflow rather than wrapping.
+fn checked_total(amounts: impl Iterator<Item = u64>) -> Result<u64> {
+    let mut total: u64 = 0;
+    for amount in amounts {
+        total = total
+            .checked_add(amount)
+            .ok_or_else(|| Error::from(Proto::Error::Error_amount_overflow))?;
+    }
+
+    Ok(total)
+}
+
+// Unlike legacy inputs, Taproot does not reproduce the `SIGHASH_SINGLE` bug:
+// signing an input without a corresponding output is invalid (BIP-341).
+fn check_taproot_sighash_single(
+    index: usize,
+    sighash_type: TapSighashType,
+    outputs: usize,
+) -> Result<()> {
+    let is_single = matches!(
+        sighash_type,
+        TapSighashType::Single | TapSighashType::SinglePlusAnyoneCanPay
+    );
+
+    if is_single && index >= outputs {
+        return Err(Error::from(
+            Proto::Error::Error_sighash_single_missing_output,
+        ));
+    }
+
+    Ok(())
+}
+
+// The position of the last executed `OP_CODESEPARATOR` of a script-path spend,
+// `0xFFFFFFFF` if none (BIP-342).
+fn codesep_pos(input: &Proto::TxIn<'_>) -> u32 {
+    input
+        .codesep_pos
+        .as_ref()
+        .map_or(0xFFFFFFFF, |codesep| codesep.position)
+}
+
+// Searches for a subset of inputs whose total amount covers the outputs and
+// the fee without requiring a change output. The excess may not exceed the
+// fee of the change output, which is paid as fee instead. This is a depth-first
+// (branch and bound) search over the inputs in descending order of value.
+// Returns the indices of the selected inputs in the given order.
+fn select_changeless(
+    proto: &Proto::SigningInput<'_>,
+    total_output: u64,
+) -> Result<Option<Vec<usize>>> {
+    if proto.inputs.is_empty() {
+        return Ok(None);
+    }
+
+    let base_weight = weight_without_inputs(proto)?;
+    let cost_of_change = (change_output_weight(proto) + 3) / 4 * proto.weight_base;
+    let candidates = selection_candidates(proto);
+
+    let search = ChangelessSearch {
+        candidates: &candidates,
+        total_output,
+        base_weight,
+        weight_base: proto.weight_base,
+        cost_of_change,
+    };
+
+    let mut selected = vec![];
+    let mut tries = CHANGELESS_MAX_TRIES;
+    if !search.run(0, 0, 0, &mut selected, &mut tries) {
+        return Ok(None);
+    }
+
+    let mut indices: Vec<usize> = selected.into_iter().map(|pos| candidates[pos].0).collect();
+    indices.sort();
+
+    Ok(Some(indices))
+}
+
+// Searches for a subset of inputs whose change amount (after paying the fee,
+// including the change output) is closest to `target_change`. This is a
+// depth-first search over the inputs in descending order of value, in the
+// same manner as `select_changeless`. Subsets whose change would be donated
+// (below `min_change`) or would not reach `min_change_amount` are skipped,
+// since the remaining inputs are dropped once a subset is selected. Returns
+// the indices of the selected inputs in the given order.
+fn select_target_change(
+    proto: &Proto::SigningInput<'_>,
+    total_output: u64,
+) -> Result<Option<Vec<usize>>> {
+    if proto.inputs.is_empty() {
+        return Ok(None);
+    }
+
+    let candidates = selection_candidates(proto);
+
+    let search = TargetChangeSearch {
+        candidates: &candidates,
+        total_output,
+        base_weight: weight_without_inputs(proto)? + change_output_weight(proto),
+        weight_base: proto.weight_base,
+        target_change: proto.target_change,
+        min_change: proto.min_change.max(proto.min_change_amount),
+    };
+
+    let mut best = None;
+    let mut tries = TARGET_CHANGE_MAX_TRIES;
+    search.run(0, 0, 0, &mut vec![], &mut best, &mut tries);
+
+    Ok(best.map(|(_, selected)| {
+        let mut indices: Vec<usize> = selected.into_iter().map(|pos| candidates[pos].0).collect();
+        indices.sort();
+        indices
+    }))
+}
+
+// Weight of the transaction without the non-witness part of the inputs.
+// Note that a transaction without any inputs would be serialized in the
+// Segwit format, hence it's derived from the full transaction.
+fn weight_without_inputs(proto: &Proto::SigningInput<'_>) -> Result<u64> {
+    Ok(convert_proto_to_tx(proto)?.weight().to_wu() - TXIN_BASE_WEIGHT * proto.inputs.len() as u64)
+}
+
+// Weight of the change output (value + length + scriptPubkey).
+fn change_output_weight(proto: &Proto::SigningInput<'_>) -> u64 {
+    (8 + 1 + proto.change_script_pubkey.len() as u64) * 4
+}
+
+// The (index, value, weight) of each input, including the non-witness part of
+// the weight, in descending order of value.
+fn selection_candidates(proto: &Proto::SigningInput<'_>) -> Vec<(usize, u64, u64)> {
+    let mut candidates: Vec<(usize, u64, u64)> = proto
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(index, input)| (index, input.value, TXIN_BASE_WEIGHT + input.weight_estimate))
+        .collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1));
+
+    candidates
+}
+
+// Weight of the non-witness part of an input with an empty scriptSig (txid,
+// vout, sequence and the length of the scriptSig).
+const TXIN_BASE_WEIGHT: u64 = 4 * (32 + 4 + 4 + 1);
+
+// The maximum number of branches explored by the changeless input selection.
+const CHANGELESS_MAX_TRIES: usize = 100_000;
+
+// The maximum number of branches explored by the target change input
+// selection.
+const TARGET_CHANGE_MAX_TRIES: usize = 100_000;
+
+struct ChangelessSearch<'a> {
+    // The (index, value, weight) of each input, in descending order of value.
+    candidates: &'a [(usize, u64, u64)],
+    total_output: u64,
+    base_weight: u64,
+    weight_base: u64,
+    cost_of_change: u64,
+}
+
+impl ChangelessSearch<'_> {
+    fn run(
+        &self,
+        pos: usize,
+        value: u64,
+        weight: u64,
+        selected: &mut Vec<usize>,
+        tries: &mut usize,
+    ) -> bool {
+        if *tries == 0 {
+            return false;
+        }
+        *tries -= 1;
+
+        if !selected.is_empty() {
+            let fee = (self.base_weight + weight + 3) / 4 * self.weight_base;
+            let target = self.total_output + fee;
+
+            if value >= target {
+                // Adding more inputs only increases the excess.
+                return value - target <= self.cost_of_change;
+            }
+        }
+
+        // Not enough value left to reach the target.
+        let remaining: u64 = self.candidates[pos..]
+            .iter()
+            .map(|(_, value, _)| value)
+            .sum();
+        if pos == self.candidates.len() || value + remaining < self.total_output {
+            return false;
+        }
+
+        let (_, candidate_value, candidate_weight) = self.candidates[pos];
+
+        // Branch including the candidate.
+        selected.push(pos);
+        if self.run(
+            pos + 1,
+            value + candidate_value,
+            weight + candidate_weight,
+            selected,
+            tries,
+        ) {
+            return true;
+        }
+        selected.pop();
+
+        // Branch excluding the candidate.
+        self.run(pos + 1, value, weight, selected, tries)
+    }
+}
+
+struct TargetChangeSearch<'a> {
+    // The (index, value, weight) of each input, in descending order of value.
+    candidates: &'a [(usize, u64, u64)],
+    total_output: u64,
+    // Weight of the transaction without the inputs, including the change
+    // output.
+    base_weight: u64,
+    weight_base: u64,
+    target_change: u64,
+    // The minimum change amount of a selection.
+    min_change: u64,
+}
+
+impl TargetChangeSearch<'_> {
+    // Records the selection with the smallest deviation from the target
+    // change in `best`, along with the deviation.
+    fn run(
+        &self,
+        pos: usize,
+        value: u64,
+        weight: u64,
+        selected: &mut Vec<usize>,
+        best: &mut Option<(u64, Vec<usize>)>,
+        tries: &mut usize,
+    ) {
+        if *tries == 0 {
+            return;
+        }
+        *tries -= 1;
+
+        if !selected.is_empty() {
+            let fee = (self.base_weight + weight + 3) / 4 * self.weight_base;
+            let required = self.total_output + fee;
+
+            if value >= required {
+                let change = value - required;
+                let deviation = change.abs_diff(self.target_change);
+
+                if change >= self.min_change
+                    && best
+                        .as_ref()
+                        .map_or(true, |(best_deviation, _)| deviation < *best_deviation)
+                {
+                    *best = Some((deviation, selected.clone()));
+                }
+
+                // Adding more inputs only moves the change further away from
+                // the target.
+                if change >= self.target_change.max(self.min_change) {
+                    return;
+                }
+            }
+        }
+
+        // Not enough value left to cover the outputs.
+        let remaining: u64 = self.candidates[pos..]
+            .iter()
+            .map(|(_, value, _)| value)
+            .sum();
+        if pos == self.candidates.len() || value + remaining < self.total_output {
+            return;
+        }
+
+        let (_, candidate_value, candidate_weight) = self.candidates[pos];
+
+        // Branch including the candidate.
+        selected.push(pos);
+        self.run(
+            pos + 1,
+            value + candidate_value,
+            weight + candidate_weight,
+            selected,
+            best,
+            tries,
+        );
+        selected.pop();
+
+        // Branch excluding the candidate.
+        self.run(pos + 1, value, weight, selected, best, tries);
+    }
+}
+
 // Convenience function to retreive the lock time. If none is provided, the
 // default lock time is used (immediately spendable).
 fn lock_time_from_proto(proto: &Option<Proto::LockTime>) -> Result<LockTime> {
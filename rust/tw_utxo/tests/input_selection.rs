@@ -54,6 +54,7 @@ fn input_selector_all() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -83,6 +84,7 @@ fn input_selector_all() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -148,6 +150,7 @@ fn input_selector_all_insufficient_inputs() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -171,6 +174,7 @@ fn input_selector_all_insufficient_inputs() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -224,6 +228,7 @@ fn input_selector_one_input_required() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -250,6 +255,7 @@ fn input_selector_one_input_required() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -320,6 +326,7 @@ fn input_selector_two_inputs_required() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -349,6 +356,7 @@ fn input_selector_two_inputs_required() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -406,6 +414,7 @@ fn input_selector_one_input_cannot_cover_fees() {
         change_script_pubkey: Default::default(),
         // DISABLE change output.
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -430,6 +439,7 @@ fn input_selector_one_input_cannot_cover_fees() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -474,6 +484,7 @@ fn input_selector_exact_balance_no_change() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -524,6 +535,7 @@ fn input_selector_empty_script_bufs() {
         change_script_pubkey: Default::default(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -581,6 +593,7 @@ fn input_selector_select_ascending() {
         change_script_pubkey: change_script.as_bytes().into(),
         // ENABLE change output.
         disable_change_output: false,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
@@ -595,3 +608,251 @@ fn input_selector_select_ascending() {
     assert_eq!(output.outputs.len(), 2);
     assert_eq!(output.outputs[0], out1);
 }
+
+#[test]
+fn input_selector_select_changeless() {
+    // Reusing the txid is fine here, although in production this would mark the transaction invalid.
+    let txid = txid_rev("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b");
+
+    let tx1 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 5_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx2 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 3_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx3 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        // Together with `tx2`, covers the exact output value + projected fee.
+        value: 1_000 + (404 + 3) / 4 * WEIGHT_BASE,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx4 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 700,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+
+    let out1 = Proto::TxOut {
+        value: 4_000,
+        script_pubkey: Default::default(),
+    };
+
+    let change_script = change_output();
+    let signing = Proto::SigningInput {
+        version: 2,
+        lock_time: Default::default(),
+        inputs: vec![tx1.clone(), tx2.clone(), tx3.clone(), tx4.clone()],
+        outputs: vec![out1.clone()],
+        input_selector: Proto::InputSelector::SelectChangeless,
+        weight_base: WEIGHT_BASE,
+        change_script_pubkey: change_script.as_bytes().into(),
+        // ENABLE change output.
+        disable_change_output: false,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing.clone());
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.sighashes.len(), 2);
+    assert_eq!(output.weight_estimate, 404);
+    assert_eq!(output.fee_estimate, (404 + 3) / 4 * WEIGHT_BASE);
+    assert_eq!(output.donated_change, 0);
+
+    // The two inputs that exactly match the output value + fee are selected,
+    // although the first input would cover the output on its own.
+    assert_eq!(output.inputs.len(), 2);
+    assert_eq!(output.inputs[0], tx2);
+    assert_eq!(output.inputs[1], tx3);
+
+    // NO change output
+    assert_eq!(output.outputs.len(), 1);
+    assert_eq!(output.outputs[0], out1);
+
+    // Without an exact match, inputs are selected in ascending order and a
+    // change output is created.
+    let signing = Proto::SigningInput {
+        inputs: vec![tx1.clone(), tx4.clone()],
+        ..signing
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.inputs.len(), 2);
+    assert_eq!(output.inputs[0], tx4);
+    assert_eq!(output.inputs[1], tx1);
+    assert_eq!(output.outputs.len(), 2);
+}
+
+#[test]
+fn input_selector_select_target_change() {
+    // Reusing the txid is fine here, although in production this would mark the transaction invalid.
+    let txid = txid_rev("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b");
+
+    let tx1 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        // Covers the output value + projected fee without a change output.
+        value: 100_150,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx2 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 150_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+    let tx3 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 60_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+
+    let out1 = Proto::TxOut {
+        value: 100_000,
+        script_pubkey: Default::default(),
+    };
+
+    let change_script = change_output();
+    let signing = Proto::SigningInput {
+        version: 2,
+        lock_time: Default::default(),
+        inputs: vec![tx1.clone(), tx2.clone(), tx3.clone()],
+        outputs: vec![out1.clone()],
+        input_selector: Proto::InputSelector::SelectTargetChange,
+        weight_base: WEIGHT_BASE,
+        change_script_pubkey: change_script.as_bytes().into(),
+        // ENABLE change output.
+        disable_change_output: false,
+        target_change: 50_000,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing.clone());
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.weight_estimate, 266);
+    assert_eq!(output.fee_estimate, (266 + 3) / 4 * WEIGHT_BASE);
+
+    // The input whose change is closest to the target is selected, rather
+    // than the changeless input.
+    assert_eq!(output.inputs.len(), 1);
+    assert_eq!(output.inputs[0], tx2);
+
+    // The achieved change falls short of the target by the fee.
+    assert_eq!(output.outputs.len(), 2);
+    assert_eq!(output.outputs[0], out1);
+    assert_eq!(output.outputs[1].value, 49_866);
+    assert_eq!(output.change_amount, 49_866);
+
+    // Whereas the changeless selection picks the first input.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(Proto::SigningInput {
+        input_selector: Proto::InputSelector::SelectChangeless,
+        ..signing.clone()
+    });
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.inputs.len(), 1);
+    assert_eq!(output.inputs[0], tx1);
+    assert_eq!(output.outputs.len(), 1);
+    assert_eq!(output.change_amount, 0);
+
+    // Without a change output, inputs are selected in ascending order.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(Proto::SigningInput {
+        disable_change_output: true,
+        ..signing.clone()
+    });
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.inputs.len(), 2);
+    assert_eq!(output.inputs[0], tx3);
+    assert_eq!(output.inputs[1], tx1);
+    assert_eq!(output.change_amount, 0);
+
+    // The change of the closest subset would not reach the minimum change
+    // amount, so the closest subset above the minimum is selected instead.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(Proto::SigningInput {
+        min_change_amount: 55_000,
+        ..signing.clone()
+    });
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.inputs.len(), 2);
+    assert_eq!(output.inputs[0], tx1);
+    assert_eq!(output.inputs[1], tx3);
+    assert_eq!(output.change_amount, 59_934);
+
+    // Same for change that would be donated.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(Proto::SigningInput {
+        min_change: 55_000,
+        ..signing.clone()
+    });
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.inputs.len(), 2);
+    assert_eq!(output.change_amount, 59_934);
+
+    // The target change must be set.
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(Proto::SigningInput {
+        target_change: 0,
+        ..signing
+    });
+    assert_eq!(output.error, Proto::Error::Error_missing_target_change);
+}
+
+#[test]
+fn input_selector_output_amount_overflow() {
+    let txid = txid_rev("1e1cdc48aa990d7e154a161d5b5f1cad737742e97d2712ab188027bb42e6e47b");
+
+    let tx1 = Proto::TxIn {
+        txid: txid.as_slice().into(),
+        value: 1_000,
+        sequence: u32::MAX,
+        ..Default::default()
+    };
+
+    // Each value is near `u64::MAX`, the sum (if wrapped) would be smaller
+    // than the input amount.
+    let out1 = Proto::TxOut {
+        value: u64::MAX - 100,
+        script_pubkey: Default::default(),
+    };
+    let out2 = Proto::TxOut {
+        value: 200,
+        script_pubkey: Default::default(),
+    };
+    let out3 = Proto::TxOut {
+        value: u64::MAX,
+        script_pubkey: Default::default(),
+    };
+
+    let signing = Proto::SigningInput {
+        version: 2,
+        inputs: vec![tx1.clone()],
+        outputs: vec![out1.clone(), out2],
+        input_selector: Proto::InputSelector::UseAll,
+        weight_base: WEIGHT_BASE,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
+    assert_eq!(output.error, Proto::Error::Error_amount_overflow);
+    assert_eq!(output.sighashes.len(), 0);
+
+    let signing = Proto::SigningInput {
+        version: 2,
+        inputs: vec![tx1],
+        outputs: vec![out1, out3],
+        input_selector: Proto::InputSelector::UseAll,
+        weight_base: WEIGHT_BASE,
+        disable_change_output: true,
+        ..Default::default()
+    };
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
+    assert_eq!(output.error, Proto::Error::Error_amount_overflow);
+}
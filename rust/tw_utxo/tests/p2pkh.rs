@@ -30,6 +30,7 @@ fn sighash_input_p2pkh_output_p2pkh() {
             signing_method: Proto::SigningMethod::Legacy,
             weight_estimate: 1,
             leaf_hash: Default::default(),
+            codesep_pos: None,
         }],
         outputs: vec![Proto::TxOut {
             value: 50 * 100_000_000 - 1_000_000,
@@ -39,6 +40,7 @@ fn sighash_input_p2pkh_output_p2pkh() {
         weight_base: 1,
         change_script_pubkey: Default::default(),
         disable_change_output: true,
+        ..Default::default()
     };
 
     let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
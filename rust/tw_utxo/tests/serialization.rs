@@ -0,0 +1,91 @@
+mod common;
+use common::{txid_rev, witness_pubkey_hash};
+
+use bitcoin::ScriptBuf;
+use tw_encoding::hex;
+use tw_proto::Utxo::Proto;
+use tw_utxo::compiler::{Compiler, StandardBitcoinContext};
+
+// Convenience function, creates a PreSerialization with two inputs carrying
+// the given witness items.
+fn pre_serialization(witness_items: Vec<Vec<u8>>) -> Proto::PreSerialization<'static> {
+    let wpubkey_hash = witness_pubkey_hash("60cda7b50f14c152d7401c28ae773c698db92373");
+    let output_script_pubkey = ScriptBuf::new_v0_p2wpkh(&wpubkey_hash);
+
+    let txid = txid_rev("858e450a1da44397bde05ca2f8a78510d74c623cc2f69736a8b3fbfadc161f6e");
+
+    let inputs = (0..2)
+        .map(|vout| Proto::TxInClaim {
+            txid: txid.clone().into(),
+            vout,
+            sequence: u32::MAX,
+            script_sig: Default::default(),
+            // Only the first input carries witness data (if any).
+            witness_items: if vout == 0 {
+                witness_items.iter().cloned().map(Into::into).collect()
+            } else {
+                vec![]
+            },
+        })
+        .collect();
+
+    Proto::PreSerialization {
+        version: 2,
+        lock_time: Default::default(),
+        inputs,
+        outputs: vec![Proto::TxOut {
+            value: 50 * 100_000_000,
+            script_pubkey: output_script_pubkey.to_bytes().into(),
+        }],
+        weight_base: 1,
+    }
+}
+
+#[test]
+fn serialize_empty_witnesses_legacy_format() {
+    let output = Compiler::<StandardBitcoinContext>::compile(pre_serialization(vec![]));
+    assert_eq!(output.error, Proto::Error::OK);
+
+    // No Segwit marker and flag after the version.
+    let encoded = hex::encode(output.encoded.as_ref(), false);
+    assert!(encoded.starts_with("0200000002"));
+
+    assert_eq!(output.txid, output.wtxid);
+}
+
+#[test]
+fn serialize_any_witness_segwit_format() {
+    let output =
+        Compiler::<StandardBitcoinContext>::compile(pre_serialization(vec![vec![0xab; 72]]));
+    assert_eq!(output.error, Proto::Error::OK);
+
+    // Segwit marker and flag after the version.
+    let encoded = hex::encode(output.encoded.as_ref(), false);
+    assert!(encoded.starts_with("020000000001"));
+
+    assert_ne!(output.txid, output.wtxid);
+}
+
+#[test]
+fn serialize_output_value_little_endian() {
+    let mut proto = pre_serialization(vec![]);
+    proto.outputs = vec![Proto::TxOut {
+        value: 1,
+        script_pubkey: Default::default(),
+    }];
+
+    let output = Compiler::<StandardBitcoinContext>::compile(proto);
+    assert_eq!(output.error, Proto::Error::OK);
+
+    // Output count, 8-byte little-endian value, empty scriptPubkey and the
+    // lock time.
+    let encoded = hex::encode(output.encoded.as_ref(), false);
+    assert!(encoded.ends_with(&["01", "0100000000000000", "00", "00000000"].concat()));
+
+    // 50 BTC (0x012a05f200).
+    let output = Compiler::<StandardBitcoinContext>::compile(pre_serialization(vec![]));
+    assert_eq!(output.error, Proto::Error::OK);
+
+    let encoded = hex::encode(output.encoded.as_ref(), false);
+    assert!(encoded.contains("0100f2052a0100000016"));
+}
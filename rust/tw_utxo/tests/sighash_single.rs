@@ -0,0 +1,84 @@
+mod common;
+use common::{pubkey_hash_from_hex, txid_rev, untweaked_pubkey};
+
+use bitcoin::ScriptBuf;
+use tw_proto::Utxo::Proto;
+use tw_utxo::compiler::{Compiler, StandardBitcoinContext};
+
+// Convenience function, creates a signing input with four inputs of the given
+// script and signing method, but only a single output. The last input signs
+// with `SIGHASH_SINGLE`.
+fn signing_input(
+    script_pubkey: &ScriptBuf,
+    signing_method: Proto::SigningMethod,
+) -> Proto::SigningInput<'static> {
+    let txid = txid_rev("c50563913e5a838f937c94232f5a8fc74e58b629fae41dfdffcc9a70f833b53a");
+
+    let inputs = (0..4)
+        .map(|vout| Proto::TxIn {
+            txid: txid.clone().into(),
+            vout,
+            value: 100_000,
+            sequence: u32::MAX,
+            script_pubkey: script_pubkey.to_bytes().into(),
+            sighash_type: if vout == 3 {
+                Proto::SighashType::Single
+            } else {
+                Proto::SighashType::All
+            },
+            signing_method,
+            weight_estimate: 1,
+            leaf_hash: Default::default(),
+            codesep_pos: None,
+        })
+        .collect();
+
+    Proto::SigningInput {
+        version: 2,
+        lock_time: Default::default(),
+        inputs,
+        outputs: vec![Proto::TxOut {
+            value: 100_000,
+            script_pubkey: script_pubkey.to_bytes().into(),
+        }],
+        input_selector: Proto::InputSelector::UseAll,
+        weight_base: 1,
+        change_script_pubkey: Default::default(),
+        disable_change_output: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn sighash_single_bug_legacy() {
+    let pubkey_hash = pubkey_hash_from_hex("a0cd6d6e2f9804351ba4b722b708bc2fd3229a5a");
+    let script_pubkey = ScriptBuf::new_p2pkh(&pubkey_hash);
+
+    let signing = signing_input(&script_pubkey, Proto::SigningMethod::Legacy);
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
+    assert_eq!(output.error, Proto::Error::OK);
+    assert_eq!(output.sighashes.len(), 4);
+
+    // The input without a corresponding output signs the special hash `1`.
+    let mut one = [0; 32];
+    one[0] = 1;
+    assert_eq!(output.sighashes[3].sighash.as_ref(), one.as_slice());
+    assert_ne!(output.sighashes[0].sighash.as_ref(), one.as_slice());
+}
+
+#[test]
+fn sighash_single_missing_output_taproot() {
+    let untweaked_pubkey =
+        untweaked_pubkey("02c0938cf377023dfde55e9c96b3cff4ca8894fb6b5d2009006bd43c0bff69cac9");
+    let script_pubkey =
+        ScriptBuf::new_v1_p2tr(&secp256k1::Secp256k1::new(), untweaked_pubkey, None);
+
+    let signing = signing_input(&script_pubkey, Proto::SigningMethod::TaprootAll);
+
+    let output = Compiler::<StandardBitcoinContext>::preimage_hashes(signing);
+    assert_eq!(
+        output.error,
+        Proto::Error::Error_sighash_single_missing_output
+    );
+}
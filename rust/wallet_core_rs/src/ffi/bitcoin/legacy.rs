@@ -42,6 +42,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_p2pkh_script(
                 to_address: ProtoPubkeyOrHash::pubkey(recipient.to_bytes().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -83,6 +84,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_p2wpkh_script(
                 to_address: ProtoPubkeyOrHash::pubkey(recipient.to_bytes().into()),
             }),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -121,6 +123,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_p2tr_key_path_script(
         to_recipient: ProtoOutputRecipient::builder(Proto::mod_Output::OutputBuilder {
             variant: ProtoOutputBuilder::p2tr_key_path(recipient.to_bytes().into()),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -175,6 +178,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_brc20_transfer_inscription(
                 },
             ),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -233,9 +237,11 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_nft_inscription(
                     inscribe_to: recipient.to_bytes().into(),
                     mime_type: mime_type.into(),
                     payload: payload.into(),
+                    parent: None,
                 },
             ),
         }),
+        ..Default::default()
     };
 
     let res = try_or_else!(
@@ -254,6 +260,72 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_build_nft_inscription(
     CByteArray::from(serialized)
 }
 
+#[no_mangle]
+#[deprecated]
+// Builds the `BitcoinV2.proto` signing input of the reveal transaction, which
+// spends the BRC20 transfer inscription as returned by
+// `tw_bitcoin_legacy_build_brc20_transfer_inscription` (the Taproot payload is
+// carried in `spendingScript`) to the destination output, such as returned by
+// `tw_bitcoin_legacy_build_p2wpkh_script`. The private key must be set by the
+// caller.
+pub unsafe extern "C" fn tw_bitcoin_legacy_build_brc20_reveal_signing_input(
+    // The serialized legacy `TransactionOutput` of the inscription.
+    inscription: *const u8,
+    inscription_len: usize,
+    // The txid of the commit transaction, in REVERSED order.
+    txid: *const u8,
+    txid_len: usize,
+    vout: u32,
+    pubkey: *const u8,
+    pubkey_len: usize,
+    // The serialized legacy `TransactionOutput` of the destination.
+    destination: *const u8,
+    destination_len: usize,
+    sat_vb: u64,
+) -> CByteArray {
+    // Convert Recipient
+    let slice = try_or_else!(
+        CByteArrayRef::new(pubkey, pubkey_len).as_slice(),
+        CByteArray::null
+    );
+    let my_pubkey = try_or_else!(PublicKey::from_slice(slice), CByteArray::null);
+
+    let txid = try_or_else!(
+        CByteArrayRef::new(txid, txid_len).as_slice(),
+        CByteArray::null
+    );
+
+    // Decode the legacy outputs.
+    let inscription = try_or_else!(
+        CByteArrayRef::new(inscription, inscription_len).as_slice(),
+        CByteArray::null
+    );
+    let inscription: LegacyProto::TransactionOutput =
+        try_or_else!(tw_proto::deserialize(inscription), CByteArray::null);
+
+    let destination = try_or_else!(
+        CByteArrayRef::new(destination, destination_len).as_slice(),
+        CByteArray::null
+    );
+    let destination: LegacyProto::TransactionOutput =
+        try_or_else!(tw_proto::deserialize(destination), CByteArray::null);
+
+    let signing = try_or_else!(
+        tw_bitcoin::modules::legacy::brc20_reveal_signing_input(
+            my_pubkey,
+            &inscription,
+            txid,
+            vout,
+            &destination,
+            sat_vb,
+        ),
+        CByteArray::null
+    );
+
+    let serialized = tw_proto::serialize(&signing).expect("failed to serialize signing input");
+    CByteArray::from(serialized)
+}
+
 #[deprecated]
 #[no_mangle]
 pub unsafe extern "C" fn tw_bitcoin_legacy_calculate_transaction_fee(
@@ -299,7 +371,7 @@ pub unsafe extern "C" fn tw_bitcoin_legacy_taproot_build_and_sign_transaction(
         };
 
         let serialized = tw_proto::serialize(&error).expect("failed to serialize error message");
-        return CByteArray::from(serialized)
+        return CByteArray::from(serialized);
     };
 
     // Serialize SigningOutput and return.
@@ -6,3 +6,4 @@
 
 #[cfg(feature = "bitcoin-legacy")]
 pub mod legacy;
+pub mod transfer;
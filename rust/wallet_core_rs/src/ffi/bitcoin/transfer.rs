@@ -0,0 +1,74 @@
+// Copyright © 2017-2023 Trust Wallet.
+//
+// This file is part of Trust. The full Trust copyright notice, including
+// terms governing use, modification, and redistribution, is contained in the
+// file LICENSE at the root of the source code distribution tree.
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::{c_char, CStr};
+use tw_memory::ffi::c_byte_array::CByteArray;
+use tw_memory::ffi::c_byte_array_ref::CByteArrayRef;
+use tw_misc::try_or_else;
+use tw_proto::BitcoinV2::Proto;
+
+// NOTE: The tests for those APIs can be found in `tw_bitcoin`.
+
+#[no_mangle]
+// Builds and signs a simple transfer to the given recipients, where the
+// inputs are selected automatically and the remainder minus the fee is
+// returned to the change address. Returns the serialized `SigningOutput`.
+pub unsafe extern "C" fn tw_bitcoin_build_simple_transfer(
+    private_key: *const u8,
+    private_key_len: usize,
+    // The serialized `SimpleTransferUtxos`.
+    utxos: *const u8,
+    utxos_len: usize,
+    // The serialized `SimpleTransferRecipients`.
+    recipients: *const u8,
+    recipients_len: usize,
+    change_address: *const c_char,
+    sat_vb: u64,
+    // The `Network` of the addresses.
+    network: i32,
+) -> CByteArray {
+    let private_key = try_or_else!(
+        CByteArrayRef::new(private_key, private_key_len).as_slice(),
+        CByteArray::null
+    );
+
+    let utxos = CByteArrayRef::new(utxos, utxos_len)
+        .to_vec()
+        .unwrap_or_default();
+    let utxos: Proto::SimpleTransferUtxos =
+        try_or_else!(tw_proto::deserialize(&utxos), CByteArray::null);
+
+    let recipients = CByteArrayRef::new(recipients, recipients_len)
+        .to_vec()
+        .unwrap_or_default();
+    let recipients: Proto::SimpleTransferRecipients =
+        try_or_else!(tw_proto::deserialize(&recipients), CByteArray::null);
+
+    let change_address = match CStr::from_ptr(change_address).to_str() {
+        Ok(input) => input,
+        Err(_) => return CByteArray::null(),
+    };
+
+    let recipients: Vec<(&str, u64)> = recipients
+        .recipients
+        .iter()
+        .map(|recipient| (recipient.address.as_ref(), recipient.amount))
+        .collect();
+
+    let signed = tw_bitcoin::modules::transfer::build_and_sign(
+        utxos.utxos,
+        &recipients,
+        change_address,
+        sat_vb,
+        Proto::Network::from(network),
+        private_key,
+    );
+
+    let serialized = tw_proto::serialize(&signed).expect("failed to serialize signed transaction");
+    CByteArray::from(serialized)
+}